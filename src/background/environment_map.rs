@@ -0,0 +1,232 @@
+use std::f64::consts::PI;
+
+use crate::{color::Color, distribution::Distribution2D, random::random, vec3::Vec3};
+
+use super::{Background, HdrImage};
+
+/// A background made of an equirectangular (latitude-longitude) image of the
+/// surroundings, such as an HDR photograph of a studio or the sky.
+///
+/// The middle of the image is seen toward -Z, its top straight up.
+pub struct EnvironmentMap {
+    image: HdrImage,
+    /// The distribution of the pixels, in proportion to the radiance they
+    /// send.
+    distribution: Distribution2D,
+    /// The rotation around the Y axis, in radians.
+    rotation: f64,
+    intensity: f64,
+}
+
+impl EnvironmentMap {
+    /// Constructs a new `EnvironmentMap` from an equirectangular `image`.
+    pub fn new(image: HdrImage) -> EnvironmentMap {
+        // Rows near the poles cover a smaller solid angle, so they are sampled
+        // less often.
+        let (width, height) = (image.width(), image.height());
+        let weights: Vec<f64> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let sin_theta = (PI * (y as f64 + 0.5) / height as f64).sin();
+                image.pixel(x, y).luminance().max(0.0) * sin_theta
+            })
+            .collect();
+        let distribution = Distribution2D::new(width, height, &weights);
+
+        EnvironmentMap {
+            image,
+            distribution,
+            rotation: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    /// Sets the rotation of the map around the Y axis, counter-clockwise seen
+    /// from above, in degrees.
+    pub fn with_rotation(self, degrees: f64) -> EnvironmentMap {
+        EnvironmentMap {
+            rotation: degrees.to_radians(),
+            ..self
+        }
+    }
+
+    /// Sets the factor by which the radiances of the image are multiplied.
+    pub fn with_intensity(self, intensity: f64) -> EnvironmentMap {
+        EnvironmentMap { intensity, ..self }
+    }
+
+    /// Returns the coordinates in the image of the `direction`, in [0, 1]
+    /// from the top left corner.
+    fn uv(&self, direction: Vec3) -> (f64, f64) {
+        let direction = direction.normalized();
+        let phi = direction.x().atan2(-direction.z()) + self.rotation;
+        let theta = direction.y().clamp(-1.0, 1.0).acos();
+
+        ((0.5 + phi / (2.0 * PI)).rem_euclid(1.0), theta / PI)
+    }
+
+    /// Returns the normalized direction of the coordinates `(u, v)` in the
+    /// image. This is the inverse of [`EnvironmentMap::uv`].
+    fn direction(&self, (u, v): (f64, f64)) -> Vec3 {
+        let phi = (u - 0.5) * 2.0 * PI - self.rotation;
+        let theta = v * PI;
+
+        Vec3::new(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            -theta.sin() * phi.cos(),
+        )
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn radiance(&self, direction: Vec3) -> Color {
+        let (u, v) = self.uv(direction);
+        let (width, height) = (self.image.width(), self.image.height());
+        let x = ((u * width as f64) as usize).min(width - 1);
+        let y = ((v * height as f64) as usize).min(height - 1);
+
+        self.intensity * self.image.pixel(x, y)
+    }
+
+    /// Samples a direction toward the map, in proportion to the luminance of
+    /// its pixels.
+    fn sample(&self) -> Option<(Vec3, f64)> {
+        let (uv, pdf) = self.distribution.sample((random(), random()));
+        let sin_theta = (uv.1 * PI).sin();
+        if pdf == 0.0 || sin_theta == 0.0 {
+            return None;
+        }
+
+        // The image covers 2π by π radians, and a pixel covers a solid angle
+        // proportional to sin θ.
+        Some((self.direction(uv), pdf / (2.0 * PI * PI * sin_theta)))
+    }
+
+    fn pdf(&self, direction: Vec3) -> f64 {
+        let uv = self.uv(direction);
+        let sin_theta = (uv.1 * PI).sin();
+        if sin_theta == 0.0 {
+            return 0.0;
+        }
+
+        self.distribution.pdf(uv) / (2.0 * PI * PI * sin_theta)
+    }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{super::Background, *};
+    use crate::random::seed;
+
+    /// A 4 by 2 image, whose pixels are numbered from the top left corner.
+    fn numbered_image() -> HdrImage {
+        HdrImage::new(
+            4,
+            2,
+            (0..8).map(|i| Color::new(i as f64, 0.0, 0.0)).collect(),
+        )
+    }
+
+    #[test]
+    fn radiance_looks_up_the_pixel_in_the_direction() {
+        let map = EnvironmentMap::new(numbered_image());
+
+        // Forward is the middle of the image.
+        assert_eq!(map.radiance(Vec3::new(-0.1, 0.5, -1.0)).x(), 1.0);
+        assert_eq!(map.radiance(Vec3::new(0.1, 0.5, -1.0)).x(), 2.0);
+        assert_eq!(map.radiance(Vec3::new(0.1, -0.5, -1.0)).x(), 6.0);
+        // Backward is on the sides.
+        assert_eq!(map.radiance(Vec3::new(0.1, 0.5, 1.0)).x(), 3.0);
+        assert_eq!(map.radiance(Vec3::new(-0.1, 0.5, 1.0)).x(), 0.0);
+    }
+
+    #[test]
+    fn direction_is_the_inverse_of_uv() {
+        let map = EnvironmentMap::new(numbered_image()).with_rotation(35.0);
+
+        for &uv in &[(0.1, 0.2), (0.5, 0.5), (0.9, 0.7), (0.3, 0.95)] {
+            let (u, v) = map.uv(map.direction(uv));
+
+            assert!((u - uv.0).abs() < 1e-12);
+            assert!((v - uv.1).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn sampled_directions_follow_the_luminance() {
+        // A dark map with a bright pixel and a dimmer one, on the same row.
+        let (width, height) = (8, 4);
+        let mut pixels = vec![Color::new(0.1, 0.1, 0.1); width * height];
+        pixels[width + 2] = Color::new(10.0, 10.0, 10.0);
+        pixels[width + 5] = Color::new(0.0, 5.0, 0.0);
+        let map =
+            EnvironmentMap::new(HdrImage::new(width, height, pixels.clone())).with_rotation(-60.0);
+        let weights: Vec<f64> = (0..width * height)
+            .map(|i| {
+                let sin_theta = (PI * ((i / width) as f64 + 0.5) / height as f64).sin();
+                pixels[i].luminance() * sin_theta
+            })
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let samples = 200_000;
+        seed(17);
+
+        let mut counts = vec![0; width * height];
+        for _ in 0..samples {
+            let (direction, pdf) = map.sample().expect("map should be sampled");
+            let (u, v) = map.uv(direction);
+            let (x, y) = ((u * width as f64) as usize, (v * height as f64) as usize);
+            counts[y * width + x] += 1;
+
+            assert!((direction.length() - 1.0).abs() < 1e-12);
+            // Near the poles, the direction loses some precision.
+            assert!((pdf - map.pdf(direction)).abs() < 1e-6 * pdf);
+        }
+
+        for (count, weight) in counts.iter().zip(&weights) {
+            assert!((*count as f64 / samples as f64 - weight / total).abs() < 0.005);
+        }
+    }
+
+    #[test]
+    fn importance_sampling_estimates_the_irradiance() {
+        let map = EnvironmentMap::new(numbered_image());
+        let samples = 100_000;
+
+        // The integral of the radiance over the sphere, estimated by sampling
+        // the map, then uniformly.
+        let sampled = (0..samples)
+            .map(|_| {
+                let (direction, pdf) = map.sample().unwrap();
+                map.radiance(direction).x() / pdf
+            })
+            .sum::<f64>()
+            / samples as f64;
+        let uniform = (0..samples)
+            .map(|_| map.radiance(Vec3::random_normalized()).x() * 4.0 * PI)
+            .sum::<f64>()
+            / samples as f64;
+
+        // Each half of the sphere covers 2π steradians.
+        let expected =
+            2.0 * PI * (0.0 + 1.0 + 2.0 + 3.0) / 4.0 + 2.0 * PI * (4.0 + 5.0 + 6.0 + 7.0) / 4.0;
+        assert!((sampled - expected).abs() < 0.005 * expected);
+        assert!((uniform - expected).abs() < 0.02 * expected);
+    }
+
+    #[test]
+    fn radiance_is_rotated_and_scaled() {
+        let map = EnvironmentMap::new(numbered_image())
+            .with_rotation(90.0)
+            .with_intensity(2.0);
+
+        // The middle of the image is now toward -X.
+        assert_eq!(map.radiance(Vec3::new(-1.0, 0.5, 0.1)).x(), 2.0);
+        assert_eq!(map.radiance(Vec3::new(-1.0, 0.5, -0.1)).x(), 4.0);
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::color::Color;
+
+/// A high dynamic range image, whose pixels are linear radiances.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HdrImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl HdrImage {
+    /// Constructs a new `HdrImage` of `width` by `height` pixels, stored row by
+    /// row from the top.
+    ///
+    /// # Panics
+    ///
+    /// The `new` function will panic if the image is empty or if there isn't
+    /// `width * height` pixels.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> HdrImage {
+        assert!(width > 0 && height > 0, "An image can't be empty");
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "A {} by {} image needs {} pixels",
+            width,
+            height,
+            width * height
+        );
+
+        HdrImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Loads an image from a Radiance HDR file.
+    ///
+    /// See [`HdrImage::read`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<HdrImage, HdrError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|source| HdrError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        HdrImage::read(BufReader::new(file), path)
+    }
+
+    /// Reads an image in Radiance HDR (RGBE) format from a `reader`. `path` is
+    /// only used to report errors.
+    ///
+    /// Both flat and run-length encoded scanlines are supported, for images
+    /// stored from the top row and the left column (`-Y height +X width`).
+    ///
+    /// See [RGBE image format on Wikipedia](https://en.wikipedia.org/wiki/RGBE_image_format)
+    /// and [Graphics Gems II, II.5](https://www.graphicsgems.org/).
+    pub fn read<R: Read>(mut reader: R, path: &Path) -> Result<HdrImage, HdrError> {
+        let mut content = Vec::new();
+        reader
+            .read_to_end(&mut content)
+            .map_err(|source| HdrError::Io {
+                path: path.to_owned(),
+                source,
+            })?;
+        let parse_error = |line: usize, message: String| HdrError::Parse {
+            path: path.to_owned(),
+            line,
+            message,
+        };
+
+        // The header is made of text lines, ended by an empty line, followed
+        // by the resolution line.
+        let mut offset = 0;
+        let mut lines: Vec<String> = Vec::new();
+        while lines.len() < 2 || !lines[lines.len() - 2].is_empty() {
+            let end = content[offset..]
+                .iter()
+                .position(|&byte| byte == b'\n')
+                .ok_or_else(|| {
+                    parse_error(lines.len() + 1, String::from("unexpected end of header"))
+                })?;
+            lines.push(String::from_utf8_lossy(&content[offset..offset + end]).into_owned());
+            offset += end + 1;
+        }
+
+        if !lines[0].starts_with("#?") {
+            return Err(parse_error(
+                1,
+                String::from("not a Radiance HDR file, expected `#?`"),
+            ));
+        }
+        for (index, line) in lines.iter().enumerate() {
+            if let Some(format) = line.strip_prefix("FORMAT=") {
+                if format != "32-bit_rle_rgbe" {
+                    return Err(parse_error(
+                        index + 1,
+                        format!("unsupported format `{}`", format),
+                    ));
+                }
+            }
+        }
+
+        let resolution_line = lines.len();
+        let resolution: Vec<&str> = lines[resolution_line - 1].split_whitespace().collect();
+        let (width, height) = match resolution.as_slice() {
+            ["-Y", height, "+X", width] => (width.parse().ok(), height.parse().ok()),
+            _ => (None, None),
+        };
+        let (width, height) = match (width, height) {
+            (Some(width), Some(height)) if width > 0 && height > 0 => (width, height),
+            _ => {
+                return Err(parse_error(
+                    resolution_line,
+                    format!(
+                        "unsupported resolution `{}`, expected `-Y <height> +X <width>`",
+                        lines[resolution_line - 1]
+                    ),
+                ))
+            }
+        };
+
+        let mut pixels = Vec::with_capacity(width * height);
+        let mut scanline = vec![[0; 4]; width];
+        for _ in 0..height {
+            offset =
+                read_scanline(&content, offset, &mut scanline).map_err(|(offset, message)| {
+                    HdrError::ParseBinary {
+                        path: path.to_owned(),
+                        offset: offset as u64,
+                        message,
+                    }
+                })?;
+            pixels.extend(scanline.iter().map(|&rgbe| rgbe_to_color(rgbe)));
+        }
+
+        Ok(HdrImage::new(width, height, pixels))
+    }
+
+    /// Returns the width of the image, in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the image, in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the pixel of the column `x` and the row `y`, from the top left
+    /// corner.
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+}
+
+/// Converts a pixel made of a shared exponent and three mantissas to a color.
+fn rgbe_to_color([r, g, b, e]: [u8; 4]) -> Color {
+    if e == 0 {
+        return Color::zero();
+    }
+
+    let scale = 2.0_f64.powi(e as i32 - (128 + 8));
+    Color::new(r as f64 * scale, g as f64 * scale, b as f64 * scale)
+}
+
+/// Reads a scanline of RGBE pixels starting at `offset` in the `content`.
+/// Returns the offset following the scanline, or the offset of the error and
+/// its message.
+fn read_scanline(
+    content: &[u8],
+    mut offset: usize,
+    scanline: &mut [[u8; 4]],
+) -> Result<usize, (usize, String)> {
+    let width = scanline.len();
+    let end_of_file = |offset: usize| (offset, String::from("unexpected end of file"));
+    let byte = |offset: usize| {
+        content
+            .get(offset)
+            .copied()
+            .ok_or_else(|| end_of_file(offset))
+    };
+
+    // Run-length encoded scanlines start with 2, 2 and their width. Narrow or
+    // very wide images can't be encoded.
+    let encoded = (8..0x8000).contains(&width)
+        && content.get(offset..offset + 4)
+            == Some(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8]);
+    if !encoded {
+        for pixel in scanline.iter_mut() {
+            let bytes = content
+                .get(offset..offset + 4)
+                .ok_or_else(|| end_of_file(content.len()))?;
+            pixel.copy_from_slice(bytes);
+            offset += 4;
+        }
+
+        return Ok(offset);
+    }
+
+    // Each channel is encoded separately, as runs of a repeated byte and
+    // sequences of literal bytes.
+    offset += 4;
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = byte(offset)? as usize;
+            offset += 1;
+
+            if count > 128 {
+                let length = count - 128;
+                if x + length > width {
+                    return Err((offset - 1, String::from("run overflowing the scanline")));
+                }
+                let value = byte(offset)?;
+                offset += 1;
+                scanline[x..x + length]
+                    .iter_mut()
+                    .for_each(|pixel| pixel[channel] = value);
+                x += length;
+            } else {
+                if count == 0 || x + count > width {
+                    return Err((offset - 1, String::from("invalid literal sequence")));
+                }
+                for pixel in &mut scanline[x..x + count] {
+                    pixel[channel] = byte(offset)?;
+                    offset += 1;
+                }
+                x += count;
+            }
+        }
+    }
+
+    Ok(offset)
+}
+
+/// An error that occurred while loading an HDR image file.
+#[derive(Debug)]
+pub enum HdrError {
+    /// The file could not be read.
+    Io { path: PathBuf, source: io::Error },
+    /// The header of the file is invalid.
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    /// The pixels of the file are invalid.
+    ParseBinary {
+        path: PathBuf,
+        offset: u64,
+        message: String,
+    },
+}
+
+impl fmt::Display for HdrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HdrError::Io { path, source } => {
+                write!(f, "{}: {}", path.display(), source)
+            }
+            HdrError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+            HdrError::ParseBinary {
+                path,
+                offset,
+                message,
+            } => write!(f, "{}: byte {}: {}", path.display(), offset, message),
+        }
+    }
+}
+
+impl Error for HdrError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HdrError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read(content: &[u8]) -> Result<HdrImage, HdrError> {
+        HdrImage::read(content, Path::new("test.hdr"))
+    }
+
+    fn file(resolution: &str, pixels: &[u8]) -> Vec<u8> {
+        let mut content =
+            format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n{}\n", resolution).into_bytes();
+        content.extend_from_slice(pixels);
+        content
+    }
+
+    #[test]
+    fn read_flat_image() {
+        // 1.0 is 128 * 2^-7, and 0.5 is 128 * 2^-8.
+        let image = read(&file("-Y 2 +X 1", &[128, 64, 0, 129, 128, 128, 128, 128])).unwrap();
+
+        assert_eq!(image.width(), 1);
+        assert_eq!(image.height(), 2);
+        assert_eq!(image.pixel(0, 0), Color::new(1.0, 0.5, 0.0));
+        assert_eq!(image.pixel(0, 1), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn read_run_length_encoded_image() {
+        let mut pixels = vec![2, 2, 0, 8];
+        // Red: a run of 8.
+        pixels.extend_from_slice(&[128 + 8, 128]);
+        // Green: 2 literal bytes, then a run of 6.
+        pixels.extend_from_slice(&[2, 0, 64, 128 + 6, 128]);
+        // Blue: a run of 8.
+        pixels.extend_from_slice(&[128 + 8, 0]);
+        // Exponent: a run of 8.
+        pixels.extend_from_slice(&[128 + 8, 129]);
+
+        let image = read(&file("-Y 1 +X 8", &pixels)).unwrap();
+
+        assert_eq!(image.pixel(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(image.pixel(1, 0), Color::new(1.0, 0.5, 0.0));
+        assert_eq!(image.pixel(7, 0), Color::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn read_image_without_magic_number() {
+        let error = read(b"P3\n\n-Y 1 +X 1\n\x80\x80\x80\x80").unwrap_err();
+
+        assert!(matches!(error, HdrError::Parse { line: 1, .. }));
+    }
+
+    #[test]
+    fn read_image_with_unsupported_orientation() {
+        let error = read(&file("+Y 1 +X 1", &[128, 128, 128, 128])).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "test.hdr:4: unsupported resolution `+Y 1 +X 1`, expected `-Y <height> +X <width>`"
+        );
+    }
+
+    #[test]
+    fn read_truncated_image_reports_offset() {
+        let error = read(&file("-Y 2 +X 1", &[128, 128, 128, 128, 128])).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "test.hdr: byte 50: unexpected end of file"
+        );
+    }
+}
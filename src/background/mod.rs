@@ -0,0 +1,107 @@
+//! # Background module
+//!
+//! Backgrounds give the radiance coming from far away in any direction, seen
+//! by the rays that don't hit anything.
+
+pub use environment_map::EnvironmentMap;
+pub use hdr::HdrImage;
+pub use sky::PreethamSky;
+
+use crate::{color::Color, vec3::Vec3};
+
+mod environment_map;
+mod hdr;
+mod sky;
+
+/// The background of a scene.
+pub trait Background {
+    /// Returns the radiance coming from the `direction`, which may not be
+    /// normalized.
+    fn radiance(&self, direction: Vec3) -> Color;
+
+    /// Samples a direction toward the background, in proportion to its
+    /// radiance. Returns the normalized direction and its probability density
+    /// with respect to solid angle, or [`None`] if the background can't be
+    /// sampled.
+    fn sample(&self) -> Option<(Vec3, f64)> {
+        None
+    }
+
+    /// Returns the probability density with respect to solid angle of
+    /// [`Background::sample`] giving the `direction`.
+    fn pdf(&self, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// Returns whether the background can be sampled, i.e. whether it is worth
+    /// sending rays toward it with [`Background::sample`].
+    fn can_sample(&self) -> bool {
+        false
+    }
+}
+
+/// A background of a single color.
+pub struct SolidBackground {
+    color: Color,
+}
+
+impl SolidBackground {
+    /// Constructs a new `SolidBackground`.
+    pub fn new(color: Color) -> SolidBackground {
+        SolidBackground { color }
+    }
+}
+
+impl Background for SolidBackground {
+    fn radiance(&self, _direction: Vec3) -> Color {
+        self.color
+    }
+}
+
+/// A background blending two colors from below to above.
+pub struct GradientBackground {
+    bottom: Color,
+    top: Color,
+}
+
+impl GradientBackground {
+    /// Constructs a new `GradientBackground`, of the `bottom` color straight
+    /// down and the `top` color straight up.
+    pub fn new(bottom: Color, top: Color) -> GradientBackground {
+        GradientBackground { bottom, top }
+    }
+
+    /// Constructs a new `GradientBackground` looking like a white-to-blue
+    /// sky.
+    pub fn sky() -> GradientBackground {
+        GradientBackground::new(Color::new(1.0, 1.0, 1.0), Color::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Background for GradientBackground {
+    fn radiance(&self, direction: Vec3) -> Color {
+        let t = 0.5 * (direction.normalized().y() + 1.0);
+
+        (1.0 - t) * self.bottom + t * self.top
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gradient_blends_colors_with_height() {
+        let gradient = GradientBackground::new(Color::zero(), Color::new(1.0, 2.0, 4.0));
+
+        assert_eq!(gradient.radiance(Vec3::new(0.0, -3.0, 0.0)), Color::zero());
+        assert_eq!(
+            gradient.radiance(Vec3::new(2.0, 0.0, 0.0)),
+            Color::new(0.5, 1.0, 2.0)
+        );
+        assert_eq!(
+            gradient.radiance(Vec3::new(0.0, 0.5, 0.0)),
+            Color::new(1.0, 2.0, 4.0)
+        );
+    }
+}
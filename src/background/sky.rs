@@ -0,0 +1,340 @@
+use std::f64::consts::PI;
+
+use crate::{color::Color, random::random, vec3::Vec3};
+
+use super::Background;
+
+/// The angular radius of the sun seen from the ground, in radians.
+const SUN_ANGULAR_RADIUS: f64 = 0.00465;
+/// The irradiance of the sun before going through the atmosphere, in the same
+/// units as the sky radiance.
+const SUN_IRRADIANCE: f64 = 10.0;
+/// The factor converting the luminance of the model, in kcd/m², to radiance,
+/// so a clear sky is about as bright as [`GradientBackground::sky`](super::GradientBackground::sky).
+const SKY_SCALE: f64 = 0.1;
+/// The probability of sampling a direction toward the sun rather than toward
+/// the whole sky.
+const SUN_SAMPLING_PROBABILITY: f64 = 0.5;
+
+/// The five coefficients of the Perez sky luminance distribution.
+type Perez = [f64; 5];
+
+/// A clear daylight sky, lit by the sun.
+///
+/// The sky radiance follows the analytic model of Preetham, Shirley and Smits,
+/// from the position of the sun and the turbidity (haziness) of the
+/// atmosphere. The sun is a small bright disk, reddened by the atmosphere as
+/// it sets, which is sampled directly.
+///
+/// See [A Practical Analytic Model for Daylight](https://www2.cs.utah.edu/~shirley/papers/sunsky/sunsky.pdf),
+/// Preetham, Shirley and Smits, 1999.
+pub struct PreethamSky {
+    /// The normalized direction toward the sun.
+    sun_direction: Vec3,
+    /// The angle between the zenith and the sun, in radians.
+    sun_theta: f64,
+    sun_radiance: Color,
+    /// The sky luminance and chromaticity straight up.
+    zenith: [f64; 3],
+    /// The Perez coefficients of the luminance and the chromaticity.
+    perez: [Perez; 3],
+    intensity: f64,
+}
+
+impl PreethamSky {
+    /// Constructs a new `PreethamSky`.
+    ///
+    /// The sun is `sun_elevation` degrees above the horizon, in [0, 90], and
+    /// `sun_azimuth` degrees clockwise from -Z seen from above. `turbidity`
+    /// goes from 2 for a very clear sky to 10 for a hazy one.
+    ///
+    /// # Panics
+    ///
+    /// The `new` function will panic if the sun is below the horizon or if the
+    /// turbidity is out of [1, 20].
+    pub fn new(sun_elevation: f64, sun_azimuth: f64, turbidity: f64) -> PreethamSky {
+        assert!(
+            (0.0..=90.0).contains(&sun_elevation),
+            "The sun must be above the horizon"
+        );
+        assert!(
+            (1.0..=20.0).contains(&turbidity),
+            "The turbidity must be in [1, 20]"
+        );
+
+        let (elevation, azimuth) = (sun_elevation.to_radians(), sun_azimuth.to_radians());
+        let sun_direction = Vec3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+        let sun_theta = PI / 2.0 - elevation;
+        let t = turbidity;
+
+        let perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * sun_theta);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let chromaticity = |matrix: [[f64; 4]; 3]| {
+            let thetas = [sun_theta.powi(3), sun_theta.powi(2), sun_theta, 1.0];
+            let turbidities = [t * t, t, 1.0];
+            (0..3)
+                .map(|row| {
+                    turbidities[row]
+                        * (0..4)
+                            .map(|column| matrix[row][column] * thetas[column])
+                            .sum::<f64>()
+                })
+                .sum::<f64>()
+        };
+        let zenith_x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let zenith_y = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+
+        PreethamSky {
+            sun_direction,
+            sun_theta,
+            sun_radiance: sun_radiance(sun_theta, turbidity),
+            zenith: [zenith_luminance, zenith_x, zenith_y],
+            perez,
+            intensity: 1.0,
+        }
+    }
+
+    /// Sets the factor by which the radiances of the sky and the sun are
+    /// multiplied.
+    pub fn with_intensity(self, intensity: f64) -> PreethamSky {
+        PreethamSky { intensity, ..self }
+    }
+
+    /// Returns the radiance of the sky alone, without the sun, coming from the
+    /// normalized `direction`.
+    fn sky_radiance(&self, direction: Vec3) -> Color {
+        // The model is only valid above the horizon, which is extended below.
+        let cos_theta = direction.y().max(0.01);
+        let gamma = direction.dot(&self.sun_direction).clamp(-1.0, 1.0).acos();
+
+        let [luminance, x, y] = [0, 1, 2].map(|i| {
+            self.zenith[i] * perez(&self.perez[i], cos_theta, gamma)
+                / perez(&self.perez[i], 1.0, self.sun_theta)
+        });
+
+        SKY_SCALE * xyy_to_rgb(x, y, luminance)
+    }
+
+    /// Returns whether the normalized `direction` goes through the sun disk.
+    fn is_toward_sun(&self, direction: Vec3) -> bool {
+        direction.dot(&self.sun_direction) >= SUN_ANGULAR_RADIUS.cos()
+    }
+}
+
+/// Returns the Perez distribution of the sky luminance, for a direction whose
+/// angle to the zenith has the cosine `cos_theta`, at `gamma` radians from the
+/// sun.
+fn perez(&[a, b, c, d, e]: &Perez, cos_theta: f64, gamma: f64) -> f64 {
+    (1.0 + a * (b / cos_theta).exp()) * (1.0 + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+}
+
+/// Converts a color from its chromaticity `(x, y)` and luminance `luminance`
+/// (CIE xyY) to linear sRGB.
+fn xyy_to_rgb(x: f64, y: f64, luminance: f64) -> Color {
+    let big_x = x / y * luminance;
+    let big_z = (1.0 - x - y) / y * luminance;
+
+    Color::new(
+        (3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z).max(0.0),
+        (-0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z).max(0.0),
+        (0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z).max(0.0),
+    )
+}
+
+/// Returns the radiance of the sun seen through the atmosphere, when it is
+/// `sun_theta` radians from the zenith.
+///
+/// The light of the sun is scattered away by air molecules (Rayleigh
+/// scattering) and by aerosols, more for shorter wavelengths and longer paths
+/// through the atmosphere.
+fn sun_radiance(sun_theta: f64, turbidity: f64) -> Color {
+    // The relative length of the path through the atmosphere, see Kasten and
+    // Young, 1989.
+    let air_mass =
+        1.0 / (sun_theta.cos() + 0.50572 * (96.07995 - sun_theta.to_degrees()).powf(-1.6364));
+    let beta = 0.04608 * turbidity - 0.04586;
+    let transmittance = |wavelength: f64| {
+        let rayleigh = -0.008735 * wavelength.powf(-4.08) * air_mass;
+        let aerosol = -beta * wavelength.powf(-1.3) * air_mass;
+        (rayleigh + aerosol).exp()
+    };
+
+    // Red, green and blue wavelengths, in micrometers.
+    let solid_angle = 2.0 * PI * (1.0 - SUN_ANGULAR_RADIUS.cos());
+    SUN_IRRADIANCE / solid_angle
+        * Color::new(
+            transmittance(0.68),
+            transmittance(0.55),
+            transmittance(0.44),
+        )
+}
+
+impl Background for PreethamSky {
+    fn radiance(&self, direction: Vec3) -> Color {
+        let direction = direction.normalized();
+        let mut radiance = self.sky_radiance(direction);
+        if self.is_toward_sun(direction) {
+            radiance += self.sun_radiance;
+        }
+
+        self.intensity * radiance
+    }
+
+    /// Samples a direction toward the sun disk or, less often, uniformly
+    /// toward the whole sky.
+    fn sample(&self) -> Option<(Vec3, f64)> {
+        let direction = if random() < SUN_SAMPLING_PROBABILITY {
+            let cos_theta = 1.0 - random() * (1.0 - SUN_ANGULAR_RADIUS.cos());
+            let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+            let phi = 2.0 * PI * random();
+            let (tangent, bitangent) = self.sun_direction.orthonormal_basis();
+
+            cos_theta * self.sun_direction
+                + sin_theta * (phi.cos() * tangent + phi.sin() * bitangent)
+        } else {
+            Vec3::random_normalized()
+        };
+
+        Some((direction, self.pdf(direction)))
+    }
+
+    fn pdf(&self, direction: Vec3) -> f64 {
+        let uniform = (1.0 - SUN_SAMPLING_PROBABILITY) / (4.0 * PI);
+        if self.is_toward_sun(direction.normalized()) {
+            let sun_solid_angle = 2.0 * PI * (1.0 - SUN_ANGULAR_RADIUS.cos());
+            uniform + SUN_SAMPLING_PROBABILITY / sun_solid_angle
+        } else {
+            uniform
+        }
+    }
+
+    fn can_sample(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sky_is_brighter_around_the_sun() {
+        let sky = PreethamSky::new(30.0, 90.0, 3.0);
+
+        let toward_sun = sky.radiance(Vec3::new(1.0, 0.7, 0.0)).luminance();
+        let away_from_sun = sky.radiance(Vec3::new(-1.0, 0.7, 0.0)).luminance();
+        let zenith = sky.radiance(Vec3::new(0.0, 1.0, 0.0)).luminance();
+
+        assert!(toward_sun > zenith);
+        assert!(toward_sun > away_from_sun);
+        assert!((0.3..3.0).contains(&zenith));
+    }
+
+    #[test]
+    fn zenith_has_the_zenith_luminance() {
+        let sky = PreethamSky::new(45.0, 0.0, 3.0);
+
+        // About 7.3 kcd/m² for this sun and turbidity.
+        let luminance = sky.sky_radiance(Vec3::new(0.0, 1.0, 0.0)).luminance();
+
+        assert!((luminance / SKY_SCALE - 7.32).abs() < 0.05);
+    }
+
+    #[test]
+    fn clear_sky_is_blue() {
+        let sky = PreethamSky::new(60.0, 0.0, 2.0);
+
+        let color = sky.radiance(Vec3::new(0.0, 0.5, 1.0));
+
+        assert!(color.z() > color.x());
+    }
+
+    #[test]
+    fn setting_sun_is_reddened() {
+        let noon = PreethamSky::new(90.0, 0.0, 3.0).sun_radiance;
+        let sunset = PreethamSky::new(2.0, 0.0, 3.0).sun_radiance;
+
+        assert!(noon.z() / noon.x() > sunset.z() / sunset.x());
+        assert!(sunset.x() < noon.x());
+    }
+
+    #[test]
+    fn sun_is_sampled_directly() {
+        let sky = PreethamSky::new(20.0, 45.0, 3.0).with_intensity(2.0);
+        let samples = 10_000;
+
+        let mut toward_sun = 0;
+        for _ in 0..samples {
+            let (direction, pdf) = sky.sample().expect("sky should be sampled");
+            if sky.is_toward_sun(direction) {
+                toward_sun += 1;
+                assert!(sky.radiance(direction).luminance() > 1e4);
+            }
+
+            assert!((direction.length() - 1.0).abs() < 1e-12);
+            assert_eq!(pdf, sky.pdf(direction));
+        }
+
+        let fraction = toward_sun as f64 / samples as f64;
+        assert!((fraction - SUN_SAMPLING_PROBABILITY).abs() < 0.02);
+    }
+
+    #[test]
+    fn sampling_estimates_the_sun_power() {
+        let sky = PreethamSky::new(90.0, 0.0, 2.0);
+        let samples = 100_000;
+
+        // The integral of the sun radiance over the sphere.
+        let power = (0..samples)
+            .map(|_| {
+                let (direction, pdf) = sky.sample().unwrap();
+                if sky.is_toward_sun(direction) {
+                    sky.sun_radiance.y() / pdf
+                } else {
+                    0.0
+                }
+            })
+            .sum::<f64>()
+            / samples as f64;
+
+        let solid_angle = 2.0 * PI * (1.0 - SUN_ANGULAR_RADIUS.cos());
+        let expected = sky.sun_radiance.y() * solid_angle;
+        assert!((power - expected).abs() < 0.01 * expected);
+    }
+}
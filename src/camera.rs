@@ -1,4 +1,5 @@
 use crate::{
+    random::random_range,
     ray::Ray,
     vec3::{Point3, Vec3},
 };
@@ -7,6 +8,7 @@ use crate::{
 ///
 /// All ray are sent from the camera origin.
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct Camera {
     origin: Point3,
     lower_left_corner: Point3,
@@ -16,6 +18,7 @@ pub struct Camera {
     v: Vec3,
     w: Vec3,
     lens_radius: f64,
+    shutter: Option<(f64, f64)>,
 }
 
 impl Camera {
@@ -51,6 +54,19 @@ impl Camera {
             v,
             w,
             lens_radius: aperture / 2.0,
+            shutter: None,
+        }
+    }
+
+    /// Returns the camera with its shutter open from `open` to `close`. Rays
+    /// are sent at random times in that interval, which blurs moving
+    /// hittables.
+    ///
+    /// A new camera has no shutter and sends all its rays at time 0.
+    pub fn with_shutter(self, open: f64, close: f64) -> Camera {
+        Camera {
+            shutter: Some((open, close)),
+            ..self
         }
     }
 
@@ -60,12 +76,136 @@ impl Camera {
     /// `s` and `t` are two coefficient making two offset vectors along the
     /// screen sides to move the ray endpoint across the screen.
     pub fn ray_to(&self, s: f64, t: f64) -> Ray {
+        let origin = self.lens_point();
+
+        Ray::with_time(
+            origin,
+            self.lower_left_corner + s * self.horizontal + t * self.vertical - origin,
+            self.shutter_time(),
+        )
+    }
+
+    /// Returns a random time while the shutter is open.
+    pub fn shutter_time(&self) -> f64 {
+        match self.shutter {
+            Some((open, close)) if open < close => random_range(open..close),
+            Some((open, _)) => open,
+            None => 0.0,
+        }
+    }
+
+    /// Returns a random point of the lens, from which rays are sent.
+    fn lens_point(&self) -> Point3 {
         let rd = self.lens_radius * Vec3::random_in_unit_disk();
-        let offset = self.u * rd.x() + self.v * rd.y();
 
-        Ray::new(
-            self.origin + offset,
-            self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
+        self.origin + self.u * rd.x() + self.v * rd.y()
+    }
+
+    /// Returns the distance from the camera origin to the plane in focus.
+    fn focus_distance(&self) -> f64 {
+        (self.origin - self.lower_left_corner).dot(&self.w)
+    }
+
+    /// Projects a `point` of the scene on the screen, through a random point
+    /// of the lens. Returns that point of the lens and the coordinates `(s, t)`
+    /// of the projection as given to [`Camera::ray_to`], which are outside
+    /// [0, 1] when the point is out of view, or [`None`] if the point is
+    /// behind the camera.
+    pub fn project(&self, point: Point3) -> Option<(Point3, (f64, f64))> {
+        let origin = self.lens_point();
+        let direction = point - origin;
+        let forward = -direction.dot(&self.w);
+        if forward <= 0.0 {
+            return None;
+        }
+
+        let screen_point = origin + self.focus_distance() / forward * direction;
+        let offset = screen_point - self.lower_left_corner;
+
+        Some((
+            origin,
+            (
+                offset.dot(&self.horizontal) / self.horizontal.length_squared(),
+                offset.dot(&self.vertical) / self.vertical.length_squared(),
+            ),
+        ))
+    }
+
+    /// Returns the probability density with respect to solid angle of the
+    /// camera sending a ray along `direction`, when `(s, t)` are uniformly
+    /// distributed in [0, 1].
+    ///
+    /// It is also the importance of the camera (how much the radiance coming
+    /// back along the ray counts in the image) times the cosine of the angle
+    /// between the ray and the camera axis, divided by the density of the point
+    /// of the lens.
+    pub fn direction_pdf(&self, direction: Vec3) -> f64 {
+        let cos_theta = -direction.normalized().dot(&self.w);
+        if cos_theta <= 0.0 {
+            return 0.0;
+        }
+
+        // The area of the screen at a distance of 1 from the lens.
+        let area = self.horizontal.length() * self.vertical.length()
+            / (self.focus_distance() * self.focus_distance());
+
+        1.0 / (area * cos_theta * cos_theta * cos_theta)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn camera(aperture: f64) -> Camera {
+        Camera::new(
+            Point3::new(1.0, 2.0, 3.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.5,
+            aperture,
+            5.0,
         )
     }
+
+    #[test]
+    fn project_is_the_inverse_of_ray_to() {
+        for &aperture in &[0.0, 0.5] {
+            let camera = camera(aperture);
+            // A point of the plane in focus is seen at the same place from
+            // any point of the lens.
+            let ray = camera.ray_to(0.2, 0.7);
+            let point = ray.origin() + ray.direction();
+
+            let (origin, (s, t)) = camera.project(point).unwrap();
+
+            assert!((s - 0.2).abs() < 1e-9 && (t - 0.7).abs() < 1e-9);
+            assert!((origin - Point3::new(1.0, 2.0, 3.0)).length() <= aperture / 2.0);
+        }
+        assert!(camera(0.0).project(Point3::new(2.0, 4.0, 7.0)).is_none());
+    }
+
+    #[test]
+    fn direction_pdf_integrates_to_one() {
+        let camera = camera(0.0);
+        let samples = 20_000;
+
+        let integral = (0..samples)
+            .map(|_| {
+                let ray = camera.ray_to(random_range(0.0..1.0), random_range(0.0..1.0));
+                let (_, (s, t)) = camera.project(ray.origin() + ray.direction()).unwrap();
+                assert!((0.0..=1.0).contains(&s) && (0.0..=1.0).contains(&t));
+                1.0 / camera.direction_pdf(ray.direction())
+            })
+            .sum::<f64>()
+            / samples as f64;
+        // The mean of 1 / pdf is the solid angle covered by the screen, a
+        // rectangle of half sides a and b at a distance of 1.
+        let b = 20.0_f64.to_radians().tan();
+        let a = 1.5 * b;
+        let solid_angle = 4.0 * (a * b / ((1.0 + a * a) * (1.0 + b * b)).sqrt()).asin();
+
+        assert!((integral - solid_angle).abs() < 0.02 * solid_angle);
+    }
 }
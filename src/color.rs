@@ -6,6 +6,14 @@ use crate::vec3;
 pub type Color = vec3::Vec3;
 
 impl Color {
+    /// Returns the luminance of the color, its brightness as perceived by the
+    /// human eye.
+    ///
+    /// See [relative luminance on Wikipedia](https://en.wikipedia.org/wiki/Relative_luminance).
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.x() + 0.7152 * self.y() + 0.0722 * self.z()
+    }
+
     /// Writes a single pixel's color out to a writer.
     ///
     /// The color is the sum of multiple samples, thus this function will
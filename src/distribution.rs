@@ -0,0 +1,232 @@
+//! # Distribution module
+//!
+//! Piecewise-constant probability distributions, used to sample values (e.g.
+//! directions toward the bright parts of an environment map) in proportion to
+//! a tabulated function.
+//!
+//! See [Sampling Random Variables](https://www.pbr-book.org/3ed-2018/Monte_Carlo_Integration/Sampling_Random_Variables)
+//! and [2D Sampling with Multidimensional Transformations](https://www.pbr-book.org/3ed-2018/Monte_Carlo_Integration/2D_Sampling_with_Multidimensional_Transformations)
+//! in Physically Based Rendering.
+
+/// A probability distribution over [0, 1), proportional to a piecewise-constant
+/// function made of equal-width pieces.
+pub struct Distribution1D {
+    function: Vec<f64>,
+    /// The cumulative distribution function at the start of each piece, and
+    /// 1 at the end.
+    cdf: Vec<f64>,
+    /// The integral of the function over [0, 1].
+    integral: f64,
+}
+
+impl Distribution1D {
+    /// Constructs a new `Distribution1D` proportional to the `function`, whose
+    /// values are the ones of each piece. A function that is zero everywhere
+    /// gives the uniform distribution.
+    ///
+    /// # Panics
+    ///
+    /// The `new` function will panic if the `function` is empty or if one of
+    /// its values is negative or not finite.
+    pub fn new(function: Vec<f64>) -> Distribution1D {
+        assert!(!function.is_empty(), "A distribution can't be empty");
+        assert!(
+            function
+                .iter()
+                .all(|&value| value >= 0.0 && value.is_finite()),
+            "A distribution needs finite and non-negative values"
+        );
+
+        let count = function.len() as f64;
+        let mut cdf = Vec::with_capacity(function.len() + 1);
+        cdf.push(0.0);
+        for value in &function {
+            cdf.push(cdf[cdf.len() - 1] + value / count);
+        }
+
+        let integral = cdf[function.len()];
+        if integral == 0.0 {
+            cdf.iter_mut()
+                .enumerate()
+                .for_each(|(i, c)| *c = i as f64 / count);
+        } else {
+            cdf.iter_mut().for_each(|c| *c /= integral);
+        }
+
+        Distribution1D {
+            function,
+            cdf,
+            integral,
+        }
+    }
+
+    /// Returns the number of pieces of the function.
+    pub fn count(&self) -> usize {
+        self.function.len()
+    }
+
+    /// Returns the integral of the function over [0, 1].
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// Samples the distribution by inverting its cumulative distribution
+    /// function at `u`, in [0, 1).
+    ///
+    /// Returns the sampled value in [0, 1), its probability density and the
+    /// index of the piece it belongs to.
+    pub fn sample(&self, u: f64) -> (f64, f64, usize) {
+        // The last piece whose start is at most `u`, skipping empty pieces.
+        let offset = (self.cdf.partition_point(|&c| c <= u) - 1).min(self.count() - 1);
+
+        let width = self.cdf[offset + 1] - self.cdf[offset];
+        let fraction = if width > 0.0 {
+            (u - self.cdf[offset]) / width
+        } else {
+            0.0
+        };
+        let value = ((offset as f64 + fraction) / self.count() as f64).min(1.0 - f64::EPSILON);
+
+        (value, self.piece_pdf(offset), offset)
+    }
+
+    /// Returns the probability density of sampling `value`, in [0, 1).
+    pub fn pdf(&self, value: f64) -> f64 {
+        let offset = ((value * self.count() as f64) as usize).min(self.count() - 1);
+        self.piece_pdf(offset)
+    }
+
+    fn piece_pdf(&self, offset: usize) -> f64 {
+        if self.integral == 0.0 {
+            1.0
+        } else {
+            self.function[offset] / self.integral
+        }
+    }
+}
+
+/// A probability distribution over [0, 1)², proportional to a
+/// piecewise-constant function made of a grid of equal-size pieces.
+///
+/// A row is sampled first from the marginal distribution of the rows, then a
+/// column from the distribution of that row.
+pub struct Distribution2D {
+    /// The distribution of each row.
+    rows: Vec<Distribution1D>,
+    /// The distribution of the rows, proportional to their integrals.
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// Constructs a new `Distribution2D` proportional to the `function`, made
+    /// of `width` by `height` pieces stored row by row.
+    ///
+    /// # Panics
+    ///
+    /// The `new` function will panic if there isn't `width * height` values,
+    /// or for the same reasons as [`Distribution1D::new`].
+    pub fn new(width: usize, height: usize, function: &[f64]) -> Distribution2D {
+        assert_eq!(
+            function.len(),
+            width * height,
+            "A {} by {} distribution needs {} values",
+            width,
+            height,
+            width * height
+        );
+        assert!(width > 0 && height > 0, "A distribution can't be empty");
+
+        let rows: Vec<Distribution1D> = function
+            .chunks_exact(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(rows.iter().map(Distribution1D::integral).collect());
+
+        Distribution2D { rows, marginal }
+    }
+
+    /// Samples the distribution from `(u, v)`, in [0, 1)².
+    ///
+    /// Returns the sampled point in [0, 1)², whose first coordinate is along
+    /// the rows, and its probability density.
+    pub fn sample(&self, (u, v): (f64, f64)) -> ((f64, f64), f64) {
+        let (y, row_pdf, row) = self.marginal.sample(v);
+        let (x, column_pdf, _) = self.rows[row].sample(u);
+
+        ((x, y), row_pdf * column_pdf)
+    }
+
+    /// Returns the probability density of sampling the point `(x, y)`, in
+    /// [0, 1)².
+    pub fn pdf(&self, (x, y): (f64, f64)) -> f64 {
+        let row = ((y * self.rows.len() as f64) as usize).min(self.rows.len() - 1);
+
+        self.marginal.pdf(y) * self.rows[row].pdf(x)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::random;
+
+    use super::*;
+
+    #[test]
+    fn sample_1d_inverts_the_cdf() {
+        let distribution = Distribution1D::new(vec![1.0, 0.0, 3.0]);
+
+        assert_eq!(distribution.integral(), 4.0 / 3.0);
+
+        let (value, pdf, offset) = distribution.sample(0.125);
+        assert!((value - 1.0 / 6.0).abs() < 1e-12);
+        assert_eq!(pdf, 0.75);
+        assert_eq!(offset, 0);
+
+        // The empty piece is never sampled.
+        let (value, pdf, offset) = distribution.sample(0.25);
+        assert!((value - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(pdf, 2.25);
+        assert_eq!(offset, 2);
+
+        assert_eq!(distribution.pdf(0.5), 0.0);
+    }
+
+    #[test]
+    fn sample_1d_of_zero_function_is_uniform() {
+        let distribution = Distribution1D::new(vec![0.0; 4]);
+
+        let (value, pdf, offset) = distribution.sample(0.6);
+
+        assert!((value - 0.6).abs() < 1e-12);
+        assert_eq!(pdf, 1.0);
+        assert_eq!(offset, 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_1d_with_negative_value_panics() {
+        Distribution1D::new(vec![1.0, -1.0]);
+    }
+
+    #[test]
+    fn sample_2d_follows_the_function() {
+        let function = [1.0, 2.0, 0.0, 5.0, 0.5, 3.5];
+        let distribution = Distribution2D::new(3, 2, &function);
+        let total: f64 = function.iter().sum();
+        let samples = 200_000;
+
+        let mut counts = [0; 6];
+        for _ in 0..samples {
+            let ((x, y), pdf) = distribution.sample((random(), random()));
+            let (column, row) = ((x * 3.0) as usize, (y * 2.0) as usize);
+            counts[row * 3 + column] += 1;
+
+            assert!((pdf - distribution.pdf((x, y))).abs() < 1e-12);
+            assert!((pdf - function[row * 3 + column] * 6.0 / total).abs() < 1e-12);
+        }
+
+        for (count, value) in counts.iter().zip(&function) {
+            assert!((*count as f64 / samples as f64 - value / total).abs() < 0.005);
+        }
+    }
+}
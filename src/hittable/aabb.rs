@@ -0,0 +1,190 @@
+use std::ops::RangeInclusive;
+
+use crate::{
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// An axis-aligned bounding box.
+///
+/// The box is the volume between its [`Aabb::minimum`] and [`Aabb::maximum`]
+/// corners.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Aabb {
+    minimum: Point3,
+    maximum: Point3,
+}
+
+impl Aabb {
+    /// Constructs a new `Aabb` from two opposite corners.
+    ///
+    /// The corners don't need to be ordered, the smallest and largest
+    /// components are picked on each axis.
+    pub fn new(a: Point3, b: Point3) -> Aabb {
+        Aabb {
+            minimum: a.min(&b),
+            maximum: a.max(&b),
+        }
+    }
+
+    /// Returns the corner with the smallest components.
+    pub fn minimum(&self) -> Point3 {
+        self.minimum
+    }
+
+    /// Returns the corner with the largest components.
+    pub fn maximum(&self) -> Point3 {
+        self.maximum
+    }
+
+    /// Returns the smallest box containing both the box and `other`.
+    pub fn surrounding(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            minimum: self.minimum.min(&other.minimum),
+            maximum: self.maximum.max(&other.maximum),
+        }
+    }
+
+    /// Returns the smallest box containing both the box and `point`.
+    pub fn including(&self, point: Point3) -> Aabb {
+        Aabb {
+            minimum: self.minimum.min(&point),
+            maximum: self.maximum.max(&point),
+        }
+    }
+
+    /// Returns the point at the center of the box.
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (self.minimum + self.maximum)
+    }
+
+    /// Returns the area of the six faces of the box.
+    pub fn surface_area(&self) -> f64 {
+        let extent = self.maximum - self.minimum;
+        2.0 * (extent.x() * extent.y() + extent.y() * extent.z() + extent.z() * extent.x())
+    }
+
+    /// Returns the axis along which the box is the longest: 0 for X, 1 for Y
+    /// and 2 for Z.
+    pub fn longest_axis(&self) -> usize {
+        let extent = self.maximum - self.minimum;
+        if extent.x() > extent.y() && extent.x() > extent.z() {
+            0
+        } else if extent.y() > extent.z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Returns the box grown by `margin` on every side.
+    pub fn expanded(&self, margin: f64) -> Aabb {
+        let margin = Vec3::new(margin, margin, margin);
+
+        Aabb {
+            minimum: self.minimum - margin,
+            maximum: self.maximum + margin,
+        }
+    }
+
+    /// Returns whether the ray goes through the box for a distance in the
+    /// `valid_range`.
+    ///
+    /// See [`Aabb::clip`].
+    pub fn hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> bool {
+        self.clip(ray, valid_range).is_some()
+    }
+
+    /// Returns the part of the `valid_range` where the ray is inside the box,
+    /// as the distances where it enters and exits the box, or [`None`] if the
+    /// ray misses the box in that range.
+    ///
+    /// See [slab method on Wikipedia](https://en.wikipedia.org/wiki/Slab_method).
+    pub fn clip(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<(f64, f64)> {
+        let mut t_min = *valid_range.start();
+        let mut t_max = *valid_range.end();
+
+        for axis in 0..3 {
+            let inverse_direction = 1.0 / ray.direction()[axis];
+            let mut t0 = (self.minimum[axis] - ray.origin()[axis]) * inverse_direction;
+            let mut t1 = (self.maximum[axis] - ray.origin()[axis]) * inverse_direction;
+            if inverse_direction < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // `max` and `min` ignore NaN, which happens when the ray origin
+            // lies on a slab plane and the ray is parallel to it.
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_max < t_min {
+                return None;
+            }
+        }
+
+        Some((t_min, t_max))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn new_orders_corners() {
+        let aabb = Aabb::new(Point3::new(1.0, -1.0, 2.0), Point3::new(-1.0, 1.0, 0.0));
+
+        assert_eq!(aabb.minimum(), Point3::new(-1.0, -1.0, 0.0));
+        assert_eq!(aabb.maximum(), Point3::new(1.0, 1.0, 2.0));
+    }
+
+    #[test]
+    fn surrounding_works() {
+        let a = Aabb::new(Point3::zero(), Point3::new(1.0, 1.0, 1.0));
+        let b = Aabb::new(Point3::new(2.0, -1.0, 0.5), Point3::new(3.0, 0.0, 0.5));
+
+        assert_eq!(
+            a.surrounding(&b),
+            Aabb::new(Point3::new(0.0, -1.0, 0.0), Point3::new(3.0, 1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn surface_area_works() {
+        let aabb = Aabb::new(Point3::zero(), Point3::new(1.0, 2.0, 3.0));
+
+        assert_eq!(aabb.surface_area(), 22.0);
+    }
+
+    #[test]
+    fn hit_aabb_with_hitting_ray() {
+        let aabb = Aabb::new(Point3::new(2.0, -1.0, -1.0), Point3::new(4.0, 1.0, 1.0));
+        let ray = Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(aabb.hit(&ray, 0.0..=f64::INFINITY));
+    }
+
+    #[test]
+    fn hit_aabb_with_hitting_ray_not_in_range() {
+        let aabb = Aabb::new(Point3::new(2.0, -1.0, -1.0), Point3::new(4.0, 1.0, 1.0));
+        let ray = Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(!aabb.hit(&ray, 0.0..=1.0));
+    }
+
+    #[test]
+    fn clip_works() {
+        let aabb = Aabb::new(Point3::new(2.0, -1.0, -1.0), Point3::new(4.0, 1.0, 1.0));
+        let ray = Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0));
+
+        assert_eq!(aabb.clip(&ray, 0.0..=f64::INFINITY), Some((2.0, 4.0)));
+        assert_eq!(aabb.clip(&ray, 3.0..=10.0), Some((3.0, 4.0)));
+    }
+
+    #[test]
+    fn hit_aabb_with_not_hitting_ray() {
+        let aabb = Aabb::new(Point3::new(2.0, -1.0, -1.0), Point3::new(4.0, 1.0, 1.0));
+        let ray = Ray::new(Point3::zero(), Vec3::new(0.0, 1.0, 0.0));
+
+        assert!(!aabb.hit(&ray, 0.0..=f64::INFINITY));
+    }
+}
@@ -0,0 +1,765 @@
+use std::{cmp::Ordering, error::Error, fmt, ops::RangeInclusive, str::FromStr, sync::Arc};
+
+use crate::ray::Ray;
+
+use super::{Aabb, HitRecord, Hittable, HittableCollection};
+
+/// The default maximum number of hittables stored in a leaf of the hierarchy.
+const DEFAULT_MAX_OBJECTS_PER_LEAF: usize = 4;
+
+/// The estimated cost of traversing an inner node, relatively to the cost of
+/// testing a ray against an hittable.
+const TRAVERSAL_COST: f64 = 0.125;
+
+/// The estimated cost of testing a ray against an hittable.
+const INTERSECTION_COST: f64 = 1.0;
+
+/// The number of buckets the hittables are binned into when evaluating the
+/// surface area heuristic.
+const SAH_BUCKETS: usize = 12;
+
+/// How the hittables of a node are split into its two children when building
+/// a [`Bvh`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SplitMethod {
+    /// Splits at the middle of the longest axis of the node, or falls back to
+    /// [`SplitMethod::Median`] when all hittables lie on the same side.
+    Middle,
+    /// Splits in two halves with the same amount of hittables along the
+    /// longest axis of the node.
+    Median,
+    /// Splits where the binned surface area heuristic (SAH) estimates the
+    /// traversal cost is the lowest. Nodes are not split when it is cheaper to
+    /// test all their hittables.
+    ///
+    /// See [PBRT, chapter 4.3](https://www.pbr-book.org/3ed-2018/Primitives_and_Intersection_Acceleration/Bounding_Volume_Hierarchies).
+    Sah,
+}
+
+impl fmt::Display for SplitMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitMethod::Middle => write!(f, "middle"),
+            SplitMethod::Median => write!(f, "median"),
+            SplitMethod::Sah => write!(f, "sah"),
+        }
+    }
+}
+
+impl FromStr for SplitMethod {
+    type Err = ParseSplitMethodError;
+
+    /// Parses a split method from its name: `middle`, `median` or `sah`.
+    fn from_str(s: &str) -> Result<SplitMethod, ParseSplitMethodError> {
+        match s.trim().to_lowercase().as_str() {
+            "middle" => Ok(SplitMethod::Middle),
+            "median" => Ok(SplitMethod::Median),
+            "sah" => Ok(SplitMethod::Sah),
+            _ => Err(ParseSplitMethodError(s.to_owned())),
+        }
+    }
+}
+
+/// The error returned when parsing an unknown [`SplitMethod`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSplitMethodError(String);
+
+impl fmt::Display for ParseSplitMethodError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown split method `{}`, expected `middle`, `median` or `sah`",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseSplitMethodError {}
+
+/// A bounding volume hierarchy (BVH).
+///
+/// Hittables are stored in a binary tree of nested [`Aabb`], so a ray only has
+/// to be tested against the hittables whose boxes it goes through instead of
+/// every hittable of the scene.
+///
+/// Unbounded hittables (those without a [`Hittable::bounding_box`]) can't be
+/// placed in the tree: they are stored aside and always tested.
+///
+/// See [bounding volume hierarchy on Wikipedia](https://en.wikipedia.org/wiki/Bounding_volume_hierarchy).
+pub struct Bvh {
+    nodes: Vec<Node>,
+    objects: Vec<Arc<dyn Hittable + Sync + Send>>,
+    unbounded: Vec<Arc<dyn Hittable + Sync + Send>>,
+    split_method: SplitMethod,
+    max_objects_per_leaf: usize,
+}
+
+/// A node of the tree, stored in depth-first order in [`Bvh::nodes`].
+struct Node {
+    bounds: Aabb,
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    /// A leaf referencing the `count` hittables starting at `first` in
+    /// [`Bvh::objects`].
+    Leaf { first: usize, count: usize },
+    /// An inner node split along `axis`. Its left child directly follows it in
+    /// [`Bvh::nodes`], its right child is at index `right`.
+    Interior { right: usize, axis: usize },
+}
+
+/// A hittable waiting to be placed in the tree.
+struct Primitive {
+    object: Arc<dyn Hittable + Sync + Send>,
+    bounds: Aabb,
+}
+
+impl Bvh {
+    /// Constructs a new `Bvh` containing the `objects`, built with the
+    /// [`SplitMethod::Sah`] split method.
+    pub fn new(objects: Vec<Arc<dyn Hittable + Sync + Send>>) -> Bvh {
+        Bvh::with_split_method(objects, SplitMethod::Sah, DEFAULT_MAX_OBJECTS_PER_LEAF)
+    }
+
+    /// Constructs a new `Bvh` containing the `objects`, whose nodes are split
+    /// using `split_method`. Leaves contain at most `max_objects_per_leaf`
+    /// hittables.
+    ///
+    /// # Panics
+    ///
+    /// The `with_split_method` function will panic if `max_objects_per_leaf`
+    /// is zero.
+    pub fn with_split_method(
+        objects: Vec<Arc<dyn Hittable + Sync + Send>>,
+        split_method: SplitMethod,
+        max_objects_per_leaf: usize,
+    ) -> Bvh {
+        assert!(max_objects_per_leaf > 0);
+
+        let mut primitives = Vec::with_capacity(objects.len());
+        let mut unbounded = Vec::new();
+
+        for object in objects {
+            match object.bounding_box() {
+                Some(bounds) => primitives.push(Primitive { object, bounds }),
+                None => unbounded.push(object),
+            }
+        }
+
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(2 * primitives.len()),
+            objects: Vec::with_capacity(primitives.len()),
+            unbounded,
+            split_method,
+            max_objects_per_leaf,
+        };
+        if !primitives.is_empty() {
+            bvh.build(&mut primitives);
+        }
+
+        bvh
+    }
+
+    /// Recursively builds the subtree containing the `primitives`. Returns the
+    /// index of its root node.
+    fn build(&mut self, primitives: &mut [Primitive]) -> usize {
+        let index = self.nodes.len();
+        let bounds = primitives[1..]
+            .iter()
+            .fold(primitives[0].bounds, |bounds, primitive| {
+                bounds.surrounding(&primitive.bounds)
+            });
+
+        let centroid_bounds = primitives[1..].iter().fold(
+            Aabb::new(
+                primitives[0].bounds.centroid(),
+                primitives[0].bounds.centroid(),
+            ),
+            |bounds, primitive| bounds.including(primitive.bounds.centroid()),
+        );
+
+        let split = if primitives.len() == 1 {
+            None
+        } else {
+            match self.split_method {
+                SplitMethod::Middle if primitives.len() > self.max_objects_per_leaf => {
+                    Some(split_middle(primitives, &centroid_bounds))
+                }
+                SplitMethod::Median if primitives.len() > self.max_objects_per_leaf => {
+                    Some(split_median(primitives, centroid_bounds.longest_axis()))
+                }
+                SplitMethod::Sah => split_sah(
+                    primitives,
+                    &bounds,
+                    &centroid_bounds,
+                    self.max_objects_per_leaf,
+                ),
+                _ => None,
+            }
+        };
+
+        let (axis, split) = match split {
+            Some(split) => split,
+            None => {
+                self.nodes.push(Node {
+                    bounds,
+                    kind: NodeKind::Leaf {
+                        first: self.objects.len(),
+                        count: primitives.len(),
+                    },
+                });
+                self.objects.extend(
+                    primitives
+                        .iter()
+                        .map(|primitive| Arc::clone(&primitive.object)),
+                );
+
+                return index;
+            }
+        };
+
+        // The right child index is only known once the left subtree is built.
+        self.nodes.push(Node {
+            bounds,
+            kind: NodeKind::Interior { right: 0, axis },
+        });
+        let (left, right) = primitives.split_at_mut(split);
+        self.build(left);
+        let right_index = self.build(right);
+        self.nodes[index].kind = NodeKind::Interior {
+            right: right_index,
+            axis,
+        };
+
+        index
+    }
+
+    /// Returns statistics about the shape of the hierarchy.
+    pub fn stats(&self) -> BvhStats {
+        let mut stats = BvhStats {
+            split_method: self.split_method,
+            max_objects_per_leaf: self.max_objects_per_leaf,
+            node_count: self.nodes.len(),
+            leaf_count: 0,
+            unbounded_count: self.unbounded.len(),
+            max_depth: 0,
+            leaf_size_histogram: vec![0; self.max_objects_per_leaf + 1],
+            traversal_cost: 0.0,
+        };
+
+        let root_area = match self.nodes.first() {
+            Some(root) => root.bounds.surface_area(),
+            None => return stats,
+        };
+
+        let mut stack = vec![(0, 0)];
+        while let Some((index, depth)) = stack.pop() {
+            let node = &self.nodes[index];
+            // The probability for a ray going through the root to go through
+            // the node, assuming uniformly distributed rays.
+            let probability = if root_area > 0.0 {
+                node.bounds.surface_area() / root_area
+            } else {
+                1.0
+            };
+
+            stats.max_depth = stats.max_depth.max(depth);
+            match node.kind {
+                NodeKind::Leaf { count, .. } => {
+                    stats.leaf_count += 1;
+                    if count >= stats.leaf_size_histogram.len() {
+                        stats.leaf_size_histogram.resize(count + 1, 0);
+                    }
+                    stats.leaf_size_histogram[count] += 1;
+                    stats.traversal_cost += probability * count as f64 * INTERSECTION_COST;
+                }
+                NodeKind::Interior { right, .. } => {
+                    stats.traversal_cost += probability * TRAVERSAL_COST;
+                    stack.push((index + 1, depth + 1));
+                    stack.push((right, depth + 1));
+                }
+            }
+        }
+
+        stats
+    }
+}
+
+/// Statistics about the shape of a [`Bvh`], used to compare split methods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BvhStats {
+    /// The split method the hierarchy was built with.
+    pub split_method: SplitMethod,
+    /// The maximum number of hittables per leaf the hierarchy was built with.
+    pub max_objects_per_leaf: usize,
+    /// The number of nodes, leaves included.
+    pub node_count: usize,
+    /// The number of leaves.
+    pub leaf_count: usize,
+    /// The number of unbounded hittables stored aside of the tree.
+    pub unbounded_count: usize,
+    /// The depth of the deepest leaf, the root being at depth 0.
+    pub max_depth: usize,
+    /// The number of leaves of each size: `leaf_size_histogram[n]` is the
+    /// number of leaves containing `n` hittables.
+    pub leaf_size_histogram: Vec<usize>,
+    /// The cost of tracing a ray through the tree estimated with the surface
+    /// area heuristic, in units of [`INTERSECTION_COST`].
+    pub traversal_cost: f64,
+}
+
+impl fmt::Display for BvhStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "BVH ({} split, at most {} hittable(s) per leaf): {} node(s), {} leaf(s), \
+             {} unbounded hittable(s)",
+            self.split_method,
+            self.max_objects_per_leaf,
+            self.node_count,
+            self.leaf_count,
+            self.unbounded_count
+        )?;
+        writeln!(
+            f,
+            "Depth: {}, estimated traversal cost: {:.3}",
+            self.max_depth, self.traversal_cost
+        )?;
+        write!(f, "Leaf sizes:")?;
+        for (size, count) in self.leaf_size_histogram.iter().enumerate() {
+            if *count > 0 {
+                write!(f, " {}: {}", size, count)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares the centroids of two primitives along `axis`.
+fn compare_centroids(a: &Primitive, b: &Primitive, axis: usize) -> Ordering {
+    a.bounds.centroid()[axis]
+        .partial_cmp(&b.bounds.centroid()[axis])
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Partitions the `primitives` in place so those matching `predicate` come
+/// first. Returns the number of primitives matching `predicate`.
+fn partition<P>(primitives: &mut [Primitive], predicate: P) -> usize
+where
+    P: Fn(&Primitive) -> bool,
+{
+    let mut split = 0;
+    for i in 0..primitives.len() {
+        if predicate(&primitives[i]) {
+            primitives.swap(i, split);
+            split += 1;
+        }
+    }
+
+    split
+}
+
+/// Splits the `primitives` in two halves along `axis`. Returns the split axis
+/// and the index of the first primitive of the second half.
+fn split_median(primitives: &mut [Primitive], axis: usize) -> (usize, usize) {
+    let split = primitives.len() / 2;
+    primitives.select_nth_unstable_by(split, |a, b| compare_centroids(a, b, axis));
+
+    (axis, split)
+}
+
+/// Splits the `primitives` at the middle of the longest axis of their
+/// `centroid_bounds`. Returns the split axis and the index of the first
+/// primitive of the second part.
+///
+/// Falls back to a median split so neither part is ever empty.
+fn split_middle(primitives: &mut [Primitive], centroid_bounds: &Aabb) -> (usize, usize) {
+    let axis = centroid_bounds.longest_axis();
+    let middle = centroid_bounds.centroid()[axis];
+
+    let split = partition(primitives, |primitive| {
+        primitive.bounds.centroid()[axis] < middle
+    });
+    if split == 0 || split == primitives.len() {
+        return split_median(primitives, axis);
+    }
+
+    (axis, split)
+}
+
+/// Returns the SAH bucket the `primitive` centroid falls into along `axis`.
+fn sah_bucket(primitive: &Primitive, centroid_bounds: &Aabb, axis: usize) -> usize {
+    let minimum = centroid_bounds.minimum()[axis];
+    let extent = centroid_bounds.maximum()[axis] - minimum;
+    let offset = (primitive.bounds.centroid()[axis] - minimum) / extent;
+
+    ((offset * SAH_BUCKETS as f64) as usize).min(SAH_BUCKETS - 1)
+}
+
+/// Splits the `primitives` with the lowest cost estimated by the binned
+/// surface area heuristic, trying every axis. Returns the split axis and the
+/// index of the first primitive of the second part, or [`None`] if the
+/// primitives should rather be stored in a single leaf.
+fn split_sah(
+    primitives: &mut [Primitive],
+    bounds: &Aabb,
+    centroid_bounds: &Aabb,
+    max_objects_per_leaf: usize,
+) -> Option<(usize, usize)> {
+    let mut best: Option<(f64, usize, usize)> = None;
+
+    for axis in 0..3 {
+        if centroid_bounds.maximum()[axis] <= centroid_bounds.minimum()[axis] {
+            continue;
+        }
+
+        let mut counts = [0; SAH_BUCKETS];
+        let mut buckets: [Option<Aabb>; SAH_BUCKETS] = [None; SAH_BUCKETS];
+        for primitive in primitives.iter() {
+            let bucket = sah_bucket(primitive, centroid_bounds, axis);
+            counts[bucket] += 1;
+            buckets[bucket] = Some(match buckets[bucket] {
+                Some(aabb) => aabb.surrounding(&primitive.bounds),
+                None => primitive.bounds,
+            });
+        }
+
+        // Sweep from the right to know the area and count on the right side of
+        // each split, then from the left to evaluate each split.
+        let mut right_areas = [0.0; SAH_BUCKETS];
+        let mut right_counts = [0; SAH_BUCKETS];
+        let mut right_bounds: Option<Aabb> = None;
+        let mut right_count = 0;
+        for bucket in (1..SAH_BUCKETS).rev() {
+            right_bounds = surrounding(right_bounds, buckets[bucket]);
+            right_count += counts[bucket];
+            right_areas[bucket] = right_bounds.map_or(0.0, |aabb| aabb.surface_area());
+            right_counts[bucket] = right_count;
+        }
+
+        let mut left_bounds: Option<Aabb> = None;
+        let mut left_count = 0;
+        for bucket in 0..SAH_BUCKETS - 1 {
+            left_bounds = surrounding(left_bounds, buckets[bucket]);
+            left_count += counts[bucket];
+            let right_count = right_counts[bucket + 1];
+            if left_count == 0 || right_count == 0 {
+                continue;
+            }
+
+            let left_area = left_bounds.map_or(0.0, |aabb| aabb.surface_area());
+            let cost = TRAVERSAL_COST
+                + INTERSECTION_COST
+                    * (left_count as f64 * left_area
+                        + right_count as f64 * right_areas[bucket + 1])
+                    / bounds.surface_area();
+
+            if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                best = Some((cost, axis, bucket));
+            }
+        }
+    }
+
+    let leaf_cost = primitives.len() as f64 * INTERSECTION_COST;
+    match best {
+        Some((cost, _, _)) if primitives.len() <= max_objects_per_leaf && leaf_cost <= cost => None,
+        Some((_, axis, bucket)) => {
+            let split = partition(primitives, |primitive| {
+                sah_bucket(primitive, centroid_bounds, axis) <= bucket
+            });
+
+            Some((axis, split))
+        }
+        // All centroids are at the same position, no split can separate them.
+        None if primitives.len() > max_objects_per_leaf => {
+            Some(split_median(primitives, centroid_bounds.longest_axis()))
+        }
+        None => None,
+    }
+}
+
+/// Returns the box surrounding two optional boxes.
+fn surrounding(a: Option<Aabb>, b: Option<Aabb>) -> Option<Aabb> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.surrounding(&b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+impl From<HittableCollection> for Bvh {
+    fn from(collection: HittableCollection) -> Bvh {
+        Bvh::new(collection.into_objects())
+    }
+}
+
+impl Hittable for Bvh {
+    /// Tries to hit an hittable in the hierarchy with a ray. The point of
+    /// intersection must be in the `valid_range`.
+    /// Returns a record of the closest hit, or [`None`] if no hittable can be
+    /// hit.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let t_min = *valid_range.start();
+        let mut closest = *valid_range.end();
+        let mut record: Option<HitRecord> = None;
+
+        for object in &self.unbounded {
+            if let Some(hit) = object.try_hit(ray, t_min..=closest) {
+                closest = hit.t;
+                record = Some(hit);
+            }
+        }
+
+        if self.nodes.is_empty() {
+            return record;
+        }
+
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0);
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.bounds.hit(ray, t_min..=closest) {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Leaf { first, count } => {
+                    for object in &self.objects[first..first + count] {
+                        if let Some(hit) = object.try_hit(ray, t_min..=closest) {
+                            closest = hit.t;
+                            record = Some(hit);
+                        }
+                    }
+                }
+                NodeKind::Interior { right, axis } => {
+                    // Visit the child closest to the ray origin first, so that
+                    // `closest` shrinks as fast as possible.
+                    if ray.direction()[axis] < 0.0 {
+                        stack.push(index + 1);
+                        stack.push(right);
+                    } else {
+                        stack.push(right);
+                        stack.push(index + 1);
+                    }
+                }
+            }
+        }
+
+        record
+    }
+
+    /// Returns whether any hittable of the hierarchy is hit by the ray in the
+    /// `valid_range`, stopping at the first hit found.
+    fn occluded(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> bool {
+        if self
+            .unbounded
+            .iter()
+            .any(|object| object.occluded(ray, valid_range.clone()))
+        {
+            return true;
+        }
+
+        if self.nodes.is_empty() {
+            return false;
+        }
+
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0);
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.bounds.hit(ray, valid_range.clone()) {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Leaf { first, count } => {
+                    if self.objects[first..first + count]
+                        .iter()
+                        .any(|object| object.occluded(ray, valid_range.clone()))
+                    {
+                        return true;
+                    }
+                }
+                NodeKind::Interior { right, .. } => {
+                    stack.push(right);
+                    stack.push(index + 1);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Returns the box of the root node, or [`None`] if the hierarchy is empty
+    /// or contains an unbounded hittable.
+    fn bounding_box(&self) -> Option<Aabb> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+
+        self.nodes.first().map(|node| node.bounds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        color::Color,
+        hittable::Sphere,
+        material::{Lambertian, Material},
+        random::random_range,
+        vec3::{Point3, Vec3},
+    };
+
+    use super::*;
+
+    fn random_spheres(count: usize) -> Vec<Arc<dyn Hittable + Sync + Send>> {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+
+        (0..count)
+            .map(|_| {
+                Arc::new(Sphere::new(
+                    Point3::random_range(-10.0..10.0),
+                    random_range(0.1..1.0),
+                    Arc::clone(&material) as Arc<dyn Material + Send + Sync>,
+                )) as Arc<dyn Hittable + Sync + Send>
+            })
+            .collect()
+    }
+
+    #[test]
+    fn try_hit_empty_bvh() {
+        let bvh = Bvh::new(Vec::new());
+        let ray = Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(bvh.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+        assert!(bvh.bounding_box().is_none());
+    }
+
+    #[test]
+    fn occluded_bvh_matches_collection() {
+        let objects = random_spheres(100);
+        let mut collection = HittableCollection::new();
+        objects
+            .iter()
+            .for_each(|object| collection.add(Arc::clone(object)));
+        let bvh = Bvh::new(objects);
+
+        for _ in 0..1000 {
+            let ray = Ray::new(Point3::random_range(-15.0..15.0), Vec3::random_normalized());
+            let range = 0.001..=random_range(0.0..20.0);
+
+            let expected = collection.try_hit(&ray, range.clone()).is_some();
+
+            assert_eq!(bvh.occluded(&ray, range.clone()), expected);
+            assert_eq!(collection.occluded(&ray, range), expected);
+        }
+    }
+
+    #[test]
+    fn try_hit_bvh_matches_collection() {
+        let objects = random_spheres(100);
+        let mut collection = HittableCollection::new();
+        objects
+            .iter()
+            .for_each(|object| collection.add(Arc::clone(object)));
+        let bvh = Bvh::new(objects);
+
+        for _ in 0..1000 {
+            let ray = Ray::new(Point3::random_range(-15.0..15.0), Vec3::random_normalized());
+
+            let expected = collection.try_hit(&ray, 0.001..=f64::INFINITY);
+            let result = bvh.try_hit(&ray, 0.001..=f64::INFINITY);
+
+            assert_eq!(result.map(|hit| hit.t), expected.map(|hit| hit.t));
+        }
+    }
+
+    #[test]
+    fn bounding_box_matches_collection() {
+        let objects = random_spheres(10);
+        let mut collection = HittableCollection::new();
+        objects
+            .iter()
+            .for_each(|object| collection.add(Arc::clone(object)));
+
+        assert_eq!(Bvh::new(objects).bounding_box(), collection.bounding_box());
+    }
+
+    #[test]
+    fn try_hit_bvh_matches_collection_with_every_split_method() {
+        let objects = random_spheres(200);
+        let mut collection = HittableCollection::new();
+        objects
+            .iter()
+            .for_each(|object| collection.add(Arc::clone(object)));
+
+        for &split_method in &[SplitMethod::Middle, SplitMethod::Median, SplitMethod::Sah] {
+            let bvh = Bvh::with_split_method(objects.clone(), split_method, 3);
+
+            for _ in 0..500 {
+                let ray = Ray::new(Point3::random_range(-15.0..15.0), Vec3::random_normalized());
+
+                let expected = collection.try_hit(&ray, 0.001..=f64::INFINITY);
+                let result = bvh.try_hit(&ray, 0.001..=f64::INFINITY);
+
+                assert_eq!(result.map(|hit| hit.t), expected.map(|hit| hit.t));
+            }
+        }
+    }
+
+    #[test]
+    fn stats_count_every_hittable() {
+        for &split_method in &[SplitMethod::Middle, SplitMethod::Median, SplitMethod::Sah] {
+            let stats = Bvh::with_split_method(random_spheres(100), split_method, 4).stats();
+
+            let hittables: usize = stats
+                .leaf_size_histogram
+                .iter()
+                .enumerate()
+                .map(|(size, count)| size * count)
+                .sum();
+            assert_eq!(hittables, 100);
+            assert_eq!(stats.node_count, 2 * stats.leaf_count - 1);
+            assert!(stats.leaf_size_histogram.len() <= 5);
+            assert!(stats.traversal_cost > 0.0);
+        }
+    }
+
+    #[test]
+    fn stats_of_empty_bvh() {
+        let stats = Bvh::new(Vec::new()).stats();
+
+        assert_eq!(stats.node_count, 0);
+        assert_eq!(stats.leaf_count, 0);
+        assert_eq!(stats.traversal_cost, 0.0);
+    }
+
+    #[test]
+    fn sah_is_not_worse_than_middle_split_on_clustered_spheres() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let mut objects: Vec<Arc<dyn Hittable + Sync + Send>> = random_spheres(200);
+        // A far away sphere, making the middle split put nearly everything on
+        // the same side.
+        objects.push(Arc::new(Sphere::new(
+            Point3::new(1000.0, 0.0, 0.0),
+            1.0,
+            material as Arc<dyn Material + Send + Sync>,
+        )));
+
+        let middle = Bvh::with_split_method(objects.clone(), SplitMethod::Middle, 4).stats();
+        let sah = Bvh::with_split_method(objects, SplitMethod::Sah, 4).stats();
+
+        assert!(sah.traversal_cost <= middle.traversal_cost);
+    }
+
+    #[test]
+    fn split_method_from_str_works() {
+        assert_eq!("middle".parse(), Ok(SplitMethod::Middle));
+        assert_eq!("Median".parse(), Ok(SplitMethod::Median));
+        assert_eq!(" sah\n".parse(), Ok(SplitMethod::Sah));
+        assert!("octree".parse::<SplitMethod>().is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use std::{f64::consts::PI, ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    polynomial::solve_quadratic,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{disk::disk_extent, Aabb, HitRecord, Hittable, Span};
+
+/// A cone closed by a disk at its base.
+pub struct Cone {
+    base: Point3,
+    /// The unit vector from the base to the apex.
+    axis: Vec3,
+    height: f64,
+    radius: f64,
+    /// The unit vectors from which the angle around the axis is measured.
+    basis: (Vec3, Vec3),
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Cone {
+    /// Constructs a new `Cone` whose axis goes from the center of its `base`,
+    /// of radius `radius`, to its `apex`.
+    pub fn new(
+        base: Point3,
+        apex: Point3,
+        radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Cone {
+        let height = (apex - base).length();
+        let axis = (apex - base) / height;
+
+        Cone {
+            base,
+            axis,
+            height,
+            radius,
+            basis: axis.orthonormal_basis(),
+            material,
+        }
+    }
+
+    /// Returns the distances along the whole line of the ray where it crosses
+    /// the surface of the cone, in increasing order, with whether the crossing
+    /// is on the side or on the base.
+    ///
+    /// The ray is intersected with the infinite double cone, whose hits are
+    /// kept between the base and the apex, and with the plane of the base,
+    /// whose hit is kept inside the disk.
+    fn crossings(&self, ray: &Ray) -> Vec<(f64, bool)> {
+        let oc = ray.origin() - self.base;
+        let direction = ray.direction();
+
+        // Components along the axis and perpendicular to it.
+        let origin_height = oc.dot(&self.axis);
+        let direction_height = direction.dot(&self.axis);
+        let origin_radial = oc - origin_height * self.axis;
+        let direction_radial = direction - direction_height * self.axis;
+
+        // At a given height `h`, the radius of the cone is `k * (height - h)`.
+        let k = self.radius / self.height;
+        let k_squared = k * k;
+        let origin_to_apex = self.height - origin_height;
+        let side = solve_quadratic(
+            direction_radial.length_squared() - k_squared * direction_height * direction_height,
+            2.0 * (origin_radial.dot(&direction_radial)
+                + k_squared * origin_to_apex * direction_height),
+            origin_radial.length_squared() - k_squared * origin_to_apex * origin_to_apex,
+        )
+        .into_iter()
+        .filter(|t| (0.0..=self.height).contains(&(origin_height + t * direction_height)))
+        .map(|t| (t, true));
+
+        let base = Some(-origin_height / direction_height)
+            .filter(|t| {
+                direction_height != 0.0
+                    && (origin_radial + *t * direction_radial).length_squared()
+                        <= self.radius * self.radius
+            })
+            .map(|t| (t, false));
+
+        let mut crossings: Vec<(f64, bool)> = side.chain(base).collect();
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+        crossings
+    }
+
+    /// Constructs the `HitRecord` of a ray hitting the cone at `t`, on its side
+    /// or on its base.
+    fn hit_record(&self, ray: &Ray, t: f64, on_side: bool) -> HitRecord<'_> {
+        let offset = ray.at(t) - self.base;
+        let height = offset.dot(&self.axis);
+        let radial = offset - height * self.axis;
+        let outward_normal = if on_side {
+            // Perpendicular to the slope going from the base edge to the apex,
+            // or along the axis at the apex.
+            let radial_direction = if radial.is_near_zero() {
+                Vec3::zero()
+            } else {
+                radial.normalized()
+            };
+            (self.height * radial_direction + self.radius * self.axis).normalized()
+        } else {
+            -self.axis
+        };
+
+        let mut record = HitRecord::new(ray, t, outward_normal, Arc::clone(&self.material));
+        // `u` goes around the axis. `v` goes along it on the side and from the
+        // center to the edge on the base.
+        record.uv = if on_side {
+            (self.angle(radial), height / self.height)
+        } else {
+            (self.angle(radial), radial.length() / self.radius)
+        };
+        record.set_shading_frame(outward_normal, self.axis.cross(&radial));
+
+        record
+    }
+
+    /// Returns the angle around the axis of a vector perpendicular to it,
+    /// normalized to [0, 1].
+    fn angle(&self, radial: Vec3) -> f64 {
+        let phi = radial.dot(&self.basis.1).atan2(radial.dot(&self.basis.0));
+        (phi + PI) / (2.0 * PI)
+    }
+}
+
+impl Hittable for Cone {
+    /// Tries to hit a cone with a ray. The intersection point must be in the
+    /// `valid_range`.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        self.crossings(ray)
+            .into_iter()
+            .find(|(t, _)| valid_range.contains(t))
+            .map(|(t, on_side)| self.hit_record(ray, t, on_side))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = disk_extent(self.axis, self.radius);
+
+        Some(
+            Aabb::new(self.base - extent, self.base + extent)
+                .including(self.base + self.height * self.axis),
+        )
+    }
+
+    fn spans(&self, ray: &Ray) -> Option<Vec<Span<'_>>> {
+        // The cone is convex, so the ray is inside between the first and the
+        // last crossings.
+        let crossings = self.crossings(ray);
+        let spans = match (crossings.first(), crossings.last()) {
+            (Some(&(entry, entry_on_side)), Some(&(exit, exit_on_side))) if entry < exit => {
+                vec![Span {
+                    entry: self.hit_record(ray, entry, entry_on_side),
+                    exit: self.hit_record(ray, exit, exit_on_side),
+                }]
+            }
+            _ => Vec::new(),
+        };
+
+        Some(spans)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, material::Lambertian};
+
+    use super::*;
+
+    fn cone() -> Cone {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Cone::new(
+            Point3::new(0.0, -1.0, -3.0),
+            Point3::new(0.0, 1.0, -3.0),
+            1.0,
+            material as Arc<dyn Material + Send + Sync>,
+        )
+    }
+
+    #[test]
+    fn try_hit_cone_with_not_hitting_ray() {
+        let cone = cone();
+        let ray = Ray::new(Point3::new(0.0, 1.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(cone.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_cone_with_ray_through_the_other_nappe() {
+        let cone = cone();
+        // Hits the mirrored cone above the apex, which isn't part of the cone.
+        let ray = Ray::new(Point3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(cone.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_cone_with_hitting_ray_not_in_range() {
+        let cone = cone();
+        let ray = Ray::new(Point3::zero(), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(cone.try_hit(&ray, 0.0..=1.0).is_none());
+    }
+
+    #[test]
+    fn try_hit_cone_side_with_hitting_ray_in_range() {
+        let cone = cone();
+        let ray = Ray::new(Point3::zero(), Vec3::new(0.0, 0.0, -1.0));
+
+        let result = cone
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit cone");
+
+        assert!((result.intersection_point - Point3::new(0.0, 0.0, -2.5)).length() < 1e-12);
+        assert!((result.t - 2.5).abs() < 1e-12);
+        let expected_normal = Vec3::new(0.0, 1.0, 2.0).normalized();
+        assert!((result.normal - expected_normal).length() < 1e-12);
+        assert!(result.front_face);
+        assert!((result.uv.1 - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn try_hit_cone_base_with_hitting_ray_in_range() {
+        let cone = cone();
+        let ray = Ray::new(Point3::new(0.5, -3.0, -3.0), Vec3::new(0.0, 1.0, 0.0));
+
+        let result = cone
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit cone");
+
+        assert_eq!(result.intersection_point, Point3::new(0.5, -1.0, -3.0));
+        assert_eq!(result.t, 2.0);
+        assert_eq!(result.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(result.front_face);
+    }
+
+    #[test]
+    fn spans_of_cone_along_its_axis() {
+        let cone = cone();
+        let ray = Ray::new(Point3::new(0.0, 5.0, -3.0), Vec3::new(0.0, -1.0, 0.0));
+
+        let spans = cone.spans(&ray).expect("cone should support spans");
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].entry.t, 4.0);
+        assert!(spans[0].entry.front_face);
+        assert_eq!(spans[0].exit.t, 6.0);
+        assert_eq!(spans[0].exit.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!(!spans[0].exit.front_face);
+    }
+
+    #[test]
+    fn bounding_box_encloses_cone() {
+        assert_eq!(
+            cone().bounding_box(),
+            Some(Aabb::new(
+                Point3::new(-1.0, -1.0, -4.0),
+                Point3::new(1.0, 1.0, -2.0)
+            ))
+        );
+    }
+}
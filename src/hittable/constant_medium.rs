@@ -0,0 +1,224 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{material::Material, random::random, ray::Ray, vec3::Vec3};
+
+use super::{Aabb, HitRecord, Hittable};
+
+/// A participating medium of constant density (e.g. fog or smoke), filling a
+/// closed boundary.
+///
+/// Rays going through the medium scatter at random distances, more often as
+/// the density grows. The scattering is given by a phase function material,
+/// such as [`Isotropic`](crate::material::Isotropic).
+///
+/// See [Ray Tracing: The Next Week, chapter 9](https://raytracing.github.io/books/RayTracingTheNextWeek.html#volumes).
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable + Sync + Send>,
+    density: f64,
+    phase_function: Arc<dyn Material + Sync + Send>,
+}
+
+impl ConstantMedium {
+    /// Constructs a new `ConstantMedium` filling the `boundary`, of `density`
+    /// scattering events per unit of length.
+    pub fn new(
+        boundary: Arc<dyn Hittable + Sync + Send>,
+        density: f64,
+        phase_function: Arc<dyn Material + Sync + Send>,
+    ) -> ConstantMedium {
+        ConstantMedium {
+            boundary,
+            density,
+            phase_function,
+        }
+    }
+}
+
+/// Returns the intervals of the ray inside the `boundary`, restricted to the
+/// `valid_range`, in increasing order.
+///
+/// The spans of the boundary are used when it supports them. Otherwise the
+/// boundary is assumed convex: the ray is inside it between the first two
+/// hits.
+pub(super) fn inside_intervals(
+    boundary: &(dyn Hittable + Sync + Send),
+    ray: &Ray,
+    valid_range: RangeInclusive<f64>,
+) -> Vec<(f64, f64)> {
+    let (start, end) = (*valid_range.start(), *valid_range.end());
+    let clip = |(entry, exit): (f64, f64)| {
+        let (entry, exit) = (entry.max(start), exit.min(end));
+        if entry < exit {
+            Some((entry, exit))
+        } else {
+            None
+        }
+    };
+
+    if let Some(spans) = boundary.spans(ray) {
+        return spans
+            .iter()
+            .filter_map(|span| clip((span.entry.t, span.exit.t)))
+            .collect();
+    }
+
+    let entry = match boundary.try_hit(ray, f64::NEG_INFINITY..=f64::INFINITY) {
+        Some(entry) => entry.t,
+        None => return Vec::new(),
+    };
+    let exit = match boundary.try_hit(ray, entry + 0.0001..=f64::INFINITY) {
+        Some(exit) => exit.t,
+        None => return Vec::new(),
+    };
+
+    clip((entry, exit)).into_iter().collect()
+}
+
+/// Constructs the `HitRecord` of a ray scattering inside a medium at `t`.
+///
+/// A point inside a medium has no surface, so its normal is arbitrary.
+pub(super) fn scattering_record<'a>(
+    ray: &Ray,
+    t: f64,
+    phase_function: Arc<dyn Material + Sync + Send + 'a>,
+) -> HitRecord<'a> {
+    let mut record = HitRecord::new(ray, t, -ray.direction(), phase_function);
+    record.front_face = true;
+    record.in_medium = true;
+    record.normal = Vec3::new(1.0, 0.0, 0.0);
+    record.set_shading_frame(record.normal, Vec3::new(0.0, 1.0, 0.0));
+
+    record
+}
+
+impl Hittable for ConstantMedium {
+    /// Tries to scatter a ray inside the medium. The scattering point must be
+    /// in the `valid_range`.
+    ///
+    /// The distance travelled before scattering follows an exponential
+    /// distribution. As it has no memory, it is sampled anew for each part of
+    /// the ray inside the boundary.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let ray_length = ray.direction().length();
+
+        for (entry, exit) in inside_intervals(self.boundary.as_ref(), ray, valid_range) {
+            let distance_inside = (exit - entry) * ray_length;
+            let hit_distance = -random().ln() / self.density;
+
+            if hit_distance < distance_inside {
+                return Some(scattering_record(
+                    ray,
+                    entry + hit_distance / ray_length,
+                    Arc::clone(&self.phase_function),
+                ));
+            }
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        color::Color,
+        hittable::{Cuboid, Sphere, Torus},
+        material::Isotropic,
+        vec3::Point3,
+    };
+
+    use super::*;
+
+    fn medium(boundary: Arc<dyn Hittable + Sync + Send>, density: f64) -> ConstantMedium {
+        ConstantMedium::new(
+            boundary,
+            density,
+            Arc::new(Isotropic::new(Color::new(1.0, 1.0, 1.0))),
+        )
+    }
+
+    fn sphere() -> Arc<dyn Hittable + Sync + Send> {
+        Arc::new(Sphere::new(
+            Point3::zero(),
+            1.0,
+            Arc::new(Isotropic::new(Color::zero())),
+        ))
+    }
+
+    #[test]
+    fn try_hit_dense_medium_scatters_at_the_boundary() {
+        let medium = medium(sphere(), 1e9);
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0));
+
+        let result = medium
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should scatter in medium");
+
+        assert!((result.t - 2.0).abs() < 1e-6);
+        assert!(result.front_face);
+    }
+
+    #[test]
+    fn try_hit_medium_from_inside_scatters_ahead() {
+        let medium = medium(sphere(), 1e9);
+        let ray = Ray::new(Point3::new(0.5, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+
+        let result = medium
+            .try_hit(&ray, 0.001..=f64::INFINITY)
+            .expect("ray should scatter in medium");
+
+        assert!((result.t - 0.001).abs() < 1e-6);
+    }
+
+    #[test]
+    fn try_hit_medium_with_not_hitting_ray() {
+        let medium = medium(sphere(), 1e9);
+        let ray = Ray::new(Point3::new(-5.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(medium.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_medium_transmits_following_the_density() {
+        let cube = Arc::new(Cuboid::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Arc::new(Isotropic::new(Color::zero())),
+        ));
+        let medium = medium(cube, 2.0);
+        let ray = Ray::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        let samples = 100_000;
+
+        let transmitted = (0..samples)
+            .filter(|_| medium.try_hit(&ray, 0.0..=f64::INFINITY).is_none())
+            .count();
+
+        // Beer-Lambert law: exp(-density * distance).
+        let expected = (-2.0_f64).exp();
+        assert!((transmitted as f64 / samples as f64 - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn inside_intervals_of_non_convex_boundary() {
+        let torus: Arc<dyn Hittable + Sync + Send> = Arc::new(Torus::new(
+            Point3::zero(),
+            Vec3::new(0.0, 1.0, 0.0),
+            2.0,
+            0.5,
+            Arc::new(Isotropic::new(Color::zero())),
+        ));
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let intervals = inside_intervals(torus.as_ref(), &ray, 0.0..=7.0);
+
+        assert_eq!(intervals.len(), 2);
+        assert!((intervals[0].0 - 2.5).abs() < 1e-9);
+        assert!((intervals[0].1 - 3.5).abs() < 1e-9);
+        assert!((intervals[1].0 - 6.5).abs() < 1e-9);
+        assert_eq!(intervals[1].1, 7.0);
+    }
+}
@@ -0,0 +1,303 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::ray::Ray;
+
+use super::{Aabb, HitRecord, Hittable, Span};
+
+/// A boolean operation combining two solids.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CsgOperation {
+    /// The points inside either solid.
+    Union,
+    /// The points inside both solids.
+    Intersection,
+    /// The points inside the first solid but not inside the second one.
+    Difference,
+}
+
+impl CsgOperation {
+    /// Returns whether a point is inside the result of the operation, knowing
+    /// whether it is inside the `left` and the `right` solids.
+    fn contains(self, left: bool, right: bool) -> bool {
+        match self {
+            CsgOperation::Union => left || right,
+            CsgOperation::Intersection => left && right,
+            CsgOperation::Difference => left && !right,
+        }
+    }
+}
+
+/// A solid made of two solids combined by a [`CsgOperation`], i.e. a node of a
+/// constructive solid geometry tree.
+///
+/// Both solids must be closed hittables supporting [`Hittable::spans`], such
+/// as spheres, boxes, cylinders or other `Csg`s. A `Csg` made of other
+/// hittables is never hit.
+///
+/// See [Constructive solid geometry on Wikipedia](https://en.wikipedia.org/wiki/Constructive_solid_geometry).
+pub struct Csg {
+    operation: CsgOperation,
+    left: Arc<dyn Hittable + Sync + Send>,
+    right: Arc<dyn Hittable + Sync + Send>,
+}
+
+impl Csg {
+    /// Constructs a new `Csg` combining the `left` and `right` solids.
+    pub fn new(
+        operation: CsgOperation,
+        left: Arc<dyn Hittable + Sync + Send>,
+        right: Arc<dyn Hittable + Sync + Send>,
+    ) -> Csg {
+        Csg {
+            operation,
+            left,
+            right,
+        }
+    }
+}
+
+/// A point where a ray enters or exits one of the solids of a [`Csg`].
+struct Crossing<'a> {
+    record: HitRecord<'a>,
+    is_left: bool,
+    is_entry: bool,
+}
+
+/// Returns the crossings of the `spans` of a solid.
+fn crossings(spans: Vec<Span<'_>>, is_left: bool) -> impl Iterator<Item = Crossing<'_>> {
+    spans.into_iter().flat_map(move |span| {
+        vec![
+            Crossing {
+                record: span.entry,
+                is_left,
+                is_entry: true,
+            },
+            Crossing {
+                record: span.exit,
+                is_left,
+                is_entry: false,
+            },
+        ]
+    })
+}
+
+impl Hittable for Csg {
+    /// Tries to hit the combined solid with a ray. The intersection point must
+    /// be in the `valid_range`.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        if let Some(bounds) = self.bounding_box() {
+            if !bounds.hit(ray, valid_range.clone()) {
+                return None;
+            }
+        }
+
+        self.spans(ray)?
+            .into_iter()
+            .flat_map(|span| vec![span.entry, span.exit])
+            .find(|record| valid_range.contains(&record.t))
+    }
+
+    /// Returns the box enclosing the combined solid. An intersection is enclosed
+    /// by the box of either solid, and a difference by the box of the first
+    /// solid.
+    fn bounding_box(&self) -> Option<Aabb> {
+        match self.operation {
+            CsgOperation::Union => Some(
+                self.left
+                    .bounding_box()?
+                    .surrounding(&self.right.bounding_box()?),
+            ),
+            CsgOperation::Intersection => self
+                .left
+                .bounding_box()
+                .or_else(|| self.right.bounding_box()),
+            CsgOperation::Difference => self.left.bounding_box(),
+        }
+    }
+
+    /// Returns the spans of the combined solid, computed by walking along the
+    /// ray through the crossings of both solids, or [`None`] if one of the
+    /// solids doesn't support spans.
+    fn spans(&self, ray: &Ray) -> Option<Vec<Span<'_>>> {
+        let left = self.left.spans(ray)?;
+        let right = self.right.spans(ray)?;
+
+        let mut crossings: Vec<Crossing> = crossings(left, true)
+            .chain(crossings(right, false))
+            .collect();
+        crossings.sort_by(|a, b| a.record.t.total_cmp(&b.record.t));
+
+        let (mut in_left, mut in_right) = (false, false);
+        let mut spans = Vec::new();
+        let mut entry = None;
+        for mut crossing in crossings {
+            let was_inside = self.operation.contains(in_left, in_right);
+            if crossing.is_left {
+                in_left = crossing.is_entry;
+            } else {
+                in_right = crossing.is_entry;
+            }
+            let inside = self.operation.contains(in_left, in_right);
+
+            if inside != was_inside {
+                // The normal already faces against the ray, but the surface may
+                // be seen from the other side: exiting the second solid of a
+                // difference enters the result.
+                crossing.record.front_face = inside;
+                if inside {
+                    entry = Some(crossing.record);
+                } else if let Some(entry) = entry.take() {
+                    spans.push(Span {
+                        entry,
+                        exit: crossing.record,
+                    });
+                }
+            }
+        }
+
+        Some(spans)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        color::Color,
+        material::{Lambertian, Material},
+        vec3::{Point3, Vec3},
+    };
+
+    use super::super::{Plane, Sphere};
+    use super::*;
+
+    fn sphere(x: f64) -> Arc<dyn Hittable + Sync + Send> {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Arc::new(Sphere::new(
+            Point3::new(x, 0.0, 0.0),
+            1.0,
+            material as Arc<dyn Material + Send + Sync>,
+        ))
+    }
+
+    /// Returns the distances of the entries and exits of the spans.
+    fn span_distances(csg: &Csg, ray: &Ray) -> Vec<(f64, f64)> {
+        csg.spans(ray)
+            .expect("csg should support spans")
+            .iter()
+            .map(|span| (span.entry.t, span.exit.t))
+            .collect()
+    }
+
+    #[test]
+    fn spans_of_union() {
+        let csg = Csg::new(CsgOperation::Union, sphere(0.0), sphere(1.0));
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert_eq!(span_distances(&csg, &ray), vec![(4.0, 7.0)]);
+    }
+
+    #[test]
+    fn spans_of_disjoint_union() {
+        let csg = Csg::new(CsgOperation::Union, sphere(0.0), sphere(3.0));
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert_eq!(span_distances(&csg, &ray), vec![(4.0, 6.0), (7.0, 9.0)]);
+    }
+
+    #[test]
+    fn spans_of_intersection() {
+        let csg = Csg::new(CsgOperation::Intersection, sphere(0.0), sphere(1.0));
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert_eq!(span_distances(&csg, &ray), vec![(5.0, 6.0)]);
+    }
+
+    #[test]
+    fn spans_of_difference() {
+        let csg = Csg::new(CsgOperation::Difference, sphere(0.0), sphere(1.0));
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert_eq!(span_distances(&csg, &ray), vec![(4.0, 5.0)]);
+    }
+
+    #[test]
+    fn try_hit_difference_through_the_hole() {
+        // A sphere with a bite taken out of its right side.
+        let csg = Csg::new(CsgOperation::Difference, sphere(0.0), sphere(1.5));
+        let ray = Ray::new(Point3::new(5.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+
+        let result = csg
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit csg");
+
+        // The surface of the bite faces the ray and is entered from outside.
+        assert_eq!(result.intersection_point, Point3::new(0.5, 0.0, 0.0));
+        assert_eq!(result.t, 4.5);
+        assert_eq!(result.normal, Vec3::new(1.0, 0.0, 0.0));
+        assert!(result.front_face);
+    }
+
+    #[test]
+    fn try_hit_union_from_inside_skips_the_inner_surfaces() {
+        let csg = Csg::new(CsgOperation::Union, sphere(0.0), sphere(1.0));
+        let ray = Ray::new(Point3::new(0.5, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let result = csg
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit csg");
+
+        // The ray crosses the surface of the first sphere inside the second one.
+        assert_eq!(result.intersection_point, Point3::new(2.0, 0.0, 0.0));
+        assert_eq!(result.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(!result.front_face);
+    }
+
+    #[test]
+    fn try_hit_nested_csg_from_inside() {
+        let union = Arc::new(Csg::new(CsgOperation::Union, sphere(0.0), sphere(1.0)));
+        let csg = Csg::new(CsgOperation::Difference, union, sphere(-1.0));
+        let ray = Ray::new(Point3::new(0.5, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let result = csg
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit csg");
+
+        assert_eq!(result.intersection_point, Point3::new(2.0, 0.0, 0.0));
+        assert_eq!(result.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(!result.front_face);
+    }
+
+    #[test]
+    fn try_hit_csg_with_hitting_ray_not_in_range() {
+        let csg = Csg::new(CsgOperation::Union, sphere(0.0), sphere(1.0));
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(csg.try_hit(&ray, 0.0..=3.0).is_none());
+        assert_eq!(
+            csg.try_hit(&ray, 5.0..=10.0).map(|record| record.t),
+            Some(7.0)
+        );
+    }
+
+    #[test]
+    fn csg_of_open_hittable_is_not_hit() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let plane = Arc::new(Plane::new(
+            Point3::zero(),
+            Vec3::new(1.0, 0.0, 0.0),
+            material,
+        ));
+        let csg = Csg::new(CsgOperation::Intersection, plane, sphere(0.0));
+        let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(csg.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+        assert!(csg.spans(&ray).is_none());
+    }
+
+    #[test]
+    fn bounding_box_of_difference_is_the_first_solid_box() {
+        let csg = Csg::new(CsgOperation::Difference, sphere(0.0), sphere(3.0));
+
+        assert_eq!(csg.bounding_box(), sphere(0.0).bounding_box());
+    }
+}
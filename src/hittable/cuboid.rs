@@ -0,0 +1,216 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, HitRecord, Hittable, Quad, Span};
+
+/// An axis-aligned box, made of six [`Quad`]s whose normals point outward.
+///
+/// It isn't named `Box` to avoid shadowing [`std::boxed::Box`].
+pub struct Cuboid {
+    bounds: Aabb,
+    /// The sides on the positive side of the X, Y and Z axes, then the ones on
+    /// their negative side.
+    sides: Vec<Quad>,
+}
+
+impl Cuboid {
+    /// Constructs a new `Cuboid` from two opposite corners.
+    pub fn new(a: Point3, b: Point3, material: Arc<dyn Material + Sync + Send>) -> Cuboid {
+        let bounds = Aabb::new(a, b);
+        let (min, max) = (bounds.minimum(), bounds.maximum());
+        let extent = max - min;
+        let x = Vec3::new(extent.x(), 0.0, 0.0);
+        let y = Vec3::new(0.0, extent.y(), 0.0);
+        let z = Vec3::new(0.0, 0.0, extent.z());
+
+        // Each side is a (corner, u, v) triplet, with `u × v` pointing outward.
+        let sides_edges = [
+            (Point3::new(max.x(), min.y(), min.z()), y, z),
+            (Point3::new(min.x(), max.y(), min.z()), z, x),
+            (Point3::new(min.x(), min.y(), max.z()), x, y),
+            (min, z, y),
+            (min, x, z),
+            (min, y, x),
+        ];
+        let sides = sides_edges
+            .iter()
+            .map(|&(corner, u, v)| Quad::new(corner, u, v, Arc::clone(&material)))
+            .collect();
+
+        Cuboid { bounds, sides }
+    }
+
+    /// Returns the distances along the whole line of the ray where it enters
+    /// and exits the box, with the index of the side it goes through, or
+    /// [`None`] if it misses the box.
+    ///
+    /// See [slab method on Wikipedia](https://en.wikipedia.org/wiki/Slab_method).
+    fn crossing(&self, ray: &Ray) -> Option<((f64, usize), (f64, usize))> {
+        let mut entry = (f64::NEG_INFINITY, 0);
+        let mut exit = (f64::INFINITY, 0);
+
+        for axis in 0..3 {
+            let inverse_direction = 1.0 / ray.direction()[axis];
+            let t0 = (self.bounds.minimum()[axis] - ray.origin()[axis]) * inverse_direction;
+            let t1 = (self.bounds.maximum()[axis] - ray.origin()[axis]) * inverse_direction;
+
+            // A ray going toward the positive side enters through the negative
+            // side.
+            let (near, far) = if inverse_direction < 0.0 {
+                ((t1, axis), (t0, axis + 3))
+            } else {
+                ((t0, axis + 3), (t1, axis))
+            };
+            if near.0 > entry.0 {
+                entry = near;
+            }
+            if far.0 < exit.0 {
+                exit = far;
+            }
+        }
+
+        if entry.0.is_finite() && exit.0.is_finite() && entry.0 <= exit.0 {
+            Some((entry, exit))
+        } else {
+            None
+        }
+    }
+}
+
+impl Hittable for Cuboid {
+    /// Tries to hit a box with a ray. The intersection point must be in the
+    /// `valid_range`.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let (entry, exit) = self.crossing(ray)?;
+
+        [entry, exit]
+            .iter()
+            .find(|(t, _)| valid_range.contains(t))
+            .map(|&(t, side)| self.sides[side].hit_record(ray, t))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+
+    fn spans(&self, ray: &Ray) -> Option<Vec<Span<'_>>> {
+        let spans = self
+            .crossing(ray)
+            .map(|((entry, entry_side), (exit, exit_side))| Span {
+                entry: self.sides[entry_side].hit_record(ray, entry),
+                exit: self.sides[exit_side].hit_record(ray, exit),
+            });
+
+        Some(spans.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, material::Lambertian};
+
+    use super::*;
+
+    fn cuboid() -> Cuboid {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Cuboid::new(
+            Point3::new(1.0, 1.0, -1.0),
+            Point3::new(-1.0, -1.0, -3.0),
+            material as Arc<dyn Material + Send + Sync>,
+        )
+    }
+
+    #[test]
+    fn try_hit_cuboid_with_not_hitting_ray() {
+        let ray = Ray::new(Point3::zero(), Vec3::new(0.0, 1.0, 0.0));
+
+        assert!(cuboid().try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_cuboid_returns_closest_side() {
+        let ray = Ray::new(Point3::new(0.5, 0.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        let cuboid = cuboid();
+
+        let result = cuboid
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit cuboid");
+
+        assert_eq!(result.intersection_point, Point3::new(0.5, 0.5, -1.0));
+        assert_eq!(result.t, 1.0);
+        assert_eq!(result.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(result.front_face);
+    }
+
+    #[test]
+    fn try_hit_cuboid_normals_point_outward() {
+        let center = Point3::new(0.0, 0.0, -2.0);
+        let directions = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        ];
+
+        for direction in &directions {
+            let ray = Ray::new(center + 2.0 * *direction, -*direction);
+            let cuboid = cuboid();
+            let result = cuboid
+                .try_hit(&ray, 0.0..=f64::INFINITY)
+                .expect("ray should hit cuboid");
+
+            assert_eq!(result.normal, *direction);
+            assert!(result.front_face);
+        }
+    }
+
+    #[test]
+    fn try_hit_cuboid_with_ray_along_a_side() {
+        let ray = Ray::new(Point3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        let cuboid = cuboid();
+        let result = cuboid
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit cuboid");
+
+        assert_eq!(result.intersection_point, Point3::new(1.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn spans_of_cuboid() {
+        let ray = Ray::new(Point3::new(0.0, 0.0, -2.0), Vec3::new(0.0, 1.0, 0.0));
+
+        let cuboid = cuboid();
+        let spans = cuboid.spans(&ray).expect("cuboid should support spans");
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].entry.t, -1.0);
+        assert_eq!(spans[0].entry.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(spans[0].entry.front_face);
+        assert_eq!(spans[0].exit.t, 1.0);
+        assert_eq!(spans[0].exit.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(!spans[0].exit.front_face);
+    }
+
+    #[test]
+    fn try_hit_cuboid_from_inside() {
+        let ray = Ray::new(Point3::new(0.0, 0.0, -2.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let cuboid = cuboid();
+
+        let result = cuboid
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit cuboid");
+
+        assert_eq!(result.intersection_point, Point3::new(1.0, 0.0, -2.0));
+        assert!(!result.front_face);
+    }
+}
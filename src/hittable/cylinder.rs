@@ -0,0 +1,265 @@
+use std::{f64::consts::PI, ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    polynomial::solve_quadratic,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{disk::disk_extent, Aabb, HitRecord, Hittable, Span};
+
+/// A cylinder closed by a disk at each end.
+pub struct Cylinder {
+    base: Point3,
+    /// The unit vector from the base to the top.
+    axis: Vec3,
+    height: f64,
+    radius: f64,
+    /// The unit vectors from which the angle around the axis is measured.
+    basis: (Vec3, Vec3),
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Cylinder {
+    /// Constructs a new `Cylinder` whose axis goes from the center of its
+    /// `base` to the center of its `top`.
+    pub fn new(
+        base: Point3,
+        top: Point3,
+        radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Cylinder {
+        let height = (top - base).length();
+        let axis = (top - base) / height;
+
+        Cylinder {
+            base,
+            axis,
+            height,
+            radius,
+            basis: axis.orthonormal_basis(),
+            material,
+        }
+    }
+
+    /// Returns the distances along the whole line of the ray where it crosses
+    /// the surface of the cylinder, in increasing order, with whether the
+    /// crossing is on the side or on a cap.
+    ///
+    /// The ray is intersected with the infinite cylinder, whose hits are kept
+    /// between the caps, and with the planes of the caps, whose hits are kept
+    /// inside the disks.
+    fn crossings(&self, ray: &Ray) -> Vec<(f64, bool)> {
+        let oc = ray.origin() - self.base;
+        let direction = ray.direction();
+
+        // Components along the axis and perpendicular to it.
+        let origin_height = oc.dot(&self.axis);
+        let direction_height = direction.dot(&self.axis);
+        let origin_radial = oc - origin_height * self.axis;
+        let direction_radial = direction - direction_height * self.axis;
+
+        let side = solve_quadratic(
+            direction_radial.length_squared(),
+            2.0 * origin_radial.dot(&direction_radial),
+            origin_radial.length_squared() - self.radius * self.radius,
+        )
+        .into_iter()
+        .filter(|t| (0.0..=self.height).contains(&(origin_height + t * direction_height)))
+        .map(|t| (t, true));
+
+        let cap_heights = [0.0, self.height];
+        let caps = cap_heights
+            .iter()
+            .filter(|_| direction_height != 0.0)
+            .map(|cap_height| (cap_height - origin_height) / direction_height)
+            .filter(|t| {
+                (origin_radial + *t * direction_radial).length_squared()
+                    <= self.radius * self.radius
+            })
+            .map(|t| (t, false));
+
+        let mut crossings: Vec<(f64, bool)> = side.chain(caps).collect();
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+        crossings
+    }
+
+    /// Constructs the `HitRecord` of a ray hitting the cylinder at `t`, on its
+    /// side or on a cap.
+    fn hit_record(&self, ray: &Ray, t: f64, on_side: bool) -> HitRecord<'_> {
+        let offset = ray.at(t) - self.base;
+        let height = offset.dot(&self.axis);
+        let radial = offset - height * self.axis;
+        let outward_normal = if on_side {
+            radial / self.radius
+        } else if height > 0.5 * self.height {
+            self.axis
+        } else {
+            -self.axis
+        };
+
+        let mut record = HitRecord::new(ray, t, outward_normal, Arc::clone(&self.material));
+        // `u` goes around the axis. `v` goes along it on the side and from the
+        // center to the edge on the caps.
+        record.uv = if on_side {
+            (self.angle(radial), height / self.height)
+        } else {
+            (self.angle(radial), radial.length() / self.radius)
+        };
+        record.set_shading_frame(outward_normal, self.axis.cross(&radial));
+
+        record
+    }
+
+    /// Returns the angle around the axis of a vector perpendicular to it,
+    /// normalized to [0, 1].
+    fn angle(&self, radial: Vec3) -> f64 {
+        let phi = radial.dot(&self.basis.1).atan2(radial.dot(&self.basis.0));
+        (phi + PI) / (2.0 * PI)
+    }
+}
+
+impl Hittable for Cylinder {
+    /// Tries to hit a cylinder with a ray. The intersection point must be in
+    /// the `valid_range`.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        self.crossings(ray)
+            .into_iter()
+            .find(|(t, _)| valid_range.contains(t))
+            .map(|(t, on_side)| self.hit_record(ray, t, on_side))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = disk_extent(self.axis, self.radius);
+        let top = self.base + self.height * self.axis;
+
+        Some(
+            Aabb::new(self.base - extent, self.base + extent)
+                .surrounding(&Aabb::new(top - extent, top + extent)),
+        )
+    }
+
+    fn spans(&self, ray: &Ray) -> Option<Vec<Span<'_>>> {
+        // The cylinder is convex, so the ray is inside between the first and
+        // the last crossings.
+        let crossings = self.crossings(ray);
+        let spans = match (crossings.first(), crossings.last()) {
+            (Some(&(entry, entry_on_side)), Some(&(exit, exit_on_side))) if entry < exit => {
+                vec![Span {
+                    entry: self.hit_record(ray, entry, entry_on_side),
+                    exit: self.hit_record(ray, exit, exit_on_side),
+                }]
+            }
+            _ => Vec::new(),
+        };
+
+        Some(spans)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, material::Lambertian};
+
+    use super::*;
+
+    fn cylinder() -> Cylinder {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Cylinder::new(
+            Point3::new(0.0, -1.0, -3.0),
+            Point3::new(0.0, 1.0, -3.0),
+            1.0,
+            material as Arc<dyn Material + Send + Sync>,
+        )
+    }
+
+    #[test]
+    fn try_hit_cylinder_with_not_hitting_ray() {
+        let cylinder = cylinder();
+        let ray = Ray::new(Point3::new(0.0, 1.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(cylinder.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_cylinder_with_hitting_ray_not_in_range() {
+        let cylinder = cylinder();
+        let ray = Ray::new(Point3::zero(), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(cylinder.try_hit(&ray, 0.0..=1.0).is_none());
+    }
+
+    #[test]
+    fn try_hit_cylinder_side_with_hitting_ray_in_range() {
+        let cylinder = cylinder();
+        let ray = Ray::new(Point3::new(0.0, 0.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        let result = cylinder
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit cylinder");
+
+        assert_eq!(result.intersection_point, Point3::new(0.0, 0.5, -2.0));
+        assert_eq!(result.t, 2.0);
+        assert_eq!(result.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(result.front_face);
+        assert_eq!(result.uv.1, 0.75);
+    }
+
+    #[test]
+    fn try_hit_cylinder_cap_with_hitting_ray_in_range() {
+        let cylinder = cylinder();
+        let ray = Ray::new(Point3::new(0.5, -3.0, -3.0), Vec3::new(0.0, 1.0, 0.0));
+
+        let result = cylinder
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit cylinder");
+
+        assert_eq!(result.intersection_point, Point3::new(0.5, -1.0, -3.0));
+        assert_eq!(result.t, 2.0);
+        assert_eq!(result.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(result.front_face);
+        assert_eq!(result.uv.1, 0.5);
+    }
+
+    #[test]
+    fn try_hit_cylinder_from_inside() {
+        let cylinder = cylinder();
+        let ray = Ray::new(Point3::new(0.0, 0.0, -3.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let result = cylinder
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit cylinder");
+
+        assert_eq!(result.intersection_point, Point3::new(1.0, 0.0, -3.0));
+        assert_eq!(result.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(!result.front_face);
+    }
+
+    #[test]
+    fn spans_of_cylinder_through_side_and_cap() {
+        let cylinder = cylinder();
+        let ray = Ray::new(Point3::new(-0.5, -2.0, -3.0), Vec3::new(1.0, 1.0, 0.0));
+
+        let spans = cylinder.spans(&ray).expect("cylinder should support spans");
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].entry.t, 1.0);
+        assert_eq!(spans[0].entry.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(spans[0].entry.front_face);
+        assert_eq!(spans[0].exit.t, 1.5);
+        assert_eq!(spans[0].exit.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(!spans[0].exit.front_face);
+    }
+
+    #[test]
+    fn bounding_box_encloses_cylinder() {
+        assert_eq!(
+            cylinder().bounding_box(),
+            Some(Aabb::new(
+                Point3::new(-1.0, -1.0, -4.0),
+                Point3::new(1.0, 1.0, -2.0)
+            ))
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::{f64::consts::PI, ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, HitRecord, Hittable};
+
+/// A flat disk.
+pub struct Disk {
+    center: Point3,
+    normal: Vec3,
+    radius: f64,
+    tangent: Vec3,
+    bitangent: Vec3,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Disk {
+    /// Constructs a new `Disk` centered on `center`, whose outward normal is
+    /// `normal`.
+    pub fn new(
+        center: Point3,
+        normal: Vec3,
+        radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Disk {
+        let normal = normal.normalized();
+        let (tangent, bitangent) = normal.orthonormal_basis();
+
+        Disk {
+            center,
+            normal,
+            radius,
+            tangent,
+            bitangent,
+            material,
+        }
+    }
+}
+
+impl Hittable for Disk {
+    /// Tries to hit a disk with a ray. The intersection point must be in the
+    /// `valid_range`.
+    ///
+    /// The ray is intersected with the plane of the disk, then the distance of
+    /// the intersection point to the center tells whether it is inside.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let denominator = self.normal.dot(&ray.direction());
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = (self.center - ray.origin()).dot(&self.normal) / denominator;
+        if !valid_range.contains(&t) {
+            return None;
+        }
+
+        let offset = ray.at(t) - self.center;
+        let distance_squared = offset.length_squared();
+        if distance_squared > self.radius * self.radius {
+            return None;
+        }
+
+        let mut record = HitRecord::new(ray, t, self.normal, Arc::clone(&self.material));
+        // Polar coordinates: u goes around the disk and v from the center to
+        // the edge.
+        let phi = offset.dot(&self.bitangent).atan2(offset.dot(&self.tangent));
+        record.uv = (
+            (phi + PI) / (2.0 * PI),
+            distance_squared.sqrt() / self.radius,
+        );
+        record.set_shading_frame(self.normal, self.tangent);
+
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = disk_extent(self.normal, self.radius);
+
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
+
+/// Returns the half extent along each axis of a disk of `radius`, whose
+/// normal is the unit vector `normal`.
+///
+/// The extent along an axis is the radius scaled by the sine of the angle
+/// between the axis and the normal.
+pub(super) fn disk_extent(normal: Vec3, radius: f64) -> Vec3 {
+    radius
+        * Vec3::new(
+            (1.0 - normal.x() * normal.x()).max(0.0).sqrt(),
+            (1.0 - normal.y() * normal.y()).max(0.0).sqrt(),
+            (1.0 - normal.z() * normal.z()).max(0.0).sqrt(),
+        )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, material::Lambertian};
+
+    use super::*;
+
+    fn disk() -> Disk {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Disk::new(
+            Point3::new(0.0, 0.0, -2.0),
+            Vec3::new(0.0, 0.0, 3.0),
+            1.0,
+            material as Arc<dyn Material + Send + Sync>,
+        )
+    }
+
+    #[test]
+    fn try_hit_disk_with_not_hitting_ray() {
+        let ray = Ray::new(Point3::new(0.8, 0.8, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(disk().try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_disk_with_hitting_ray_not_in_range() {
+        let ray = Ray::new(Point3::zero(), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(disk().try_hit(&ray, 0.0..=1.0).is_none());
+    }
+
+    #[test]
+    fn try_hit_disk_with_hitting_ray_in_range() {
+        let ray = Ray::new(Point3::new(0.0, 0.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        let disk = disk();
+
+        let result = disk
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit disk");
+
+        assert_eq!(result.intersection_point, Point3::new(0.0, 0.5, -2.0));
+        assert_eq!(result.t, 2.0);
+        assert_eq!(result.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(result.front_face);
+        assert_eq!(result.uv.1, 0.5);
+    }
+
+    #[test]
+    fn bounding_box_encloses_tilted_disk() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let disk = Disk::new(Point3::zero(), Vec3::new(1.0, 1.0, 0.0), 2.0, material);
+        let bounding_box = disk.bounding_box().unwrap();
+
+        let extent = 2.0 * 0.5f64.sqrt();
+        assert!((bounding_box.maximum() - Point3::new(extent, extent, 2.0)).length() < 1e-12);
+        assert!((bounding_box.minimum() + Point3::new(extent, extent, 2.0)).length() < 1e-12);
+    }
+}
@@ -0,0 +1,439 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    mesh::HeightMap,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{triangle, Aabb, HitRecord, Hittable};
+
+/// A terrain made of a regular grid of heights.
+///
+/// Each cell of the grid is split into two triangles. Rays walk the cells they
+/// cross from front to back, so only the few cells near the ray are tested,
+/// however large the grid is.
+pub struct Heightfield {
+    /// The number of vertices along X.
+    width: usize,
+    /// The number of vertices along Z.
+    depth: usize,
+    corner: Point3,
+    cell_size: (f64, f64),
+    /// The Y coordinate of the vertices, row by row.
+    heights: Vec<f64>,
+    /// The outward normal of the vertices, row by row.
+    normals: Vec<Vec3>,
+    /// The lowest and highest Y coordinate of the cells, row by row.
+    cell_ranges: Vec<(f64, f64)>,
+    bounding_box: Aabb,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Heightfield {
+    /// Constructs a new `Heightfield` from a `height_map`, stretched to fill
+    /// `size` from `corner`.
+    ///
+    /// The first height of the map is at `corner`, the grid spans `size.x()`
+    /// along X and `size.z()` along Z, and a height of 1 rises by `size.y()`.
+    ///
+    /// # Panics
+    ///
+    /// The `new` function will panic if `size` isn't positive along X and Z.
+    pub fn new(
+        height_map: &HeightMap,
+        corner: Point3,
+        size: Vec3,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Heightfield {
+        assert!(
+            size.x() > 0.0 && size.z() > 0.0,
+            "A heightfield must have a positive size along X and Z"
+        );
+
+        let (width, depth) = (height_map.width(), height_map.depth());
+        let cell_size = (size.x() / (width - 1) as f64, size.z() / (depth - 1) as f64);
+        let heights: Vec<f64> = (0..depth)
+            .flat_map(|z| (0..width).map(move |x| (x, z)))
+            .map(|(x, z)| corner.y() + size.y() * height_map.height(x, z))
+            .collect();
+        let height = |x: usize, z: usize| heights[z * width + x];
+
+        // The slopes come from central differences, or one-sided differences on
+        // the border.
+        let normals = (0..depth)
+            .flat_map(|z| (0..width).map(move |x| (x, z)))
+            .map(|(x, z)| {
+                let (x0, x1) = (x.saturating_sub(1), (x + 1).min(width - 1));
+                let (z0, z1) = (z.saturating_sub(1), (z + 1).min(depth - 1));
+                let slope_x = (height(x1, z) - height(x0, z)) / ((x1 - x0) as f64 * cell_size.0);
+                let slope_z = (height(x, z1) - height(x, z0)) / ((z1 - z0) as f64 * cell_size.1);
+
+                Vec3::new(-slope_x, 1.0, -slope_z).normalized()
+            })
+            .collect();
+
+        let cell_ranges = (0..depth - 1)
+            .flat_map(|z| (0..width - 1).map(move |x| (x, z)))
+            .map(|(x, z)| {
+                let corners = [
+                    height(x, z),
+                    height(x + 1, z),
+                    height(x, z + 1),
+                    height(x + 1, z + 1),
+                ];
+                corners
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &y| {
+                        (min.min(y), max.max(y))
+                    })
+            })
+            .collect();
+
+        let (min, max) = height_map.range();
+        let bounding_box = Aabb::new(
+            Point3::new(corner.x(), corner.y() + size.y() * min, corner.z()),
+            Point3::new(
+                corner.x() + size.x(),
+                corner.y() + size.y() * max,
+                corner.z() + size.z(),
+            ),
+        );
+
+        Heightfield {
+            width,
+            depth,
+            corner,
+            cell_size,
+            heights,
+            normals,
+            cell_ranges,
+            bounding_box,
+            material,
+        }
+    }
+
+    /// Returns the position of the vertex at the `(x, z)` grid coordinates.
+    fn vertex(&self, x: usize, z: usize) -> Point3 {
+        Point3::new(
+            self.corner.x() + x as f64 * self.cell_size.0,
+            self.heights[z * self.width + x],
+            self.corner.z() + z as f64 * self.cell_size.1,
+        )
+    }
+
+    /// Tries to hit the two triangles of the cell at the `(x, z)` grid
+    /// coordinates. Returns the closest hit.
+    fn try_hit_cell(
+        &self,
+        ray: &Ray,
+        x: usize,
+        z: usize,
+        valid_range: RangeInclusive<f64>,
+    ) -> Option<HitRecord<'_>> {
+        let (v00, v10) = (self.vertex(x, z), self.vertex(x + 1, z));
+        let (v01, v11) = (self.vertex(x, z + 1), self.vertex(x + 1, z + 1));
+        let (n00, n10) = (self.normal(x, z), self.normal(x + 1, z));
+        let (n01, n11) = (self.normal(x, z + 1), self.normal(x + 1, z + 1));
+
+        // Both triangles are counter-clockwise seen from above.
+        let triangles = [
+            ([v00, v01, v11], [n00, n01, n11]),
+            ([v00, v11, v10], [n00, n11, n10]),
+        ];
+        let (vertices, normals, t, barycentric) = triangles
+            .iter()
+            .filter_map(|(vertices, normals)| {
+                let (t, barycentric) = triangle::intersect(vertices, ray, valid_range.clone())?;
+                Some((vertices, normals, t, barycentric))
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2))?;
+
+        let mut record =
+            triangle::hit_record(vertices, ray, t, barycentric, Arc::clone(&self.material));
+        let point = record.intersection_point;
+        record.uv = (
+            ((point.x() - self.corner.x()) / (self.cell_size.0 * (self.width - 1) as f64))
+                .clamp(0.0, 1.0),
+            ((point.z() - self.corner.z()) / (self.cell_size.1 * (self.depth - 1) as f64))
+                .clamp(0.0, 1.0),
+        );
+        let shading_normal =
+            barycentric[0] * normals[0] + barycentric[1] * normals[1] + barycentric[2] * normals[2];
+        record.set_shading_frame(shading_normal, Vec3::new(1.0, 0.0, 0.0));
+
+        Some(record)
+    }
+
+    fn normal(&self, x: usize, z: usize) -> Vec3 {
+        self.normals[z * self.width + x]
+    }
+}
+
+/// The state of a walk along one axis of the grid.
+struct Walk {
+    /// The current cell.
+    cell: usize,
+    /// `1` or `-1`, the direction in which the cells are crossed, or `0` if
+    /// the ray is parallel to the axis.
+    step: isize,
+    /// The distance along the ray where the next cell begins.
+    next_t: f64,
+    /// The distance along the ray needed to cross a whole cell.
+    delta_t: f64,
+}
+
+impl Walk {
+    /// Starts a walk over `cells` cells of `cell_size`, from `origin` on the
+    /// grid at `t` along a ray of `direction`. `start` is the grid coordinate
+    /// of the ray at `t`, and `origin` the one at the ray origin.
+    fn new(start: f64, origin: f64, direction: f64, cells: usize) -> Walk {
+        let cell = (start.floor().max(0.0) as usize).min(cells - 1);
+        if direction > 0.0 {
+            Walk {
+                cell,
+                step: 1,
+                next_t: ((cell + 1) as f64 - origin) / direction,
+                delta_t: 1.0 / direction,
+            }
+        } else if direction < 0.0 {
+            Walk {
+                cell,
+                step: -1,
+                next_t: (cell as f64 - origin) / direction,
+                delta_t: -1.0 / direction,
+            }
+        } else {
+            Walk {
+                cell,
+                step: 0,
+                next_t: f64::INFINITY,
+                delta_t: f64::INFINITY,
+            }
+        }
+    }
+
+    /// Moves to the next cell. Returns `false` when leaving the `cells`.
+    fn advance(&mut self, cells: usize) -> bool {
+        self.next_t += self.delta_t;
+        match self.cell.checked_add_signed(self.step) {
+            Some(cell) if cell < cells => {
+                self.cell = cell;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Hittable for Heightfield {
+    /// Tries to hit the heightfield with a ray. The intersection point must be
+    /// in the `valid_range`.
+    ///
+    /// The cells under the ray are walked from the closest to the furthest,
+    /// skipping those the ray passes above or below.
+    /// See [A Fast Voxel Traversal Algorithm for Ray Tracing](http://www.cse.yorku.ca/~amana/research/grid.pdf),
+    /// Amanatides and Woo, 1987.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let (t_min, t_max) = self.bounding_box.clip(ray, valid_range.clone())?;
+
+        // Walk in grid coordinates, where cells are 1 by 1.
+        let origin = ray.origin() - self.corner;
+        let direction = ray.direction();
+        let (origin_x, origin_z) = (origin.x() / self.cell_size.0, origin.z() / self.cell_size.1);
+        let (direction_x, direction_z) = (
+            direction.x() / self.cell_size.0,
+            direction.z() / self.cell_size.1,
+        );
+        let (cells_x, cells_z) = (self.width - 1, self.depth - 1);
+
+        let mut walk_x = Walk::new(
+            origin_x + t_min * direction_x,
+            origin_x,
+            direction_x,
+            cells_x,
+        );
+        let mut walk_z = Walk::new(
+            origin_z + t_min * direction_z,
+            origin_z,
+            direction_z,
+            cells_z,
+        );
+
+        let mut cell_entry = t_min;
+        while cell_entry <= t_max {
+            let cell_exit = walk_x.next_t.min(walk_z.next_t).min(t_max);
+
+            let (low, high) = self.cell_ranges[walk_z.cell * cells_x + walk_x.cell];
+            let (entry_y, exit_y) = (ray.at(cell_entry).y(), ray.at(cell_exit).y());
+            if entry_y.min(exit_y) <= high && entry_y.max(exit_y) >= low {
+                // The triangles lie within the cell, so the first hit is the
+                // closest one.
+                if let Some(record) =
+                    self.try_hit_cell(ray, walk_x.cell, walk_z.cell, valid_range.clone())
+                {
+                    return Some(record);
+                }
+            }
+
+            let moved = if walk_x.next_t < walk_z.next_t {
+                walk_x.advance(cells_x)
+            } else {
+                walk_z.advance(cells_z)
+            };
+            if !moved {
+                break;
+            }
+            cell_entry = cell_exit;
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounding_box)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, material::Lambertian};
+
+    use super::*;
+
+    fn heightfield(height_map: &HeightMap) -> Heightfield {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Heightfield::new(
+            height_map,
+            Point3::new(-2.0, 0.0, -2.0),
+            Vec3::new(4.0, 1.0, 4.0),
+            material,
+        )
+    }
+
+    /// A pyramid whose top is at `(0, 1, 0)`.
+    fn pyramid() -> HeightMap {
+        HeightMap::from_fn(5, 5, |x, z| {
+            1.0 - 0.5 * (x as f64 - 2.0).abs().max((z as f64 - 2.0).abs())
+        })
+    }
+
+    #[test]
+    fn try_hit_heightfield_from_above() {
+        let heightfield = heightfield(&pyramid());
+        let ray = Ray::new(Point3::new(0.5, 5.0, 0.25), Vec3::new(0.0, -1.0, 0.0));
+
+        let result = heightfield
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit heightfield");
+
+        assert!((result.t - 4.25).abs() < 1e-12);
+        assert!((result.intersection_point - Point3::new(0.5, 0.75, 0.25)).length() < 1e-12);
+        assert!(result.front_face);
+        assert!(result.normal.y() > 0.0 && result.normal.x() > 0.0);
+        assert!((result.uv.0 - 0.625).abs() < 1e-12);
+        assert!((result.uv.1 - 0.5625).abs() < 1e-12);
+    }
+
+    #[test]
+    fn try_hit_heightfield_with_grazing_ray_hits_the_first_slope() {
+        let heightfield = heightfield(&pyramid());
+        let ray = Ray::new(Point3::new(-5.0, 0.75, 0.1), Vec3::new(1.0, 0.0, 0.0));
+
+        let result = heightfield
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit heightfield");
+
+        assert!((result.intersection_point.x() + 0.4).abs() < 1e-12);
+        assert!(result.normal.x() < 0.0);
+    }
+
+    #[test]
+    fn try_hit_heightfield_with_ray_passing_above() {
+        let heightfield = heightfield(&pyramid());
+        let ray = Ray::new(Point3::new(-5.0, 1.5, 0.0), Vec3::new(1.0, -0.01, 0.0));
+
+        assert!(heightfield.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_heightfield_with_hitting_ray_not_in_range() {
+        let heightfield = heightfield(&pyramid());
+        let ray = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        assert!(heightfield.try_hit(&ray, 0.0..=3.0).is_none());
+    }
+
+    #[test]
+    fn try_hit_heightfield_from_below() {
+        let heightfield = heightfield(&pyramid());
+        let ray = Ray::new(Point3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+        let result = heightfield
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit heightfield");
+
+        assert!((result.t - 2.0).abs() < 1e-12);
+        assert!(!result.front_face);
+        assert!(result.shading_normal.y() < 0.0);
+    }
+
+    #[test]
+    fn try_hit_heightfield_interpolates_normals() {
+        let height_map = HeightMap::from_fn(3, 3, |x, _| (x as f64 * 0.5).powi(2));
+        let heightfield = heightfield(&height_map);
+        let ray = Ray::new(Point3::new(-0.5, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        let result = heightfield
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit heightfield");
+
+        // Near the middle vertices, the interpolated slope is steeper than the
+        // one of the triangle.
+        assert!(result.shading_normal.x() < result.normal.x());
+        assert!(result.normal.x() < 0.0);
+        assert!((result.shading_normal.length() - 1.0).abs() < 1e-12);
+        assert!(result.tangent.dot(&result.shading_normal).abs() < 1e-12);
+    }
+
+    #[test]
+    fn try_hit_heightfield_finds_the_closest_cell() {
+        let height_map = HeightMap::from_fn(17, 13, |x, z| {
+            0.5 + 0.5 * (x as f64 * 0.9).sin() * (z as f64 * 1.3).cos()
+        });
+        let heightfield = heightfield(&height_map);
+
+        for i in 0..200 {
+            let angle = i as f64 * 0.7;
+            let origin = Point3::new(3.0 * angle.cos(), 1.5, 3.0 * angle.sin());
+            let target = Point3::new((i as f64 * 0.37).sin(), 0.2, (i as f64 * 0.53).cos());
+            let ray = Ray::new(origin, target - origin);
+
+            // Testing every cell gives the expected closest hit.
+            let expected = (0..height_map.depth() - 1)
+                .flat_map(|z| (0..height_map.width() - 1).map(move |x| (x, z)))
+                .filter_map(|(x, z)| heightfield.try_hit_cell(&ray, x, z, 0.0..=f64::INFINITY))
+                .map(|record| record.t)
+                .min_by(f64::total_cmp);
+            let result = heightfield
+                .try_hit(&ray, 0.0..=f64::INFINITY)
+                .map(|record| record.t);
+
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn bounding_box_encloses_heights() {
+        let heightfield = heightfield(&pyramid());
+
+        assert_eq!(
+            heightfield.bounding_box(),
+            Some(Aabb::new(
+                Point3::new(-2.0, 0.0, -2.0),
+                Point3::new(2.0, 1.0, 2.0)
+            ))
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    perlin::Perlin,
+    random::random,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    constant_medium::{inside_intervals, scattering_record},
+    Aabb, HitRecord, Hittable,
+};
+
+/// A density field, giving for each point the number of scattering events per
+/// unit of length of a participating medium.
+///
+/// Any `Fn(Point3) -> f64` closure is a density field.
+pub trait DensityField {
+    /// Returns the density at `point`, which must not be negative.
+    fn density(&self, point: Point3) -> f64;
+
+    /// Returns an upper bound of the density, or [`None`] if it is unknown.
+    fn max_density(&self) -> Option<f64> {
+        None
+    }
+}
+
+impl<F> DensityField for F
+where
+    F: Fn(Point3) -> f64,
+{
+    fn density(&self, point: Point3) -> f64 {
+        self(point)
+    }
+}
+
+/// A density field sampled on a regular 3D grid, and interpolated between the
+/// samples.
+pub struct DensityGrid {
+    bounds: Aabb,
+    resolution: [usize; 3],
+    densities: Vec<f64>,
+    max_density: f64,
+}
+
+impl DensityGrid {
+    /// Constructs a new `DensityGrid` filling the `bounds`, made of
+    /// `resolution` samples along X, Y and Z, stored X first, then Y, then Z.
+    /// The density is 0 outside the bounds.
+    ///
+    /// # Panics
+    ///
+    /// The `new` function will panic if there are less than 2 samples along an
+    /// axis, if there isn't a density for each sample or if a density is
+    /// negative.
+    pub fn new(bounds: Aabb, resolution: [usize; 3], densities: Vec<f64>) -> DensityGrid {
+        assert!(
+            resolution.iter().all(|&samples| samples >= 2),
+            "A density grid needs at least 2 samples along each axis"
+        );
+        assert_eq!(
+            densities.len(),
+            resolution.iter().product::<usize>(),
+            "A density grid needs a density for each sample"
+        );
+        assert!(
+            densities.iter().all(|&density| density >= 0.0),
+            "A density can't be negative"
+        );
+
+        let max_density = densities.iter().copied().fold(0.0, f64::max);
+        DensityGrid {
+            bounds,
+            resolution,
+            densities,
+            max_density,
+        }
+    }
+
+    /// Constructs a new `DensityGrid` filling the `bounds`, made of
+    /// `resolution` samples along X, Y and Z, whose density at each sample
+    /// position is `density(position)`.
+    ///
+    /// See [`DensityGrid::new`].
+    pub fn from_fn<F>(bounds: Aabb, resolution: [usize; 3], density: F) -> DensityGrid
+    where
+        F: Fn(Point3) -> f64,
+    {
+        let (minimum, extent) = (bounds.minimum(), bounds.maximum() - bounds.minimum());
+        let step = |axis: usize| extent[axis] / (resolution[axis] - 1) as f64;
+        let (step_x, step_y, step_z) = (step(0), step(1), step(2));
+
+        let densities = (0..resolution[2])
+            .flat_map(|z| (0..resolution[1]).map(move |y| (y, z)))
+            .flat_map(|(y, z)| (0..resolution[0]).map(move |x| (x, y, z)))
+            .map(|(x, y, z)| {
+                density(
+                    minimum + Vec3::new(x as f64 * step_x, y as f64 * step_y, z as f64 * step_z),
+                )
+            })
+            .collect();
+
+        DensityGrid::new(bounds, resolution, densities)
+    }
+
+    fn sample(&self, x: usize, y: usize, z: usize) -> f64 {
+        let [width, height, _] = self.resolution;
+        self.densities[(z * height + y) * width + x]
+    }
+}
+
+impl DensityField for DensityGrid {
+    /// Returns the density at `point`, trilinearly interpolated between the 8
+    /// closest samples.
+    fn density(&self, point: Point3) -> f64 {
+        let (minimum, maximum) = (self.bounds.minimum(), self.bounds.maximum());
+        let mut cell = [0; 3];
+        let mut fraction = [0.0; 3];
+        for axis in 0..3 {
+            if point[axis] < minimum[axis] || point[axis] > maximum[axis] {
+                return 0.0;
+            }
+
+            let cells = self.resolution[axis] - 1;
+            let position =
+                (point[axis] - minimum[axis]) / (maximum[axis] - minimum[axis]) * cells as f64;
+            cell[axis] = (position.floor() as usize).min(cells - 1);
+            fraction[axis] = position - cell[axis] as f64;
+        }
+
+        let mut density = 0.0;
+        for corner in 0..8 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let weight: f64 = (0..3)
+                .map(|axis| {
+                    if offset[axis] == 1 {
+                        fraction[axis]
+                    } else {
+                        1.0 - fraction[axis]
+                    }
+                })
+                .product();
+            density += weight
+                * self.sample(
+                    cell[0] + offset[0],
+                    cell[1] + offset[1],
+                    cell[2] + offset[2],
+                );
+        }
+
+        density
+    }
+
+    fn max_density(&self) -> Option<f64> {
+        Some(self.max_density)
+    }
+}
+
+/// A density field made of Perlin turbulence, for smoke and clouds.
+pub struct NoiseDensity {
+    perlin: Perlin,
+    density: f64,
+    scale: f64,
+    octaves: usize,
+}
+
+impl NoiseDensity {
+    /// Constructs a new `NoiseDensity` whose density varies between 0 and
+    /// about `density`, with features about `1 / scale` wide and `octaves`
+    /// levels of details.
+    pub fn new(density: f64, scale: f64, octaves: usize) -> NoiseDensity {
+        NoiseDensity {
+            perlin: Perlin::new(),
+            density,
+            scale,
+            octaves,
+        }
+    }
+}
+
+impl DensityField for NoiseDensity {
+    fn density(&self, point: Point3) -> f64 {
+        self.density * self.perlin.turbulence(self.scale * point, self.octaves)
+    }
+
+    fn max_density(&self) -> Option<f64> {
+        // The turbulence stays under 2.
+        Some(2.0 * self.density)
+    }
+}
+
+/// A participating medium whose density varies in space, filling a closed
+/// boundary.
+///
+/// See [`ConstantMedium`](super::ConstantMedium) for a medium of constant
+/// density, which is faster to render.
+pub struct HeterogeneousMedium {
+    boundary: Arc<dyn Hittable + Sync + Send>,
+    density: Arc<dyn DensityField + Sync + Send>,
+    max_density: f64,
+    phase_function: Arc<dyn Material + Sync + Send>,
+}
+
+impl HeterogeneousMedium {
+    /// Constructs a new `HeterogeneousMedium` filling the `boundary`, whose
+    /// density is given by a density field.
+    ///
+    /// # Panics
+    ///
+    /// The `new` function will panic if the maximum of the density field is
+    /// unknown. Use [`HeterogeneousMedium::with_max_density`] instead.
+    pub fn new(
+        boundary: Arc<dyn Hittable + Sync + Send>,
+        density: Arc<dyn DensityField + Sync + Send>,
+        phase_function: Arc<dyn Material + Sync + Send>,
+    ) -> HeterogeneousMedium {
+        let max_density = density
+            .max_density()
+            .expect("The maximum density of the field must be known");
+
+        HeterogeneousMedium::with_max_density(boundary, density, max_density, phase_function)
+    }
+
+    /// Constructs a new `HeterogeneousMedium` filling the `boundary`, whose
+    /// density is given by a density field never above `max_density`.
+    pub fn with_max_density(
+        boundary: Arc<dyn Hittable + Sync + Send>,
+        density: Arc<dyn DensityField + Sync + Send>,
+        max_density: f64,
+        phase_function: Arc<dyn Material + Sync + Send>,
+    ) -> HeterogeneousMedium {
+        HeterogeneousMedium {
+            boundary,
+            density,
+            max_density,
+            phase_function,
+        }
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    /// Tries to scatter a ray inside the medium. The scattering point must be
+    /// in the `valid_range`.
+    ///
+    /// Tentative scattering points are sampled as in a constant medium of the
+    /// maximum density, and kept with the probability of the actual density
+    /// relative to the maximum one, which is unbiased.
+    /// See [delta tracking](https://www.pbr-book.org/3ed-2018/Light_Transport_II_Volume_Rendering/Sampling_Volume_Scattering#HeterogeneousMedium)
+    /// in Physically Based Rendering.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        if self.max_density <= 0.0 {
+            return None;
+        }
+
+        let ray_length = ray.direction().length();
+        for (entry, exit) in inside_intervals(self.boundary.as_ref(), ray, valid_range) {
+            let mut t = entry;
+            loop {
+                t -= random().ln() / (self.max_density * ray_length);
+                if t >= exit {
+                    break;
+                }
+
+                if random() * self.max_density < self.density.density(ray.at(t)) {
+                    return Some(scattering_record(ray, t, Arc::clone(&self.phase_function)));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, hittable::Cuboid, material::Isotropic};
+
+    use super::*;
+
+    fn unit_cube() -> Arc<dyn Hittable + Sync + Send> {
+        Arc::new(Cuboid::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Arc::new(Isotropic::new(Color::zero())),
+        ))
+    }
+
+    fn transmittance(medium: &HeterogeneousMedium, ray: &Ray) -> f64 {
+        let samples = 100_000;
+        let transmitted = (0..samples)
+            .filter(|_| medium.try_hit(ray, 0.0..=f64::INFINITY).is_none())
+            .count();
+
+        transmitted as f64 / samples as f64
+    }
+
+    #[test]
+    fn density_grid_interpolates_samples() {
+        let grid = DensityGrid::from_fn(
+            Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0)),
+            [3, 2, 2],
+            |point: Point3| point.x() + 10.0 * point.z(),
+        );
+
+        assert_eq!(grid.density(Point3::new(1.5, 0.3, 0.5)), 6.5);
+        assert_eq!(grid.density(Point3::new(2.0, 1.0, 1.0)), 12.0);
+        assert_eq!(grid.density(Point3::new(2.5, 0.5, 0.5)), 0.0);
+        assert_eq!(grid.max_density(), Some(12.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn density_grid_with_negative_density_panics() {
+        DensityGrid::new(
+            Aabb::new(Point3::zero(), Point3::new(1.0, 1.0, 1.0)),
+            [2, 2, 2],
+            vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, -1.0],
+        );
+    }
+
+    #[test]
+    fn noise_density_stays_under_its_maximum() {
+        let noise = NoiseDensity::new(3.0, 4.0, 5);
+        let max_density = noise.max_density().unwrap();
+
+        for i in 0..1000 {
+            let point = Point3::new(i as f64 * 0.13, i as f64 * 0.07, i as f64 * -0.05);
+            let density = noise.density(point);
+
+            assert!((0.0..=max_density).contains(&density));
+        }
+    }
+
+    #[test]
+    fn try_hit_medium_transmits_following_the_optical_depth() {
+        // The density grows linearly from 0 to 4 along X, so the optical depth
+        // through the cube is 2.
+        let density = Arc::new(|point: Point3| 4.0 * point.x());
+        let medium = HeterogeneousMedium::with_max_density(
+            unit_cube(),
+            density,
+            4.0,
+            Arc::new(Isotropic::new(Color::zero())),
+        );
+        let ray = Ray::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!((transmittance(&medium, &ray) - (-2.0_f64).exp()).abs() < 0.01);
+    }
+
+    #[test]
+    fn try_hit_medium_scatters_where_there_is_density() {
+        let density = Arc::new(|point: Point3| if point.x() > 0.5 { 5.0 } else { 0.0 });
+        let medium = HeterogeneousMedium::with_max_density(
+            unit_cube(),
+            density,
+            5.0,
+            Arc::new(Isotropic::new(Color::zero())),
+        );
+        let ray = Ray::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+
+        for _ in 0..1000 {
+            if let Some(record) = medium.try_hit(&ray, 0.0..=f64::INFINITY) {
+                assert!(record.intersection_point.x() > 0.5);
+            }
+        }
+    }
+
+    #[test]
+    fn try_hit_empty_medium_never_scatters() {
+        let medium = HeterogeneousMedium::new(
+            unit_cube(),
+            Arc::new(DensityGrid::new(
+                Aabb::new(Point3::zero(), Point3::new(1.0, 1.0, 1.0)),
+                [2, 2, 2],
+                vec![0.0; 8],
+            )),
+            Arc::new(Isotropic::new(Color::zero())),
+        );
+        let ray = Ray::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+
+        assert_eq!(transmittance(&medium, &ray), 1.0);
+    }
+}
@@ -2,7 +2,7 @@ use std::{ops::RangeInclusive, sync::Arc};
 
 use crate::ray::Ray;
 
-use super::{HitRecord, Hittable};
+use super::{Aabb, HitRecord, Hittable};
 
 /// Stores a list of `hittable::Hittable`.
 pub struct HittableCollection {
@@ -22,6 +22,11 @@ impl HittableCollection {
         self.objects.push(elem);
     }
 
+    /// Consumes the collection, returning its hittables.
+    pub fn into_objects(self) -> Vec<Arc<dyn Hittable + Sync + Send>> {
+        self.objects
+    }
+
     /// Clears the collection, removing all hittables.
     #[allow(dead_code)]
     pub fn clear(&mut self) {
@@ -34,7 +39,7 @@ impl Hittable for HittableCollection {
     /// intersection must be in the `valid_range`.
     /// Returns a record of the closest hit (the distance from the ray origin's
     /// to the point of intersection), or [`None`] if no hittable can be hit.
-    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord> {
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
         let mut record: Option<HitRecord> = None;
         let mut closest = *valid_range.end();
 
@@ -47,4 +52,21 @@ impl Hittable for HittableCollection {
 
         record
     }
+
+    fn occluded(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> bool {
+        self.objects
+            .iter()
+            .any(|object| object.occluded(ray, valid_range.clone()))
+    }
+
+    /// Returns the box surrounding every hittable of the collection, or
+    /// [`None`] if the collection is empty or contains an unbounded hittable.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut objects = self.objects.iter();
+        let first = objects.next()?.bounding_box()?;
+
+        objects.try_fold(first, |aabb, object| {
+            Some(aabb.surrounding(&object.bounding_box()?))
+        })
+    }
 }
@@ -0,0 +1,233 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{mat4::Mat4, ray::Ray, vec3::Point3};
+
+use super::{Aabb, HitRecord, Hittable, Span};
+
+/// An hittable placed in the world by an affine transform.
+///
+/// The wrapped hittable is shared, so the same geometry (e.g. a large mesh)
+/// can be placed many times while being stored once.
+pub struct Instance {
+    object: Arc<dyn Hittable + Sync + Send>,
+    /// The transform from object space to world space.
+    transform: Mat4,
+    /// The transform from world space to object space.
+    inverse: Mat4,
+    /// The transform applied to normals, i.e. the inverse transpose of
+    /// `transform`.
+    normal_transform: Mat4,
+    bounds: Option<Aabb>,
+}
+
+impl Instance {
+    /// Constructs a new `Instance` of `object`, moved from object space to
+    /// world space by `transform`.
+    ///
+    /// # Panics
+    ///
+    /// The `new` function will panic if `transform` is not invertible.
+    pub fn new(object: Arc<dyn Hittable + Sync + Send>, transform: Mat4) -> Instance {
+        let inverse = transform
+            .inverse()
+            .expect("The transform of an instance must be invertible");
+
+        // The transformed box is the one enclosing the transformed corners.
+        let bounds = object.bounding_box().map(|bounds| {
+            let (min, max) = (bounds.minimum(), bounds.maximum());
+            let corner = |i: usize| {
+                Point3::new(
+                    if i & 1 == 0 { min.x() } else { max.x() },
+                    if i & 2 == 0 { min.y() } else { max.y() },
+                    if i & 4 == 0 { min.z() } else { max.z() },
+                )
+            };
+
+            let first = transform.transform_point(&corner(0));
+            (1..8).fold(Aabb::new(first, first), |bounds, i| {
+                bounds.including(transform.transform_point(&corner(i)))
+            })
+        });
+
+        Instance {
+            object,
+            transform,
+            inverse,
+            normal_transform: inverse.transposed(),
+            bounds,
+        }
+    }
+
+    /// Returns the `ray` in object space. Its direction isn't normalized so
+    /// distances along the ray are the same in both spaces.
+    fn to_object(&self, ray: &Ray) -> Ray {
+        Ray::with_time(
+            self.inverse.transform_point(&ray.origin()),
+            self.inverse.transform_vector(&ray.direction()),
+            ray.time(),
+        )
+    }
+
+    /// Returns the object space `record` in world space.
+    fn to_world<'a>(&self, mut record: HitRecord<'a>) -> HitRecord<'a> {
+        record.intersection_point = self.transform.transform_point(&record.intersection_point);
+        // Normals are transformed by the inverse transpose to stay
+        // perpendicular to the surface under non-uniform scaling. They still
+        // face against the ray, so `front_face` doesn't change.
+        record.normal = self
+            .normal_transform
+            .transform_vector(&record.normal)
+            .normalized();
+        let shading_normal = self
+            .normal_transform
+            .transform_vector(&record.shading_normal);
+        let tangent = self.transform.transform_vector(&record.tangent);
+        // `set_shading_frame` expects an outward normal.
+        let outward_shading_normal = if record.front_face {
+            shading_normal
+        } else {
+            -shading_normal
+        };
+        record.set_shading_frame(outward_shading_normal, tangent);
+
+        record
+    }
+}
+
+impl Hittable for Instance {
+    /// Tries to hit the instance with a ray. The intersection point must be in
+    /// the `valid_range`.
+    ///
+    /// The ray is moved to object space to hit the wrapped hittable, and the
+    /// record is moved back to world space.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let record = self.object.try_hit(&self.to_object(ray), valid_range)?;
+
+        Some(self.to_world(record))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bounds
+    }
+
+    fn occluded(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> bool {
+        self.object.occluded(&self.to_object(ray), valid_range)
+    }
+
+    fn spans(&self, ray: &Ray) -> Option<Vec<Span<'_>>> {
+        let spans = self.object.spans(&self.to_object(ray))?;
+
+        Some(
+            spans
+                .into_iter()
+                .map(|span| Span {
+                    entry: self.to_world(span.entry),
+                    exit: self.to_world(span.exit),
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        color::Color,
+        material::{Lambertian, Material},
+        vec3::Vec3,
+    };
+
+    use super::super::Sphere;
+    use super::*;
+
+    fn unit_sphere() -> Arc<dyn Hittable + Sync + Send> {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Arc::new(Sphere::new(
+            Point3::zero(),
+            1.0,
+            material as Arc<dyn Material + Send + Sync>,
+        ))
+    }
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-12, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn try_hit_translated_instance() {
+        let instance = Instance::new(unit_sphere(), Mat4::translation(Vec3::new(3.0, 0.0, 0.0)));
+        let ray = Ray::new(Point3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let result = instance
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit instance");
+
+        assert_eq!(result.intersection_point, Point3::new(2.0, 0.0, 0.0));
+        assert_eq!(result.t, 1.0);
+        assert_eq!(result.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(result.front_face);
+    }
+
+    #[test]
+    fn try_hit_instance_with_hitting_ray_not_in_range() {
+        let instance = Instance::new(unit_sphere(), Mat4::translation(Vec3::new(3.0, 0.0, 0.0)));
+        let ray = Ray::new(Point3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(instance.try_hit(&ray, 0.0..=0.5).is_none());
+    }
+
+    #[test]
+    fn try_hit_non_uniformly_scaled_instance_has_perpendicular_normal() {
+        // An ellipsoid twice as wide along X.
+        let instance = Instance::new(unit_sphere(), Mat4::scaling(Vec3::new(2.0, 1.0, 1.0)));
+        let direction = Vec3::new(-1.0, -1.0, 0.0);
+        let ray = Ray::new(Point3::new(5.0, 5.0, 0.0), direction);
+
+        let result = instance
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit instance");
+
+        // The gradient of x²/4 + y² is (x/2, 2y).
+        let p = result.intersection_point;
+        assert!((p.x() * p.x() / 4.0 + p.y() * p.y() - 1.0).abs() < 1e-12);
+        assert_near(
+            result.normal,
+            Vec3::new(p.x() / 2.0, 2.0 * p.y(), 0.0).normalized(),
+        );
+        assert!(result.front_face);
+        assert!(result.tangent.dot(&result.shading_normal).abs() < 1e-12);
+    }
+
+    #[test]
+    fn try_hit_instance_from_inside_keeps_front_face() {
+        let instance = Instance::new(
+            unit_sphere(),
+            Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), 45.0)
+                * Mat4::scaling(Vec3::new(2.0, 2.0, 2.0)),
+        );
+        let ray = Ray::new(Point3::zero(), Vec3::new(0.0, 1.0, 0.0));
+
+        let result = instance
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit instance");
+
+        assert_near(result.intersection_point, Point3::new(0.0, 2.0, 0.0));
+        assert_eq!(result.t, 2.0);
+        assert_near(result.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert_near(result.shading_normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(!result.front_face);
+    }
+
+    #[test]
+    fn bounding_box_encloses_rotated_instance() {
+        let instance = Instance::new(
+            unit_sphere(),
+            Mat4::rotation(Vec3::new(0.0, 0.0, 1.0), 45.0),
+        );
+        let bounds = instance.bounding_box().unwrap();
+
+        let extent = 2.0f64.sqrt();
+        assert_near(bounds.minimum(), Point3::new(-extent, -extent, -1.0));
+        assert_near(bounds.maximum(), Point3::new(extent, extent, 1.0));
+    }
+}
@@ -1,16 +1,52 @@
 use std::{ops::RangeInclusive, sync::Arc};
 
+pub use aabb::Aabb;
+pub use bvh::{Bvh, SplitMethod};
+pub use cone::Cone;
+pub use constant_medium::ConstantMedium;
+pub use csg::{Csg, CsgOperation};
+pub use cuboid::Cuboid;
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use heightfield::Heightfield;
+pub use heterogeneous_medium::{DensityField, DensityGrid, HeterogeneousMedium, NoiseDensity};
 pub use hittable_collection::HittableCollection;
+pub use instance::Instance;
+pub use moving_sphere::MovingSphere;
+pub use plane::Plane;
+pub use quad::Quad;
+pub use sdf::{Capsule, DistanceFunction, Mandelbulb, RoundedBox, Sdf, SmoothUnion};
 pub use sphere::Sphere;
+pub use torus::Torus;
+pub use triangle_mesh::TriangleMesh;
 
 use crate::{
+    color::Color,
     material::Material,
     ray::Ray,
     vec3::{Point3, Vec3},
 };
 
+mod aabb;
+mod bvh;
+mod cone;
+mod constant_medium;
+mod csg;
+mod cuboid;
+mod cylinder;
+mod disk;
+mod heightfield;
+mod heterogeneous_medium;
 mod hittable_collection;
+mod instance;
+mod moving_sphere;
+mod plane;
+mod quad;
+mod sdf;
 mod sphere;
+mod torus;
+mod triangle;
+mod triangle_mesh;
 
 /// An `HitRecord` is the result of a [`Ray`] hitting an [`Hittable`].
 pub struct HitRecord<'a> {
@@ -20,11 +56,34 @@ pub struct HitRecord<'a> {
     /// origin.
     pub t: f64,
     /// A vector that is perpendicular to the surface at the point of
-    /// intersection.
+    /// intersection. It always points against the intersecting ray.
     pub normal: Vec3,
+    /// The normal used to shade the surface, which may differ from the
+    /// geometric [`HitRecord::normal`] (e.g. when interpolated from the vertex
+    /// normals of a mesh). It lies on the same side of the surface as
+    /// [`HitRecord::normal`].
+    pub shading_normal: Vec3,
+    /// A unit vector tangent to the surface, along the direction where the
+    /// first texture coordinate increases when possible.
+    pub tangent: Vec3,
+    /// A unit vector tangent to the surface, such that `tangent`, `bitangent`
+    /// and `shading_normal` make a right-handed orthonormal basis.
+    pub bitangent: Vec3,
+    /// The texture coordinates of the point of intersection on the surface.
+    pub uv: (f64, f64),
     /// Whether the intersecting ray met the hittable from the outside (i.e.
     /// [`HitRecord::front_face`] is `true`) or the inside.
     pub front_face: bool,
+    /// Whether the ray scattered inside a participating medium rather than
+    /// hitting a surface, in which case the normals are arbitrary.
+    pub in_medium: bool,
+    /// The barycentric coordinates of the point of intersection, i.e. the
+    /// weight of each vertex of the hit triangle, or [`None`] if the hittable
+    /// is not a triangle.
+    pub barycentric: Option<[f64; 3]>,
+    /// The color of the surface interpolated from the vertex colors of the
+    /// hit mesh, or [`None`] if the hittable has no vertex colors.
+    pub vertex_color: Option<Color>,
     /// The material of the hit face.
     pub material: Arc<dyn Material + Sync + Send + 'a>,
 }
@@ -50,20 +109,116 @@ impl<'a> HitRecord<'a> {
         } else {
             -outward_normal
         };
+        let (tangent, bitangent) = normal.orthonormal_basis();
 
         HitRecord {
             intersection_point: hitting_ray.at(t),
             normal,
+            shading_normal: normal,
+            tangent,
+            bitangent,
+            uv: (0.0, 0.0),
             t,
             front_face,
+            in_medium: false,
+            barycentric: None,
+            vertex_color: None,
             material,
         }
     }
+
+    /// Sets the shading normal and tangents of the record.
+    ///
+    /// `outward_shading_normal` points “outward” from the surface of the
+    /// `Hittable`, like the `outward_normal` given to [`HitRecord::new`]. It is
+    /// flipped along with the geometric normal when the surface is hit from the
+    /// inside.
+    /// `tangent` is made orthogonal to the shading normal. If it is parallel to
+    /// the shading normal, an arbitrary tangent is chosen.
+    pub fn set_shading_frame(&mut self, outward_shading_normal: Vec3, tangent: Vec3) {
+        let shading_normal = outward_shading_normal.normalized();
+        self.shading_normal = if self.front_face {
+            shading_normal
+        } else {
+            -shading_normal
+        };
+
+        let tangent = tangent - tangent.dot(&self.shading_normal) * self.shading_normal;
+        if tangent.is_near_zero() {
+            let (tangent, bitangent) = self.shading_normal.orthonormal_basis();
+            self.tangent = tangent;
+            self.bitangent = bitangent;
+        } else {
+            self.tangent = tangent.normalized();
+            self.bitangent = self.shading_normal.cross(&self.tangent);
+        }
+    }
+}
+
+/// An interval of a ray inside a closed [`Hittable`], from the point where the
+/// ray enters it to the point where the ray exits it.
+pub struct Span<'a> {
+    /// The record of the ray entering the hittable. Its
+    /// [`HitRecord::front_face`] is `true`.
+    pub entry: HitRecord<'a>,
+    /// The record of the ray exiting the hittable. Its
+    /// [`HitRecord::front_face`] is `false`.
+    pub exit: HitRecord<'a>,
 }
 
 /// An object that can be hit.
 pub trait Hittable {
     /// Tries to hit an object with a ray. The intersection point must be in the
     /// `valid_range`.
-    fn try_hit(&self, r: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord>;
+    fn try_hit(&self, r: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>>;
+
+    /// Returns the smallest [`Aabb`] enclosing the object, or [`None`] if the
+    /// object is unbounded (e.g. an infinite plane).
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    /// Returns whether anything of the object is hit by the ray in the
+    /// `valid_range`, e.g. to know if a light is in the shadow of the object.
+    ///
+    /// Any hit answers the query, so hittables can stop at the first one they
+    /// find and skip building its record.
+    fn occluded(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> bool {
+        self.try_hit(ray, valid_range).is_some()
+    }
+
+    /// Samples a direction from `origin` toward the object, e.g. to send rays
+    /// toward an emissive object. Returns [`None`] if the object can't be
+    /// sampled.
+    fn sample_direction(&self, _origin: Point3) -> Option<Vec3> {
+        None
+    }
+
+    /// Returns the probability density with respect to solid angle of
+    /// [`Hittable::sample_direction`] giving the `direction` from `origin`.
+    fn direction_pdf(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// Samples a point on the surface of the object, e.g. to send light from
+    /// an emissive object. Returns the record of a ray hitting the point from
+    /// outside and the probability density of the point with respect to area,
+    /// or [`None`] if the object can't be sampled.
+    fn sample_surface(&self) -> Option<(HitRecord<'_>, f64)> {
+        None
+    }
+
+    /// Returns the probability density with respect to area of
+    /// [`Hittable::sample_surface`] giving the `point`, which must be on the
+    /// surface of the object.
+    fn surface_pdf(&self, _point: Point3) -> f64 {
+        0.0
+    }
+
+    /// Returns every interval of the whole line of the ray (including negative
+    /// distances) inside the object, sorted by distance, or [`None`] if the
+    /// object isn't a closed solid or doesn't support it.
+    ///
+    /// Spans are what constructive solid geometry operations are computed from.
+    fn spans(&self, _ray: &Ray) -> Option<Vec<Span<'_>>> {
+        None
+    }
 }
@@ -0,0 +1,151 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    sphere::{hit_sphere, sphere_spans},
+    Aabb, HitRecord, Hittable, Span,
+};
+
+/// A sphere moving in a straight line at constant speed.
+///
+/// The sphere goes from `center0` at `time0` to `center1` at `time1`, and
+/// stays still before and after.
+pub struct MovingSphere {
+    center0: Point3,
+    time0: f64,
+    center1: Point3,
+    time1: f64,
+    radius: f64,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl MovingSphere {
+    /// Constructs a new `MovingSphere`, at `center0` at `time0` and at
+    /// `center1` at `time1`.
+    pub fn new(
+        center0: Point3,
+        center1: Point3,
+        time0: f64,
+        time1: f64,
+        radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> MovingSphere {
+        MovingSphere {
+            center0,
+            time0,
+            center1,
+            time1,
+            radius,
+            material,
+        }
+    }
+
+    /// Returns the center of the sphere at `time`.
+    pub fn center(&self, time: f64) -> Point3 {
+        if self.time1 <= self.time0 {
+            return self.center0;
+        }
+
+        let progress = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.center0 + progress * (self.center1 - self.center0)
+    }
+}
+
+impl Hittable for MovingSphere {
+    /// Tries to hit the sphere, where it is at the time of the ray, with a
+    /// ray. The intersection point must be in the `valid_range`.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        hit_sphere(
+            self.center(ray.time()),
+            self.radius,
+            &self.material,
+            ray,
+            valid_range,
+        )
+    }
+
+    fn spans(&self, ray: &Ray) -> Option<Vec<Span<'_>>> {
+        Some(sphere_spans(
+            self.center(ray.time()),
+            self.radius,
+            &self.material,
+            ray,
+        ))
+    }
+
+    /// Returns the box enclosing the sphere during its whole motion.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let radius = Vec3::new(self.radius, self.radius, self.radius);
+        let start = Aabb::new(self.center0 - radius, self.center0 + radius);
+        let end = Aabb::new(self.center1 - radius, self.center1 + radius);
+
+        Some(start.surrounding(&end))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, material::Lambertian};
+
+    use super::*;
+
+    fn moving_sphere() -> MovingSphere {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        MovingSphere::new(
+            Point3::new(3.0, 0.0, 0.0),
+            Point3::new(3.0, 2.0, 0.0),
+            0.0,
+            1.0,
+            1.0,
+            material as Arc<dyn Material + Send + Sync>,
+        )
+    }
+
+    #[test]
+    fn center_is_interpolated_and_clamped() {
+        let sphere = moving_sphere();
+
+        assert_eq!(sphere.center(0.5), Point3::new(3.0, 1.0, 0.0));
+        assert_eq!(sphere.center(-1.0), Point3::new(3.0, 0.0, 0.0));
+        assert_eq!(sphere.center(2.0), Point3::new(3.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn try_hit_moving_sphere_with_ray_sent_before_it_arrives() {
+        let sphere = moving_sphere();
+        let ray = Ray::with_time(Point3::new(1.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+
+        assert!(sphere.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_moving_sphere_with_ray_sent_when_it_is_there() {
+        let sphere = moving_sphere();
+        let ray = Ray::with_time(Point3::new(1.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 1.0);
+
+        let result = sphere
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit sphere");
+
+        assert_eq!(result.intersection_point, Point3::new(2.0, 2.0, 0.0));
+        assert_eq!(result.t, 1.0);
+        assert_eq!(result.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(result.front_face);
+    }
+
+    #[test]
+    fn bounding_box_encloses_whole_motion() {
+        assert_eq!(
+            moving_sphere().bounding_box(),
+            Some(Aabb::new(
+                Point3::new(2.0, -1.0, -1.0),
+                Point3::new(4.0, 3.0, 1.0)
+            ))
+        );
+    }
+}
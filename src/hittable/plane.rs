@@ -0,0 +1,129 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, HitRecord, Hittable};
+
+/// An infinite plane.
+pub struct Plane {
+    point: Point3,
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Plane {
+    /// Constructs a new `Plane` going through `point`, whose outward normal is
+    /// `normal`.
+    pub fn new(point: Point3, normal: Vec3, material: Arc<dyn Material + Sync + Send>) -> Plane {
+        let normal = normal.normalized();
+        let (tangent, bitangent) = normal.orthonormal_basis();
+
+        Plane {
+            point,
+            normal,
+            tangent,
+            bitangent,
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    /// Tries to hit a plane with a ray. The intersection point must be in the
+    /// `valid_range`.
+    /// See [Line-plane intersection on Wikipedia](https://en.wikipedia.org/wiki/Line–plane_intersection).
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let denominator = self.normal.dot(&ray.direction());
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = (self.point - ray.origin()).dot(&self.normal) / denominator;
+        if !valid_range.contains(&t) {
+            return None;
+        }
+
+        let mut record = HitRecord::new(ray, t, self.normal, Arc::clone(&self.material));
+        let offset = record.intersection_point - self.point;
+        record.uv = (offset.dot(&self.tangent), offset.dot(&self.bitangent));
+        record.set_shading_frame(self.normal, self.tangent);
+
+        Some(record)
+    }
+
+    /// Returns [`None`], as a plane is unbounded.
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, material::Lambertian};
+
+    use super::*;
+
+    fn plane() -> Plane {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Plane::new(
+            Point3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            material as Arc<dyn Material + Send + Sync>,
+        )
+    }
+
+    #[test]
+    fn try_hit_plane_with_parallel_ray() {
+        let ray = Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 1.0));
+
+        assert!(plane().try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_plane_with_hitting_ray_not_in_range() {
+        let ray = Ray::new(Point3::zero(), Vec3::new(0.0, -1.0, 0.0));
+
+        assert!(plane().try_hit(&ray, 0.0..=0.5).is_none());
+    }
+
+    #[test]
+    fn try_hit_plane_with_hitting_ray_in_range() {
+        let ray = Ray::new(Point3::new(5.0, 0.0, 5.0), Vec3::new(0.0, -2.0, 0.0));
+
+        let plane = plane();
+
+        let result = plane
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit plane");
+
+        assert_eq!(result.intersection_point, Point3::new(5.0, -1.0, 5.0));
+        assert_eq!(result.t, 0.5);
+        assert_eq!(result.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!(result.front_face);
+    }
+
+    #[test]
+    fn try_hit_plane_from_behind() {
+        let ray = Ray::new(Point3::new(0.0, -2.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+        let plane = plane();
+
+        let result = plane
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit plane");
+
+        assert_eq!(result.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(!result.front_face);
+    }
+
+    #[test]
+    fn plane_is_unbounded() {
+        assert!(plane().bounding_box().is_none());
+    }
+}
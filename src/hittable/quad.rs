@@ -0,0 +1,305 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    random::random,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, HitRecord, Hittable};
+
+/// A quadrilateral (more precisely a parallelogram).
+///
+/// The quad is made of the points `corner + α * u + β * v` with α and β in
+/// [0, 1]. Its outward normal is along `u × v`.
+pub struct Quad {
+    corner: Point3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    /// `normal / (u × v).length()`, used to compute the planar coordinates of
+    /// a point.
+    w: Vec3,
+    area: f64,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Quad {
+    /// Constructs a new `Quad` from a `corner` and its two edges `u` and `v`
+    /// starting from that corner.
+    pub fn new(
+        corner: Point3,
+        u: Vec3,
+        v: Vec3,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Quad {
+        let n = u.cross(&v);
+
+        Quad {
+            corner,
+            u,
+            v,
+            normal: n.normalized(),
+            w: n / n.length_squared(),
+            area: n.length(),
+            material,
+        }
+    }
+
+    /// Constructs a new axis-aligned `Quad` from two opposite corners, which
+    /// must share one of their coordinates. The outward normal points toward
+    /// the positive side of the axis of that coordinate.
+    ///
+    /// # Panics
+    ///
+    /// The `axis_aligned` function will panic if the corners don't share a
+    /// coordinate.
+    pub fn axis_aligned(a: Point3, b: Point3, material: Arc<dyn Material + Sync + Send>) -> Quad {
+        let minimum = a.min(&b);
+        let extent = a.max(&b) - minimum;
+
+        let (u, v) = if extent.x() == 0.0 {
+            (
+                Vec3::new(0.0, extent.y(), 0.0),
+                Vec3::new(0.0, 0.0, extent.z()),
+            )
+        } else if extent.y() == 0.0 {
+            (
+                Vec3::new(0.0, 0.0, extent.z()),
+                Vec3::new(extent.x(), 0.0, 0.0),
+            )
+        } else if extent.z() == 0.0 {
+            (
+                Vec3::new(extent.x(), 0.0, 0.0),
+                Vec3::new(0.0, extent.y(), 0.0),
+            )
+        } else {
+            panic!("The corners of an axis-aligned quad must share a coordinate")
+        };
+
+        Quad::new(minimum, u, v, material)
+    }
+
+    /// Returns the coordinates of a `point` of the plane of the quad along its
+    /// edges, in [0, 1] when the point is inside.
+    fn planar_coordinates(&self, point: Point3) -> (f64, f64) {
+        let planar_point = point - self.corner;
+
+        (
+            self.w.dot(&planar_point.cross(&self.v)),
+            self.w.dot(&self.u.cross(&planar_point)),
+        )
+    }
+
+    /// Constructs the `HitRecord` of a ray hitting the quad at `t`.
+    pub(super) fn hit_record(&self, ray: &Ray, t: f64) -> HitRecord<'_> {
+        let mut record = HitRecord::new(ray, t, self.normal, Arc::clone(&self.material));
+        record.uv = self.planar_coordinates(record.intersection_point);
+        record.set_shading_frame(self.normal, self.u);
+
+        record
+    }
+}
+
+impl Hittable for Quad {
+    /// Tries to hit a quad with a ray. The intersection point must be in the
+    /// `valid_range`.
+    ///
+    /// The ray is intersected with the plane of the quad, then the planar
+    /// coordinates of the intersection point tell whether it is inside.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let denominator = self.normal.dot(&ray.direction());
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = (self.corner - ray.origin()).dot(&self.normal) / denominator;
+        if !valid_range.contains(&t) {
+            return None;
+        }
+
+        let (alpha, beta) = self.planar_coordinates(ray.at(t));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        Some(self.hit_record(ray, t))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(
+            Aabb::new(self.corner, self.corner + self.u + self.v)
+                .including(self.corner + self.u)
+                .including(self.corner + self.v),
+        )
+    }
+
+    /// Samples a direction toward a point uniformly distributed on the quad.
+    fn sample_direction(&self, origin: Point3) -> Option<Vec3> {
+        Some(self.corner + random() * self.u + random() * self.v - origin)
+    }
+
+    /// Samples a point uniformly distributed on the quad.
+    fn sample_surface(&self) -> Option<(HitRecord<'_>, f64)> {
+        let point = self.corner + random() * self.u + random() * self.v;
+        let ray = Ray::new(point + self.normal, -self.normal);
+
+        Some((self.hit_record(&ray, 1.0), 1.0 / self.area))
+    }
+
+    fn surface_pdf(&self, _point: Point3) -> f64 {
+        1.0 / self.area
+    }
+
+    /// Returns the density of the points of the quad, converted from area to
+    /// solid angle as seen from `origin`.
+    fn direction_pdf(&self, origin: Point3, direction: Vec3) -> f64 {
+        let ray = Ray::new(origin, direction);
+        match self.try_hit(&ray, 0.001..=f64::INFINITY) {
+            Some(hit) => {
+                let distance_squared = hit.t * hit.t * direction.length_squared();
+                let cosine = self.normal.dot(&direction).abs() / direction.length();
+
+                distance_squared / (cosine * self.area)
+            }
+            None => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use crate::{color::Color, material::Lambertian, random::seed};
+
+    use super::*;
+
+    fn quad() -> Quad {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Quad::new(
+            Point3::new(-1.0, -1.0, -2.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 4.0, 0.0),
+            material as Arc<dyn Material + Send + Sync>,
+        )
+    }
+
+    #[test]
+    fn try_hit_quad_with_not_hitting_ray() {
+        let ray = Ray::new(Point3::new(1.5, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(quad().try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_quad_with_hitting_ray_not_in_range() {
+        let ray = Ray::new(Point3::zero(), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(quad().try_hit(&ray, 0.0..=1.0).is_none());
+    }
+
+    #[test]
+    fn try_hit_quad_with_hitting_ray_in_range() {
+        let ray = Ray::new(Point3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        let quad = quad();
+
+        let result = quad
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit quad");
+
+        assert_eq!(result.intersection_point, Point3::new(0.0, 2.0, -2.0));
+        assert_eq!(result.t, 2.0);
+        assert_eq!(result.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(result.front_face);
+        assert_eq!(result.uv, (0.5, 0.75));
+        assert_eq!(result.tangent, Vec3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn axis_aligned_quad_normal_points_toward_positive_axis() {
+        let material = Arc::new(Lambertian::new(Color::zero())) as Arc<dyn Material + Send + Sync>;
+        let ray = Ray::new(Point3::new(0.5, 0.5, 0.5), Vec3::new(-1.0, -1.0, -1.0));
+
+        for (a, b) in &[
+            (Point3::zero(), Point3::new(0.0, 1.0, 1.0)),
+            (Point3::zero(), Point3::new(1.0, 0.0, 1.0)),
+            (Point3::new(1.0, 1.0, 0.0), Point3::zero()),
+        ] {
+            let quad = Quad::axis_aligned(*a, *b, Arc::clone(&material));
+            let result = quad
+                .try_hit(&ray, 0.0..=f64::INFINITY)
+                .expect("ray should hit quad");
+
+            assert!(result.front_face);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn axis_aligned_quad_with_corners_not_sharing_a_coordinate_panics() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Quad::axis_aligned(Point3::zero(), Point3::new(1.0, 1.0, 1.0), material);
+    }
+
+    #[test]
+    fn sampled_directions_hit_the_quad() {
+        let quad = quad();
+        let origin = Point3::new(0.5, 0.0, 1.0);
+
+        for _ in 0..100 {
+            let direction = quad.sample_direction(origin).unwrap();
+            let ray = Ray::new(origin, direction);
+
+            assert!(quad.try_hit(&ray, 0.0..=f64::INFINITY).is_some());
+            assert!(quad.direction_pdf(origin, direction) > 0.0);
+        }
+        assert_eq!(quad.direction_pdf(origin, Vec3::new(0.0, 0.0, 1.0)), 0.0);
+    }
+
+    #[test]
+    fn sampled_points_are_on_the_quad() {
+        let quad = quad();
+
+        for _ in 0..100 {
+            let (record, pdf) = quad.sample_surface().unwrap();
+            let (alpha, beta) = quad.planar_coordinates(record.intersection_point);
+
+            assert!((record.intersection_point.z() + 2.0).abs() < 1e-12);
+            assert!((0.0..=1.0).contains(&alpha) && (0.0..=1.0).contains(&beta));
+            assert!(record.front_face);
+            assert_eq!(record.normal, Vec3::new(0.0, 0.0, 1.0));
+            assert_eq!(pdf, 1.0 / 8.0);
+        }
+    }
+
+    #[test]
+    fn direction_pdf_integrates_to_one() {
+        let quad = quad();
+        let origin = Point3::new(0.5, 0.0, -1.0);
+        let samples = 200_000;
+        seed(20);
+
+        let integral = (0..samples)
+            .map(|_| quad.direction_pdf(origin, Vec3::random_normalized()))
+            .sum::<f64>()
+            * 4.0
+            * PI
+            / samples as f64;
+
+        assert!((integral - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn bounding_box_encloses_quad() {
+        assert_eq!(
+            quad().bounding_box(),
+            Some(Aabb::new(
+                Point3::new(-1.0, -1.0, -2.0),
+                Point3::new(1.0, 3.0, -2.0)
+            ))
+        );
+    }
+}
@@ -0,0 +1,450 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, HitRecord, Hittable};
+
+/// The maximum number of steps along a ray before giving up.
+const MAX_STEPS: usize = 512;
+/// The distance to the surface under which a point is considered on it.
+const HIT_DISTANCE: f64 = 1e-5;
+/// The distance beyond which an unbounded field is considered missed.
+const MAX_DISTANCE: f64 = 1e4;
+/// The offset used to compute the gradient of the field by central
+/// differences.
+const GRADIENT_OFFSET: f64 = 1e-6;
+
+/// A signed distance function, giving for each point the distance to the
+/// closest point of a surface. The distance is negative inside the surface.
+///
+/// The distance may be underestimated (e.g. for fractals), which only makes
+/// ray marching slower. Any `Fn(Point3) -> f64` closure is a distance
+/// function.
+///
+/// See [Inigo Quilez's distance functions](https://iquilezles.org/articles/distfunctions/).
+pub trait DistanceFunction {
+    /// Returns the signed distance from `point` to the surface.
+    fn distance(&self, point: Point3) -> f64;
+
+    /// Returns the box enclosing the surface, or [`None`] if it is unknown or
+    /// the surface is unbounded.
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+impl<F> DistanceFunction for F
+where
+    F: Fn(Point3) -> f64,
+{
+    fn distance(&self, point: Point3) -> f64 {
+        self(point)
+    }
+}
+
+/// A box with rounded edges.
+pub struct RoundedBox {
+    center: Point3,
+    half_extent: Vec3,
+    radius: f64,
+}
+
+impl RoundedBox {
+    /// Constructs a new `RoundedBox` centered on `center`, whose sides are
+    /// `size` long (including the rounded edges) and whose edges are rounded
+    /// with `radius`.
+    pub fn new(center: Point3, size: Vec3, radius: f64) -> RoundedBox {
+        RoundedBox {
+            center,
+            half_extent: size / 2.0,
+            radius,
+        }
+    }
+}
+
+impl DistanceFunction for RoundedBox {
+    fn distance(&self, point: Point3) -> f64 {
+        let p = point - self.center;
+        let inner = self.half_extent - Vec3::new(self.radius, self.radius, self.radius);
+        let q = Vec3::new(p.x().abs(), p.y().abs(), p.z().abs()) - inner;
+
+        q.max(&Vec3::zero()).length() + q.x().max(q.y()).max(q.z()).min(0.0) - self.radius
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.center - self.half_extent,
+            self.center + self.half_extent,
+        ))
+    }
+}
+
+/// A cylinder with hemispherical ends, i.e. the points at `radius` from a
+/// segment.
+pub struct Capsule {
+    a: Point3,
+    b: Point3,
+    radius: f64,
+}
+
+impl Capsule {
+    /// Constructs a new `Capsule` around the segment from `a` to `b`.
+    pub fn new(a: Point3, b: Point3, radius: f64) -> Capsule {
+        Capsule { a, b, radius }
+    }
+}
+
+impl DistanceFunction for Capsule {
+    fn distance(&self, point: Point3) -> f64 {
+        let pa = point - self.a;
+        let ba = self.b - self.a;
+        // The parameter of the closest point of the segment, which is a single
+        // point when the capsule is a sphere.
+        let h = if ba.length_squared() > 0.0 {
+            (pa.dot(&ba) / ba.length_squared()).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        (pa - h * ba).length() - self.radius
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.a, self.b).expanded(self.radius))
+    }
+}
+
+/// The union of two surfaces, blended together where they are closer than a
+/// `smoothness` distance.
+pub struct SmoothUnion {
+    a: Arc<dyn DistanceFunction + Sync + Send>,
+    b: Arc<dyn DistanceFunction + Sync + Send>,
+    smoothness: f64,
+}
+
+impl SmoothUnion {
+    /// Constructs a new `SmoothUnion` of the `a` and `b` surfaces.
+    pub fn new(
+        a: Arc<dyn DistanceFunction + Sync + Send>,
+        b: Arc<dyn DistanceFunction + Sync + Send>,
+        smoothness: f64,
+    ) -> SmoothUnion {
+        SmoothUnion { a, b, smoothness }
+    }
+}
+
+impl DistanceFunction for SmoothUnion {
+    /// Returns the polynomial smooth minimum of both distances.
+    ///
+    /// See [Inigo Quilez's smooth minimum](https://iquilezles.org/articles/smin/).
+    fn distance(&self, point: Point3) -> f64 {
+        let (a, b) = (self.a.distance(point), self.b.distance(point));
+        let h = (0.5 + 0.5 * (b - a) / self.smoothness).clamp(0.0, 1.0);
+
+        b + h * (a - b) - self.smoothness * h * (1.0 - h)
+    }
+
+    /// Returns the box enclosing both surfaces, grown by how much the blend
+    /// can bulge out of them.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bounds = self.a.bounding_box()?.surrounding(&self.b.bounding_box()?);
+
+        Some(bounds.expanded(self.smoothness / 4.0))
+    }
+}
+
+/// The Mandelbulb fractal, a 3-dimensional analogue of the Mandelbrot set,
+/// centered on the origin.
+///
+/// See [Mandelbulb on Wikipedia](https://en.wikipedia.org/wiki/Mandelbulb).
+pub struct Mandelbulb {
+    power: f64,
+    iterations: usize,
+}
+
+impl Mandelbulb {
+    /// Constructs a new `Mandelbulb` of the given `power` (8 for the classic
+    /// shape), refined by `iterations` iterations.
+    pub fn new(power: f64, iterations: usize) -> Mandelbulb {
+        Mandelbulb { power, iterations }
+    }
+}
+
+impl DistanceFunction for Mandelbulb {
+    /// Returns an estimate of the distance to the fractal, computed from the
+    /// derivative of the iterated function.
+    fn distance(&self, point: Point3) -> f64 {
+        let mut z = point;
+        let mut derivative = 1.0;
+        let mut r = z.length();
+
+        for _ in 0..self.iterations {
+            if r > 2.0 || r == 0.0 {
+                break;
+            }
+
+            let theta = (z.z() / r).acos() * self.power;
+            let phi = z.y().atan2(z.x()) * self.power;
+            derivative = r.powf(self.power - 1.0) * self.power * derivative + 1.0;
+
+            let (sin_theta, cos_theta) = theta.sin_cos();
+            let (sin_phi, cos_phi) = phi.sin_cos();
+            z = r.powf(self.power) * Vec3::new(sin_theta * cos_phi, sin_phi * sin_theta, cos_theta)
+                + point;
+            r = z.length();
+        }
+
+        if r == 0.0 {
+            return 0.0;
+        }
+        0.5 * r.ln() * r / derivative
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            Point3::new(-1.25, -1.25, -1.25),
+            Point3::new(1.25, 1.25, 1.25),
+        ))
+    }
+}
+
+/// A surface defined by a signed distance function, rendered by sphere
+/// tracing.
+///
+/// See [Sphere Tracing](https://graphics.stanford.edu/courses/cs348b-20-spring-content/uploads/hart.pdf),
+/// Hart, 1996.
+pub struct Sdf {
+    distance: Arc<dyn DistanceFunction + Sync + Send>,
+    bounds: Option<Aabb>,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Sdf {
+    /// Constructs a new `Sdf`, bounded by the box of the distance function.
+    pub fn new(
+        distance: Arc<dyn DistanceFunction + Sync + Send>,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Sdf {
+        let bounds = distance.bounding_box();
+
+        Sdf {
+            distance,
+            bounds,
+            material,
+        }
+    }
+
+    /// Constructs a new `Sdf` whose surface is inside `bounds`, for distance
+    /// functions without a bounding box such as closures.
+    pub fn with_bounds(
+        distance: Arc<dyn DistanceFunction + Sync + Send>,
+        bounds: Aabb,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Sdf {
+        Sdf {
+            distance,
+            bounds: Some(bounds),
+            material,
+        }
+    }
+
+    /// Returns the gradient of the distance function at `point`, computed by
+    /// central differences. It is along the outward normal of the surface.
+    fn gradient(&self, point: Point3) -> Vec3 {
+        let axis_difference = |offset: Vec3| {
+            self.distance.distance(point + offset) - self.distance.distance(point - offset)
+        };
+
+        Vec3::new(
+            axis_difference(Vec3::new(GRADIENT_OFFSET, 0.0, 0.0)),
+            axis_difference(Vec3::new(0.0, GRADIENT_OFFSET, 0.0)),
+            axis_difference(Vec3::new(0.0, 0.0, GRADIENT_OFFSET)),
+        )
+    }
+}
+
+impl Hittable for Sdf {
+    /// Tries to hit the surface with a ray. The intersection point must be in
+    /// the `valid_range`.
+    ///
+    /// The ray marches toward the surface by the distance to it, which can't
+    /// overshoot, until it is close enough. Rays starting inside march toward
+    /// the surface the same way, using the absolute distance.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let (start, end) = match self.bounds {
+            Some(bounds) => bounds.clip(ray, valid_range)?,
+            None => (*valid_range.start(), valid_range.end().min(MAX_DISTANCE)),
+        };
+
+        // Distances are measured in space, while `t` is in ray direction units.
+        let direction_length = ray.direction().length();
+        let mut t = start;
+
+        for _ in 0..MAX_STEPS {
+            let distance = self.distance.distance(ray.at(t)).abs();
+            if distance < HIT_DISTANCE {
+                let outward_normal = self.gradient(ray.at(t)).normalized();
+                return Some(HitRecord::new(
+                    ray,
+                    t,
+                    outward_normal,
+                    Arc::clone(&self.material),
+                ));
+            }
+
+            t += distance / direction_length;
+            if t > end {
+                return None;
+            }
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, material::Lambertian};
+
+    use super::super::Sphere;
+    use super::*;
+
+    fn material() -> Arc<dyn Material + Send + Sync> {
+        Arc::new(Lambertian::new(Color::zero()))
+    }
+
+    fn sphere_sdf() -> Sdf {
+        let sphere = |point: Point3| (point - Point3::new(3.0, 0.0, 0.0)).length() - 1.0;
+        Sdf::with_bounds(
+            Arc::new(sphere),
+            Aabb::new(Point3::new(2.0, -1.0, -1.0), Point3::new(4.0, 1.0, 1.0)),
+            material(),
+        )
+    }
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn try_hit_sdf_with_not_hitting_ray() {
+        let sdf = sphere_sdf();
+        let ray = Ray::new(Point3::new(-1.0, -1.0, -1.0), Vec3::new(-1.0, -1.0, -1.0));
+
+        assert!(sdf.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_sdf_with_grazing_ray() {
+        let sdf = sphere_sdf();
+        let ray = Ray::new(Point3::new(0.0, 1.001, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(sdf.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_sdf_with_hitting_ray_not_in_range() {
+        let sdf = sphere_sdf();
+        let ray = Ray::new(Point3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(sdf.try_hit(&ray, 0.0..=0.5).is_none());
+    }
+
+    #[test]
+    fn try_hit_sdf_matches_sphere() {
+        let sdf = sphere_sdf();
+        let sphere = Sphere::new(Point3::new(3.0, 0.0, 0.0), 1.0, material());
+
+        for direction in &[
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(3.0, 0.5, 0.2),
+            Vec3::new(6.0, -1.0, 0.5),
+        ] {
+            let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), *direction);
+            let expected = sphere.try_hit(&ray, 0.0..=f64::INFINITY).unwrap();
+            let result = sdf
+                .try_hit(&ray, 0.0..=f64::INFINITY)
+                .expect("ray should hit sdf");
+
+            assert!((result.t - expected.t).abs() < 1e-4);
+            assert_near(result.intersection_point, expected.intersection_point);
+            assert_near(result.normal, expected.normal);
+            assert!(result.front_face);
+        }
+    }
+
+    #[test]
+    fn try_hit_sdf_from_inside() {
+        let sdf = sphere_sdf();
+        let ray = Ray::new(Point3::new(3.0, 0.0, 0.0), Vec3::new(0.0, 2.0, 0.0));
+
+        let result = sdf
+            .try_hit(&ray, 0.001..=f64::INFINITY)
+            .expect("ray should hit sdf");
+
+        assert_near(result.intersection_point, Point3::new(3.0, 1.0, 0.0));
+        assert!((result.t - 0.5).abs() < 1e-4);
+        assert_near(result.normal, Vec3::new(0.0, -1.0, 0.0));
+        assert!(!result.front_face);
+    }
+
+    #[test]
+    fn rounded_box_distance_works() {
+        let rounded_box = RoundedBox::new(Point3::zero(), Vec3::new(2.0, 2.0, 2.0), 0.5);
+
+        assert_eq!(rounded_box.distance(Point3::new(3.0, 0.0, 0.0)), 2.0);
+        assert_eq!(rounded_box.distance(Point3::zero()), -1.0);
+        // Off the rounded corner.
+        let corner = rounded_box.distance(Point3::new(1.0, 1.0, 1.0));
+        assert!((corner - (0.75f64.sqrt() - 0.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn capsule_distance_works() {
+        let capsule = Capsule::new(Point3::zero(), Point3::new(0.0, 2.0, 0.0), 0.5);
+
+        assert_eq!(capsule.distance(Point3::new(1.0, 1.0, 0.0)), 0.5);
+        assert_eq!(capsule.distance(Point3::new(0.0, 4.0, 0.0)), 1.5);
+        assert_eq!(capsule.distance(Point3::new(0.0, 1.0, 0.0)), -0.5);
+    }
+
+    #[test]
+    fn smooth_union_blends_surfaces() {
+        let a: Arc<dyn DistanceFunction + Sync + Send> =
+            Arc::new(Capsule::new(Point3::zero(), Point3::zero(), 1.0));
+        let b: Arc<dyn DistanceFunction + Sync + Send> = Arc::new(Capsule::new(
+            Point3::new(3.0, 0.0, 0.0),
+            Point3::new(3.0, 0.0, 0.0),
+            1.0,
+        ));
+        let union = SmoothUnion::new(Arc::clone(&a), Arc::clone(&b), 1.0);
+
+        // Far from the blend, the union is the closest surface.
+        let far = Point3::new(-3.0, 0.0, 0.0);
+        assert_eq!(union.distance(far), a.distance(far));
+        // Between both surfaces, the blend fills the gap.
+        let between = Point3::new(1.5, 0.0, 0.0);
+        assert!(union.distance(between) < a.distance(between).min(b.distance(between)));
+    }
+
+    #[test]
+    fn try_hit_mandelbulb() {
+        let sdf = Sdf::new(Arc::new(Mandelbulb::new(8.0, 8)), material());
+        let ray = Ray::new(Point3::new(0.0, 0.0, -3.0), Vec3::new(0.0, 0.0, 1.0));
+
+        let result = sdf
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit mandelbulb");
+
+        assert!(result.t > 1.75 && result.t < 3.0);
+        assert!(result.front_face);
+    }
+}
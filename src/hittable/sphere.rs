@@ -1,8 +1,13 @@
-use std::{ops::RangeInclusive, sync::Arc};
+use std::{f64::consts::PI, ops::RangeInclusive, sync::Arc};
 
-use crate::{material::Material, ray::Ray, vec3::Point3};
+use crate::{
+    material::Material,
+    random::random,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
 
-use super::{HitRecord, Hittable};
+use super::{Aabb, HitRecord, Hittable, Span};
 
 pub struct Sphere<'a> {
     center: Point3,
@@ -23,50 +28,179 @@ impl<'a> Sphere<'a> {
             material,
         }
     }
+
+    /// Returns the cosine of the half angle of the cone of directions from
+    /// `origin` toward the sphere, or [`None`] if `origin` is inside.
+    fn cos_theta_max(&self, origin: Point3) -> Option<f64> {
+        let sin_squared = self.radius * self.radius / (self.center - origin).length_squared();
+        if sin_squared >= 1.0 {
+            return None;
+        }
+
+        Some((1.0 - sin_squared).sqrt())
+    }
+}
+
+/// Returns the texture coordinates of a `point` on the unit sphere.
+///
+/// `u` is the angle around the Y axis from X = -1, `v` the angle from Y = -1,
+/// both normalized to [0, 1].
+fn uv(point: &Point3) -> (f64, f64) {
+    let theta = (-point.y()).acos();
+    let phi = (-point.z()).atan2(point.x()) + PI;
+
+    (phi / (2.0 * PI), theta / PI)
+}
+
+/// Returns the distances along the ray where it meets the sphere of `center`
+/// and `radius`, in increasing order, or [`None`] if it misses the sphere.
+/// See [Line-sphere intersection on Wikipedia](https://en.wikipedia.org/wiki/Line–sphere_intersection).
+#[allow(clippy::suspicious_operation_groupings)]
+fn roots(center: Point3, radius: f64, ray: &Ray) -> Option<(f64, f64)> {
+    let oc = ray.origin() - center;
+
+    let a = ray.direction().length_squared();
+    let half_b = oc.dot(&ray.direction());
+    let c = oc.length_squared() - radius * radius;
+    let discriminant = (half_b * half_b) - a * c;
+
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let sqrtd = discriminant.sqrt();
+    Some(((-half_b - sqrtd) / a, (-half_b + sqrtd) / a))
+}
+
+/// Constructs the `HitRecord` of a ray hitting the sphere of `center` and
+/// `radius` at `t`.
+fn sphere_record<'a>(
+    center: Point3,
+    radius: f64,
+    material: &Arc<dyn Material + Sync + Send + 'a>,
+    ray: &Ray,
+    t: f64,
+) -> HitRecord<'a> {
+    let outward_normal = (ray.at(t) - center) / radius;
+    let mut record = HitRecord::new(ray, t, outward_normal, Arc::clone(material));
+    record.uv = uv(&outward_normal);
+    // The direction in which `u` increases, undefined at the poles.
+    record.set_shading_frame(
+        outward_normal,
+        Vec3::new(outward_normal.z(), 0.0, -outward_normal.x()),
+    );
+
+    record
+}
+
+/// Tries to hit the sphere of `center` and `radius` with a ray. The
+/// intersection point must be in the `valid_range`.
+pub(super) fn hit_sphere<'a>(
+    center: Point3,
+    radius: f64,
+    material: &Arc<dyn Material + Sync + Send + 'a>,
+    ray: &Ray,
+    valid_range: RangeInclusive<f64>,
+) -> Option<HitRecord<'a>> {
+    let (near, far) = roots(center, radius, ray)?;
+
+    // Find the nearest root that lies in the acceptable range.
+    let root = if valid_range.contains(&near) {
+        near
+    } else if valid_range.contains(&far) {
+        far
+    } else {
+        return None;
+    };
+
+    Some(sphere_record(center, radius, material, ray, root))
+}
+
+/// Returns the span of a ray inside the sphere of `center` and `radius`.
+pub(super) fn sphere_spans<'a>(
+    center: Point3,
+    radius: f64,
+    material: &Arc<dyn Material + Sync + Send + 'a>,
+    ray: &Ray,
+) -> Vec<Span<'a>> {
+    match roots(center, radius, ray) {
+        Some((near, far)) => vec![Span {
+            entry: sphere_record(center, radius, material, ray, near),
+            exit: sphere_record(center, radius, material, ray, far),
+        }],
+        None => Vec::new(),
+    }
 }
 
 impl<'a> Hittable for Sphere<'a> {
     /// Tries to hit a sphere with a ray. The intersection point must be in the
     /// `valid_range`.
-    /// See [Line-sphere intersection on Wikipedia](https://en.wikipedia.org/wiki/Line–sphere_intersection).
-    #[allow(clippy::suspicious_operation_groupings)]
-    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord> {
-        let oc = ray.origin() - self.center;
+    ///
+    /// See [`hit_sphere`].
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        hit_sphere(self.center, self.radius, &self.material, ray, valid_range)
+    }
 
-        let a = ray.direction().length_squared();
-        let half_b = oc.dot(&ray.direction());
-        let c = oc.length_squared() - self.radius * self.radius;
-        let discriminant = (half_b * half_b) - a * c;
+    fn spans(&self, ray: &Ray) -> Option<Vec<Span<'_>>> {
+        Some(sphere_spans(self.center, self.radius, &self.material, ray))
+    }
 
-        if discriminant < 0.0 {
-            return None;
-        }
+    fn bounding_box(&self) -> Option<Aabb> {
+        let radius = Vec3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - radius, self.center + radius))
+    }
 
-        let sqrtd = discriminant.sqrt();
+    /// Samples a direction uniformly in the cone of directions from `origin`
+    /// toward the sphere, or in every direction from inside the sphere.
+    fn sample_direction(&self, origin: Point3) -> Option<Vec3> {
+        let cos_theta_max = match self.cos_theta_max(origin) {
+            Some(cos_theta_max) => cos_theta_max,
+            None => return Some(Vec3::random_normalized()),
+        };
 
-        // Find the nearest root that lies in the acceptable range.
-        let mut root = (-half_b - sqrtd) / a;
-        if !valid_range.contains(&root) {
-            root = (-half_b + sqrtd) / a;
-            if !valid_range.contains(&root) {
-                return None;
-            }
-        }
+        let cos_theta = 1.0 + random() * (cos_theta_max - 1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random();
+        let axis = (self.center - origin).normalized();
+        let (tangent, bitangent) = axis.orthonormal_basis();
+
+        Some(cos_theta * axis + sin_theta * phi.cos() * tangent + sin_theta * phi.sin() * bitangent)
+    }
+
+    /// Samples a point uniformly distributed on the sphere.
+    fn sample_surface(&self) -> Option<(HitRecord<'_>, f64)> {
+        let normal = Vec3::random_normalized();
+        let point = self.center + self.radius * normal;
+        let ray = Ray::new(point + normal, -normal);
 
-        Some(HitRecord::new(
-            ray,
-            root,
-            (ray.at(root) - self.center) / self.radius,
-            Arc::clone(&self.material),
+        Some((
+            sphere_record(self.center, self.radius, &self.material, &ray, 1.0),
+            self.surface_pdf(point),
         ))
     }
+
+    fn surface_pdf(&self, _point: Point3) -> f64 {
+        1.0 / (4.0 * PI * self.radius * self.radius)
+    }
+
+    fn direction_pdf(&self, origin: Point3, direction: Vec3) -> f64 {
+        let cos_theta_max = match self.cos_theta_max(origin) {
+            Some(cos_theta_max) => cos_theta_max,
+            None => return 1.0 / (4.0 * PI),
+        };
+
+        let ray = Ray::new(origin, direction);
+        match roots(self.center, self.radius, &ray) {
+            Some((_, far)) if far > 0.0 => 1.0 / (2.0 * PI * (1.0 - cos_theta_max)),
+            _ => 0.0,
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::color::Color;
     use crate::material::Lambertian;
-    use crate::vec3::Vec3;
 
     use super::*;
 
@@ -115,4 +249,111 @@ mod test {
         assert_eq!(result.normal, Vec3::new(-1.0, 0.0, 0.0));
         assert!(result.front_face);
     }
+
+    #[test]
+    fn try_hit_sphere_sets_uv_and_shading_frame() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let sphere = Sphere::new(
+            Point3::new(3.0, 0.0, 0.0),
+            1.0,
+            Arc::clone(&material) as Arc<dyn Material + Send + Sync>,
+        );
+        let ray = Ray::new(Point3::new(3.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 1.0));
+
+        let result = sphere
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit sphere");
+
+        assert_eq!(result.uv, (0.75, 0.5));
+        assert_eq!(result.shading_normal, Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(result.tangent, Vec3::new(-1.0, 0.0, 0.0));
+        assert_eq!(result.bitangent, Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn spans_of_sphere_with_origin_inside() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let sphere = Sphere::new(
+            Point3::new(1.0, 0.0, 0.0),
+            2.0,
+            Arc::clone(&material) as Arc<dyn Material + Send + Sync>,
+        );
+        let ray = Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0));
+
+        let spans = sphere.spans(&ray).expect("sphere should support spans");
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].entry.t, -1.0);
+        assert!(spans[0].entry.front_face);
+        assert_eq!(spans[0].exit.t, 3.0);
+        assert!(!spans[0].exit.front_face);
+    }
+
+    #[test]
+    fn sampled_directions_are_in_the_cone_toward_the_sphere() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let sphere = Sphere::new(
+            Point3::new(3.0, 0.0, 0.0),
+            1.0,
+            Arc::clone(&material) as Arc<dyn Material + Send + Sync>,
+        );
+        let origin = Point3::new(1.0, 0.0, 0.0);
+        // The sphere covers the cone of half angle 30°.
+        let solid_angle = 2.0 * PI * (1.0 - 0.75_f64.sqrt());
+
+        for _ in 0..100 {
+            let direction = sphere.sample_direction(origin).unwrap();
+            let ray = Ray::new(origin, direction);
+
+            assert!(sphere.try_hit(&ray, 0.0..=f64::INFINITY).is_some());
+            assert!((sphere.direction_pdf(origin, direction) - 1.0 / solid_angle).abs() < 1e-9);
+        }
+        assert_eq!(sphere.direction_pdf(origin, Vec3::new(-1.0, 0.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn sampled_points_are_on_the_sphere() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let sphere = Sphere::new(
+            Point3::new(3.0, 0.0, 0.0),
+            2.0,
+            Arc::clone(&material) as Arc<dyn Material + Send + Sync>,
+        );
+
+        for _ in 0..100 {
+            let (record, pdf) = sphere.sample_surface().unwrap();
+            let radius = record.intersection_point - Point3::new(3.0, 0.0, 0.0);
+
+            assert!((radius.length() - 2.0).abs() < 1e-12);
+            assert!((record.normal - radius / 2.0).length() < 1e-12);
+            assert!(record.front_face);
+            assert_eq!(pdf, 1.0 / (16.0 * PI));
+        }
+    }
+
+    #[test]
+    fn uv_works() {
+        assert_eq!(uv(&Point3::new(-1.0, 0.0, 0.0)), (0.0, 0.5));
+        assert_eq!(uv(&Point3::new(0.0, 1.0, 0.0)), (0.5, 1.0));
+        assert_eq!(uv(&Point3::new(1.0, 0.0, 0.0)), (0.5, 0.5));
+        assert_eq!(uv(&Point3::new(0.0, 0.0, 1.0)), (0.25, 0.5));
+    }
+
+    #[test]
+    fn bounding_box_encloses_sphere() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let sphere = Sphere::new(
+            Point3::new(3.0, 0.0, 0.0),
+            1.0,
+            Arc::clone(&material) as Arc<dyn Material + Send + Sync>,
+        );
+
+        assert_eq!(
+            sphere.bounding_box(),
+            Some(Aabb::new(
+                Point3::new(2.0, -1.0, -1.0),
+                Point3::new(4.0, 1.0, 1.0)
+            ))
+        );
+    }
 }
@@ -0,0 +1,260 @@
+use std::{f64::consts::PI, ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    polynomial::solve_quartic,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{disk::disk_extent, Aabb, HitRecord, Hittable, Span};
+
+/// A torus, i.e. the surface swept by a circle of radius `minor_radius`
+/// revolving around an axis at a distance `major_radius`.
+pub struct Torus {
+    center: Point3,
+    /// The unit vector along the axis of revolution.
+    axis: Vec3,
+    major_radius: f64,
+    minor_radius: f64,
+    /// The unit vectors spanning the plane of the torus with `axis`.
+    basis: (Vec3, Vec3),
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Torus {
+    /// Constructs a new `Torus` centered on `center`, revolving around `axis`.
+    pub fn new(
+        center: Point3,
+        axis: Vec3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Torus {
+        let axis = axis.normalized();
+
+        Torus {
+            center,
+            axis,
+            major_radius,
+            minor_radius,
+            basis: axis.orthonormal_basis(),
+            material,
+        }
+    }
+
+    /// Returns `v` in the local space of the torus, where the torus is centered
+    /// on the origin and revolves around the Y axis.
+    fn to_local(&self, v: Vec3) -> Vec3 {
+        Vec3::new(
+            v.dot(&self.basis.0),
+            v.dot(&self.axis),
+            v.dot(&self.basis.1),
+        )
+    }
+
+    /// Returns the local space vector `v` in world space.
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        v.x() * self.basis.0 + v.y() * self.axis + v.z() * self.basis.1
+    }
+
+    /// Returns the distances along the whole line of the ray where it crosses
+    /// the surface of the torus, in increasing order.
+    ///
+    /// In local space, a point `p` is on the torus when
+    /// `(|p|² - R² - r²)² = 4R²(r² - p.y²)`. Substituting the ray equation
+    /// gives a quartic in `t`.
+    /// See [Ray Tracing Gems II](https://link.springer.com/book/10.1007/978-1-4842-7185-8),
+    /// chapter 6.
+    fn crossings(&self, ray: &Ray) -> Vec<f64> {
+        let (major, minor) = (self.major_radius, self.minor_radius);
+
+        // The quartic is solved for a unit direction, so `t` has to be scaled
+        // back afterwards.
+        let direction_length = ray.direction().length();
+        let d = self.to_local(ray.direction() / direction_length);
+        let o = self.to_local(ray.origin() - self.center);
+
+        // Move the origin next to the torus so the coefficients stay small:
+        // the torus is inside a sphere of radius `R + r` around its center.
+        let shift = -o.dot(&d) - (major + minor);
+        let o = o + shift * d;
+
+        let f = o.dot(&d);
+        let e = o.length_squared() - major * major - minor * minor;
+        let four_major_squared = 4.0 * major * major;
+
+        solve_quartic(
+            1.0,
+            4.0 * f,
+            2.0 * e + 4.0 * f * f + four_major_squared * d.y() * d.y(),
+            4.0 * f * e + 2.0 * four_major_squared * o.y() * d.y(),
+            e * e - four_major_squared * (minor * minor - o.y() * o.y()),
+        )
+        .into_iter()
+        .map(|root| (root + shift) / direction_length)
+        .collect()
+    }
+
+    /// Constructs the `HitRecord` of a ray hitting the torus at `t`.
+    fn hit_record(&self, ray: &Ray, t: f64) -> HitRecord<'_> {
+        let p = self.to_local(ray.at(t) - self.center);
+        // The normal goes from the center of the tube to the point.
+        let radial = Vec3::new(p.x(), 0.0, p.z());
+        let radial_length = radial.length();
+        let tube_center = self.major_radius * radial / radial_length;
+        let local_normal = (p - tube_center).normalized();
+
+        let outward_normal = self.to_world(local_normal);
+        let mut record = HitRecord::new(ray, t, outward_normal, Arc::clone(&self.material));
+        // `u` goes around the axis and `v` around the tube.
+        record.uv = (
+            ((-p.z()).atan2(p.x()) + PI) / (2.0 * PI),
+            (p.y().atan2(radial_length - self.major_radius) + PI) / (2.0 * PI),
+        );
+        record.set_shading_frame(outward_normal, self.to_world(Vec3::new(p.z(), 0.0, -p.x())));
+
+        record
+    }
+}
+
+impl Hittable for Torus {
+    /// Tries to hit a torus with a ray. The intersection point must be in the
+    /// `valid_range`.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        self.crossings(ray)
+            .into_iter()
+            .find(|t| valid_range.contains(t))
+            .map(|t| self.hit_record(ray, t))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let minor = Vec3::new(self.minor_radius, self.minor_radius, self.minor_radius);
+        let extent = disk_extent(self.axis, self.major_radius) + minor;
+
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+
+    fn spans(&self, ray: &Ray) -> Option<Vec<Span<'_>>> {
+        // Crossings alternate between entries and exits.
+        let spans = self
+            .crossings(ray)
+            .chunks_exact(2)
+            .map(|crossings| Span {
+                entry: self.hit_record(ray, crossings[0]),
+                exit: self.hit_record(ray, crossings[1]),
+            })
+            .collect();
+
+        Some(spans)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, material::Lambertian};
+
+    use super::*;
+
+    fn torus() -> Torus {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Torus::new(
+            Point3::new(0.0, 0.0, -5.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            2.0,
+            0.5,
+            material as Arc<dyn Material + Send + Sync>,
+        )
+    }
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn try_hit_torus_with_not_hitting_ray() {
+        let torus = torus();
+        // Goes through the hole.
+        let ray = Ray::new(Point3::zero(), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(torus.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_torus_with_hitting_ray_not_in_range() {
+        let torus = torus();
+        let ray = Ray::new(Point3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(torus.try_hit(&ray, 0.0..=4.0).is_none());
+    }
+
+    #[test]
+    fn try_hit_torus_with_hitting_ray_in_range() {
+        let torus = torus();
+        let ray = Ray::new(Point3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -2.0));
+
+        let result = torus
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit torus");
+
+        assert_near(result.intersection_point, Point3::new(2.0, 0.0, -4.5));
+        assert!((result.t - 2.25).abs() < 1e-9);
+        assert_near(result.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(result.front_face);
+    }
+
+    #[test]
+    fn try_hit_torus_across_the_hole() {
+        let torus = torus();
+        let ray = Ray::new(Point3::new(-10.0, 0.0, -5.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let result = torus
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit torus");
+        assert_near(result.intersection_point, Point3::new(-2.5, 0.0, -5.0));
+        assert_near(result.normal, Vec3::new(-1.0, 0.0, 0.0));
+
+        // The second hit is the inner side of the tube, seen from the inside.
+        let result = torus
+            .try_hit(&ray, 7.6..=f64::INFINITY)
+            .expect("ray should hit torus");
+        assert_near(result.intersection_point, Point3::new(-1.5, 0.0, -5.0));
+        assert_near(result.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(!result.front_face);
+
+        // Then the other side of the hole.
+        let result = torus
+            .try_hit(&ray, 8.6..=f64::INFINITY)
+            .expect("ray should hit torus");
+        assert_near(result.intersection_point, Point3::new(1.5, 0.0, -5.0));
+        assert_near(result.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(result.front_face);
+    }
+
+    #[test]
+    fn spans_of_torus_across_the_hole() {
+        let torus = torus();
+        let ray = Ray::new(Point3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 1.0, 0.0));
+
+        let spans = torus.spans(&ray).expect("torus should support spans");
+
+        assert_eq!(spans.len(), 2);
+        assert!((spans[0].entry.t + 2.5).abs() < 1e-9);
+        assert!((spans[0].exit.t + 1.5).abs() < 1e-9);
+        assert!((spans[1].entry.t - 1.5).abs() < 1e-9);
+        assert!((spans[1].exit.t - 2.5).abs() < 1e-9);
+        assert!(spans[1].entry.front_face);
+        assert!(!spans[1].exit.front_face);
+    }
+
+    #[test]
+    fn bounding_box_encloses_torus() {
+        assert_eq!(
+            torus().bounding_box(),
+            Some(Aabb::new(
+                Point3::new(-2.5, -2.5, -5.5),
+                Point3::new(2.5, 2.5, -4.5)
+            ))
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, HitRecord, Hittable};
+
+/// A triangle.
+///
+/// The outward normal points toward the side from which the vertices are seen
+/// in counter-clockwise order.
+pub struct Triangle {
+    vertices: [Point3; 3],
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Triangle {
+    /// Constructs a new `Triangle` from its three vertices.
+    pub fn new(
+        a: Point3,
+        b: Point3,
+        c: Point3,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Triangle {
+        Triangle {
+            vertices: [a, b, c],
+            material,
+        }
+    }
+}
+
+/// Returns the axis along which `v` has its largest absolute component.
+fn max_dimension(v: Vec3) -> usize {
+    let (x, y, z) = (v.x().abs(), v.y().abs(), v.z().abs());
+    if x > y && x > z {
+        0
+    } else if y > z {
+        1
+    } else {
+        2
+    }
+}
+
+/// Returns `v` with its components reordered as `(v[x], v[y], v[z])`.
+fn permute(v: Vec3, x: usize, y: usize, z: usize) -> Vec3 {
+    Vec3::new(v[x], v[y], v[z])
+}
+
+/// Intersects a ray with the triangle made of the `vertices`. The intersection
+/// point must be in the `valid_range`.
+/// Returns the distance from the ray origin to the point of intersection and
+/// the barycentric coordinates of the point, or [`None`] if the ray misses the
+/// triangle.
+///
+/// The test is watertight: a ray going through an edge or a vertex shared by
+/// several triangles always hits at least one of them.
+/// See [Watertight Ray/Triangle Intersection](https://jcgt.org/published/0002/01/05/),
+/// Woop, Benthin and Wald, 2013.
+pub(super) fn intersect(
+    vertices: &[Point3; 3],
+    ray: &Ray,
+    valid_range: RangeInclusive<f64>,
+) -> Option<(f64, [f64; 3])> {
+    let direction = ray.direction();
+
+    // Transform the vertices to a space where the ray starts at the origin and
+    // goes along the Z axis, so the test becomes a 2D one on X and Y.
+    let kz = max_dimension(direction);
+    let kx = (kz + 1) % 3;
+    let ky = (kx + 1) % 3;
+    let d = permute(direction, kx, ky, kz);
+
+    let shear_x = -d.x() / d.z();
+    let shear_y = -d.y() / d.z();
+    let shear_z = 1.0 / d.z();
+
+    let [p0, p1, p2] = vertices.map(|vertex| {
+        let p = permute(vertex - ray.origin(), kx, ky, kz);
+        Vec3::new(p.x() + shear_x * p.z(), p.y() + shear_y * p.z(), p.z())
+    });
+
+    // Edge functions: the origin is inside the triangle when they all have the
+    // same sign.
+    let e0 = p1.x() * p2.y() - p1.y() * p2.x();
+    let e1 = p2.x() * p0.y() - p2.y() * p0.x();
+    let e2 = p0.x() * p1.y() - p0.y() * p1.x();
+
+    if (e0 < 0.0 || e1 < 0.0 || e2 < 0.0) && (e0 > 0.0 || e1 > 0.0 || e2 > 0.0) {
+        return None;
+    }
+
+    let determinant = e0 + e1 + e2;
+    if determinant == 0.0 {
+        return None;
+    }
+
+    let t_scaled = e0 * p0.z() * shear_z + e1 * p1.z() * shear_z + e2 * p2.z() * shear_z;
+    let t = t_scaled / determinant;
+    if !valid_range.contains(&t) {
+        return None;
+    }
+
+    Some((t, [e0 / determinant, e1 / determinant, e2 / determinant]))
+}
+
+/// Constructs the `HitRecord` of a ray hitting the triangle made of the
+/// `vertices` at `t`, whose barycentric coordinates are `barycentric`.
+pub(super) fn hit_record<'a>(
+    vertices: &[Point3; 3],
+    ray: &Ray,
+    t: f64,
+    barycentric: [f64; 3],
+    material: Arc<dyn Material + Sync + Send + 'a>,
+) -> HitRecord<'a> {
+    let [a, b, c] = *vertices;
+    let normal = (b - a).cross(&(c - a)).normalized();
+
+    let mut record = HitRecord::new(ray, t, normal, material);
+    // Interpolating the vertices is more precise than moving along the ray.
+    record.intersection_point = barycentric[0] * a + barycentric[1] * b + barycentric[2] * c;
+    record.barycentric = Some(barycentric);
+    record.uv = (barycentric[1], barycentric[2]);
+    record.set_shading_frame(normal, b - a);
+
+    record
+}
+
+impl Hittable for Triangle {
+    /// Tries to hit a triangle with a ray. The intersection point must be in
+    /// the `valid_range`.
+    ///
+    /// See [`intersect`] for the intersection test.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let (t, barycentric) = intersect(&self.vertices, ray, valid_range)?;
+
+        Some(hit_record(
+            &self.vertices,
+            ray,
+            t,
+            barycentric,
+            Arc::clone(&self.material),
+        ))
+    }
+
+    fn occluded(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> bool {
+        intersect(&self.vertices, ray, valid_range).is_some()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let [a, b, c] = self.vertices;
+        Some(Aabb::new(a, b).including(c))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{color::Color, material::Lambertian};
+
+    use super::*;
+
+    fn triangle(a: Point3, b: Point3, c: Point3) -> Triangle {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        Triangle::new(a, b, c, material as Arc<dyn Material + Send + Sync>)
+    }
+
+    #[test]
+    fn try_hit_triangle_with_not_hitting_ray() {
+        let triangle = triangle(
+            Point3::new(0.0, 0.0, -1.0),
+            Point3::new(1.0, 0.0, -1.0),
+            Point3::new(0.0, 1.0, -1.0),
+        );
+        let ray = Ray::new(Point3::new(1.0, 1.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(triangle.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_triangle_with_parallel_ray() {
+        let triangle = triangle(
+            Point3::new(0.0, 0.0, -1.0),
+            Point3::new(1.0, 0.0, -1.0),
+            Point3::new(0.0, 1.0, -1.0),
+        );
+        let ray = Ray::new(Point3::new(-1.0, 0.25, -1.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(triangle.try_hit(&ray, 0.0..=f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn try_hit_triangle_with_hitting_ray_not_in_range() {
+        let triangle = triangle(
+            Point3::new(0.0, 0.0, -2.0),
+            Point3::new(1.0, 0.0, -2.0),
+            Point3::new(0.0, 1.0, -2.0),
+        );
+        let ray = Ray::new(Point3::new(0.25, 0.25, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(triangle.try_hit(&ray, 0.0..=1.0).is_none());
+    }
+
+    #[test]
+    fn try_hit_triangle_with_hitting_ray_in_range() {
+        let triangle = triangle(
+            Point3::new(0.0, 0.0, -2.0),
+            Point3::new(1.0, 0.0, -2.0),
+            Point3::new(0.0, 1.0, -2.0),
+        );
+        let ray = Ray::new(Point3::new(0.25, 0.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        let result = triangle
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit triangle");
+
+        assert_eq!(result.intersection_point, Point3::new(0.25, 0.5, -2.0));
+        assert_eq!(result.t, 2.0);
+        assert_eq!(result.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(result.front_face);
+        assert_eq!(result.barycentric, Some([0.25, 0.25, 0.5]));
+        assert_eq!(result.uv, (0.25, 0.5));
+        assert_eq!(result.shading_normal, result.normal);
+        assert_eq!(result.tangent, Vec3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn try_hit_triangle_from_behind() {
+        let triangle = triangle(
+            Point3::new(0.0, 0.0, -2.0),
+            Point3::new(0.0, 1.0, -2.0),
+            Point3::new(1.0, 0.0, -2.0),
+        );
+        let ray = Ray::new(Point3::new(0.25, 0.25, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        let result = triangle
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit triangle");
+
+        assert_eq!(result.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(result.shading_normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(!result.front_face);
+    }
+
+    #[test]
+    fn try_hit_triangles_sharing_an_edge_is_watertight() {
+        let a = Point3::new(-1.0, -1.0, -2.0);
+        let b = Point3::new(1.0, -1.0, -2.3);
+        let c = Point3::new(-1.0, 1.0, -1.7);
+        let d = Point3::new(1.0, 1.0, -2.0);
+        let first = triangle(a, b, c);
+        let second = triangle(b, d, c);
+
+        // Rays aimed at points of the shared edge between `b` and `c`.
+        for i in 0..=100 {
+            let target = b + (i as f64 / 100.0) * (c - b);
+            let ray = Ray::new(
+                Point3::new(0.1, 0.2, 0.0),
+                target - Point3::new(0.1, 0.2, 0.0),
+            );
+
+            assert!(
+                first.try_hit(&ray, 0.0..=f64::INFINITY).is_some()
+                    || second.try_hit(&ray, 0.0..=f64::INFINITY).is_some()
+            );
+        }
+    }
+
+    #[test]
+    fn bounding_box_encloses_triangle() {
+        let triangle = triangle(
+            Point3::new(0.0, 2.0, -1.0),
+            Point3::new(1.0, 0.0, -2.0),
+            Point3::new(-1.0, 1.0, -3.0),
+        );
+
+        assert_eq!(
+            triangle.bounding_box(),
+            Some(Aabb::new(
+                Point3::new(-1.0, 0.0, -3.0),
+                Point3::new(1.0, 2.0, -1.0)
+            ))
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{
+    material::Material,
+    mesh::{Mesh, MeshGroup, MeshVertex},
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{triangle::Triangle, Aabb, Bvh, HitRecord, Hittable};
+
+/// A triangle mesh.
+///
+/// The triangles of the [`Mesh`] are stored in a [`Bvh`], so meshes made of
+/// many triangles can be hit efficiently.
+pub struct TriangleMesh {
+    triangles: Bvh,
+}
+
+/// A triangle of a [`TriangleMesh`], referencing the attributes of its vertices
+/// in the shared [`Mesh`].
+struct MeshTriangle {
+    triangle: Triangle,
+    mesh: Arc<Mesh>,
+    index: usize,
+}
+
+impl TriangleMesh {
+    /// Constructs a new `TriangleMesh` whose triangles are all made of the same
+    /// `material`.
+    pub fn new(mesh: Mesh, material: Arc<dyn Material + Sync + Send>) -> TriangleMesh {
+        TriangleMesh::with_materials(mesh, |_| Arc::clone(&material))
+    }
+
+    /// Constructs a new `TriangleMesh` whose triangles are made of the material
+    /// returned by `material_of` for their group.
+    pub fn with_materials<F>(mesh: Mesh, mut material_of: F) -> TriangleMesh
+    where
+        F: FnMut(&MeshGroup) -> Arc<dyn Material + Sync + Send>,
+    {
+        let mesh = Arc::new(mesh);
+        let mut triangles: Vec<Arc<dyn Hittable + Sync + Send>> =
+            Vec::with_capacity(mesh.triangles.len());
+
+        for group in &mesh.groups {
+            let material = material_of(group);
+            for index in group.triangles.clone() {
+                let [a, b, c] = mesh.triangles[index].map(|vertex| mesh.positions[vertex.position]);
+                triangles.push(Arc::new(MeshTriangle {
+                    triangle: Triangle::new(a, b, c, Arc::clone(&material)),
+                    mesh: Arc::clone(&mesh),
+                    index,
+                }));
+            }
+        }
+
+        TriangleMesh {
+            triangles: Bvh::new(triangles),
+        }
+    }
+}
+
+impl Hittable for TriangleMesh {
+    /// Tries to hit a triangle of the mesh with a ray. The intersection point
+    /// must be in the `valid_range`.
+    /// Returns a record of the closest hit, or [`None`] if no triangle can be
+    /// hit.
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        self.triangles.try_hit(ray, valid_range)
+    }
+
+    fn occluded(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> bool {
+        self.triangles.occluded(ray, valid_range)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.triangles.bounding_box()
+    }
+}
+
+impl MeshTriangle {
+    /// Returns the positions of the triangle vertices.
+    fn vertices(&self) -> [Point3; 3] {
+        self.mesh.triangles[self.index].map(|vertex| self.mesh.positions[vertex.position])
+    }
+
+    /// Interpolates a vertex attribute at the point of `barycentric`
+    /// coordinates. `attribute` returns the attribute of a vertex, if any.
+    /// Returns [`None`] if a vertex of the triangle has no attribute.
+    fn interpolate<F>(&self, barycentric: [f64; 3], attribute: F) -> Option<Vec3>
+    where
+        F: Fn(&MeshVertex) -> Option<Vec3>,
+    {
+        let [a, b, c] = &self.mesh.triangles[self.index];
+
+        Some(
+            barycentric[0] * attribute(a)?
+                + barycentric[1] * attribute(b)?
+                + barycentric[2] * attribute(c)?,
+        )
+    }
+}
+
+/// Returns the derivative of the position on the triangle made of the
+/// `vertices` with respect to the first texture coordinate, given the `uvs` of
+/// the vertices. Returns [`None`] if the texture coordinates are degenerate.
+fn position_derivative(vertices: &[Point3; 3], uvs: &[(f64, f64); 3]) -> Option<Vec3> {
+    let (du02, dv02) = (uvs[0].0 - uvs[2].0, uvs[0].1 - uvs[2].1);
+    let (du12, dv12) = (uvs[1].0 - uvs[2].0, uvs[1].1 - uvs[2].1);
+    let determinant = du02 * dv12 - dv02 * du12;
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+
+    let dp02 = vertices[0] - vertices[2];
+    let dp12 = vertices[1] - vertices[2];
+
+    Some((dv12 * dp02 - dv02 * dp12) / determinant)
+}
+
+impl Hittable for MeshTriangle {
+    fn try_hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<HitRecord<'_>> {
+        let mut record = self.triangle.try_hit(ray, valid_range)?;
+        let barycentric = record
+            .barycentric
+            .expect("triangle hits have barycentric coordinates");
+        let vertices = self.vertices();
+
+        record.vertex_color = self.interpolate(barycentric, |vertex| {
+            vertex.color.map(|color| self.mesh.colors[color])
+        });
+
+        let [a, b, c] = &self.mesh.triangles[self.index];
+        let uvs = match (a.uv, b.uv, c.uv) {
+            (Some(a), Some(b), Some(c)) => Some([a, b, c].map(|uv| self.mesh.uvs[uv])),
+            _ => None,
+        };
+        if let Some(uvs) = uvs {
+            record.uv = (
+                barycentric[0] * uvs[0].0 + barycentric[1] * uvs[1].0 + barycentric[2] * uvs[2].0,
+                barycentric[0] * uvs[0].1 + barycentric[1] * uvs[1].1 + barycentric[2] * uvs[2].1,
+            );
+        }
+
+        let shading_normal = self.interpolate(barycentric, |vertex| {
+            vertex.normal.map(|normal| self.mesh.normals[normal])
+        });
+        let tangent = uvs.and_then(|uvs| position_derivative(&vertices, &uvs));
+        if shading_normal.is_some() || tangent.is_some() {
+            let [p0, p1, _] = vertices;
+            let geometric_normal = if record.front_face {
+                record.normal
+            } else {
+                -record.normal
+            };
+
+            record.set_shading_frame(
+                shading_normal
+                    .filter(|normal| !normal.is_near_zero())
+                    .unwrap_or(geometric_normal),
+                tangent.unwrap_or(p1 - p0),
+            );
+        }
+
+        Some(record)
+    }
+
+    fn occluded(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> bool {
+        self.triangle.occluded(ray, valid_range)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.triangle.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::{
+        color::Color,
+        material::{Lambertian, Metal},
+    };
+
+    use super::*;
+
+    /// A unit cube centered on the origin, whose faces are split in two
+    /// groups.
+    fn cube() -> Mesh {
+        Mesh::read_obj(
+            "v -0.5 -0.5 -0.5\nv 0.5 -0.5 -0.5\nv 0.5 0.5 -0.5\nv -0.5 0.5 -0.5\n\
+             v -0.5 -0.5 0.5\nv 0.5 -0.5 0.5\nv 0.5 0.5 0.5\nv -0.5 0.5 0.5\n\
+             g sides\n\
+             f 1 4 3 2\nf 5 6 7 8\nf 1 5 8 4\nf 2 3 7 6\n\
+             g caps\n\
+             f 1 2 6 5\nf 4 8 7 3\n"
+                .as_bytes(),
+            Path::new("cube.obj"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn try_hit_mesh_from_outside() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let mesh = TriangleMesh::new(cube(), material);
+
+        for direction in &[
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ] {
+            let ray = Ray::new(-2.0 * *direction, *direction);
+            let result = mesh
+                .try_hit(&ray, 0.0..=f64::INFINITY)
+                .expect("ray should hit mesh");
+
+            assert_eq!(result.t, 1.5);
+            assert_eq!(result.normal, -*direction);
+            assert!(result.front_face);
+        }
+    }
+
+    #[test]
+    fn try_hit_mesh_from_inside() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let mesh = TriangleMesh::new(cube(), material);
+        let ray = Ray::new(Point3::new(0.1, 0.2, 0.0), Vec3::new(0.0, 0.0, 1.0));
+
+        let result = mesh
+            .try_hit(&ray, 0.0..=f64::INFINITY)
+            .expect("ray should hit mesh");
+
+        assert_eq!(result.t, 0.5);
+        assert!(!result.front_face);
+    }
+
+    #[test]
+    fn with_materials_uses_group_materials() {
+        let sides = Arc::new(Lambertian::new(Color::zero())) as Arc<dyn Material + Sync + Send>;
+        let caps = Arc::new(Metal::new(Color::zero(), 0.0)) as Arc<dyn Material + Sync + Send>;
+        let mesh = TriangleMesh::with_materials(cube(), |group| match group.name.as_str() {
+            "caps" => Arc::clone(&caps),
+            _ => Arc::clone(&sides),
+        });
+
+        let ray = Ray::new(Point3::new(0.0, -2.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let result = mesh.try_hit(&ray, 0.0..=f64::INFINITY).unwrap();
+        assert!(Arc::ptr_eq(&result.material, &caps));
+
+        let ray = Ray::new(Point3::new(-2.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let result = mesh.try_hit(&ray, 0.0..=f64::INFINITY).unwrap();
+        assert!(Arc::ptr_eq(&result.material, &sides));
+    }
+
+    #[test]
+    fn try_hit_mesh_interpolates_vertex_colors() {
+        let mut mesh = Mesh::read_obj(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n".as_bytes(),
+            Path::new("triangle.obj"),
+        )
+        .unwrap();
+        mesh.colors = vec![
+            Color::new(1.0, 0.0, 0.0),
+            Color::new(0.0, 1.0, 0.0),
+            Color::new(0.0, 0.0, 1.0),
+        ];
+        for vertex in mesh.triangles[0].iter_mut() {
+            vertex.color = Some(vertex.position);
+        }
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let mesh = TriangleMesh::new(mesh, material);
+
+        let ray = Ray::new(Point3::new(0.25, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let result = mesh.try_hit(&ray, 0.0..=f64::INFINITY).unwrap();
+
+        assert_eq!(result.vertex_color, Some(Color::new(0.25, 0.25, 0.5)));
+    }
+
+    #[test]
+    fn try_hit_mesh_interpolates_normals_and_uvs() {
+        let mesh = Mesh::read_obj(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+             vn -1 0 1\nvn 1 0 1\n\
+             vt 0 1\nvt 0 0\nvt 1 1\n\
+             f 1/1/1 2/2/2 3/3/1\n"
+                .as_bytes(),
+            Path::new("triangle.obj"),
+        )
+        .unwrap();
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let mesh = TriangleMesh::new(mesh, material);
+
+        let ray = Ray::new(Point3::new(0.75, 0.125, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let result = mesh.try_hit(&ray, 0.0..=f64::INFINITY).unwrap();
+
+        assert_eq!(result.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(result.uv, (0.125, 0.25));
+        assert!(result.shading_normal.x() > 0.0);
+        assert!((result.shading_normal.length() - 1.0).abs() < 1e-12);
+        // `u` increases along the Y axis.
+        assert!((result.tangent.y() - 1.0).abs() < 1e-12);
+        assert!(result.tangent.dot(&result.shading_normal).abs() < 1e-12);
+
+        // From below, the shading normal is flipped along with the normal.
+        let ray = Ray::new(Point3::new(0.75, 0.125, -1.0), Vec3::new(0.0, 0.0, 1.0));
+        let result = mesh.try_hit(&ray, 0.0..=f64::INFINITY).unwrap();
+
+        assert!(!result.front_face);
+        assert!(result.shading_normal.z() < 0.0);
+        assert!(result.shading_normal.x() < 0.0);
+    }
+
+    #[test]
+    fn try_hit_mesh_without_vertex_colors() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let mesh = TriangleMesh::new(cube(), material);
+        let ray = Ray::new(Point3::new(-2.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let result = mesh.try_hit(&ray, 0.0..=f64::INFINITY).unwrap();
+
+        assert!(result.vertex_color.is_none());
+    }
+
+    #[test]
+    fn bounding_box_encloses_mesh() {
+        let material = Arc::new(Lambertian::new(Color::zero()));
+        let mesh = TriangleMesh::new(cube(), material);
+
+        assert_eq!(
+            mesh.bounding_box(),
+            Some(Aabb::new(
+                Point3::new(-0.5, -0.5, -0.5),
+                Point3::new(0.5, 0.5, 0.5)
+            ))
+        );
+    }
+}
@@ -5,23 +5,25 @@ use std::{
 
 use progressing::{mapping::Bar as MappingBar, Baring};
 
-use crate::{camera::Camera, color::Color, hittable::Hittable, perf::ThreadPool, random};
+use crate::{
+    camera::Camera, color::Color, integrator::Integrator, perf::ThreadPool, random, scene::Scene,
+};
 
-/// A ray-traced image.
+/// A ray-traced image, whose pixels are computed by an [`Integrator`].
 ///
 /// In order to render the image use [`Image::render`] and then [`Image::write`]
 /// to write it to a writer.
-pub struct Image {
+pub struct Image<I: ?Sized> {
     camera: Arc<Camera>,
     image_width: usize,
     image_height: usize,
     samples_per_pixel: usize,
-    max_depth: usize,
-    world: Arc<dyn Hittable + Send + Sync>,
+    integrator: Arc<I>,
+    scene: Arc<Scene>,
     pixels: Option<Vec<Color>>,
 }
 
-impl Image {
+impl<I: Integrator + Send + Sync + ?Sized + 'static> Image<I> {
     /// Constructs a new [`Image`].
     ///
     /// [`Image::image_height`] is constructed from the `image_width` and
@@ -31,17 +33,17 @@ impl Image {
         aspect_ratio: f64,
         image_width: usize,
         samples_per_pixel: usize,
-        max_depth: usize,
-        world: Arc<dyn Hittable + Send + Sync>,
-    ) -> Image {
-        let image_height = (image_width as f64 / aspect_ratio as f64) as usize;
+        integrator: Arc<I>,
+        scene: Arc<Scene>,
+    ) -> Image<I> {
+        let image_height = (image_width as f64 / aspect_ratio) as usize;
         Image {
             camera: Arc::new(camera),
             image_width,
             image_height,
             samples_per_pixel,
-            max_depth,
-            world,
+            integrator,
+            scene,
             pixels: None,
         }
     }
@@ -74,17 +76,18 @@ impl Image {
 
         for j in (0..self.image_height).rev() {
             let progress_bar = Arc::clone(&progress_bar);
-            let world = Arc::clone(&self.world);
+            let scene = Arc::clone(&self.scene);
             let pixels = Arc::clone(&pixels);
             let camera = Arc::clone(&self.camera);
+            let integrator = Arc::clone(&self.integrator);
 
             let image_width = self.image_width;
             let image_height = self.image_height;
-            let max_depth = self.max_depth;
             let samples_per_pixel = self.samples_per_pixel;
 
             pool.execute(move || {
                 let mut chunk = Vec::with_capacity(image_width);
+                let mut splats = Vec::new();
                 for i in 0..image_width {
                     let mut pixel_color = Color::zero();
 
@@ -92,7 +95,14 @@ impl Image {
                         let u = (i as f64 + random()) / (image_width - 1) as f64;
                         let v = (j as f64 + random()) / (image_height - 1) as f64;
                         let r = camera.ray_to(u, v);
-                        pixel_color += r.color(&world, max_depth);
+                        let (color, ray_splats) = integrator.color_with_splats(&r, &scene);
+                        pixel_color += color;
+                        splats.extend(ray_splats.into_iter().filter_map(
+                            |(screen_point, color)| {
+                                pixel_index(screen_point, image_width, image_height)
+                                    .map(|index| (index, color))
+                            },
+                        ));
                     }
 
                     chunk.push(pixel_color);
@@ -100,8 +110,15 @@ impl Image {
 
                 let mut image = pixels.lock().unwrap();
                 chunk.into_iter().enumerate().for_each(|(index, pixel)| {
-                    image[((image_height - j - 1) * image_width + index)] = pixel;
+                    image[(image_height - j - 1) * image_width + index] += pixel;
                 });
+                // The splats are computed for a screen covering [0, 1] × [0, 1],
+                // while the rays are sent through a slightly larger one.
+                let scale = ((image_width - 1) * (image_height - 1)) as f64
+                    / (image_width * image_height) as f64;
+                for (index, color) in splats {
+                    image[index] += scale * color;
+                }
 
                 let mut progress_bar = progress_bar.lock().unwrap();
                 progress_bar.add(image_width);
@@ -143,3 +160,17 @@ impl Image {
         std::io::Result::Ok(())
     }
 }
+
+/// Returns the index of the pixel of an image of `image_width` by
+/// `image_height` pixels containing the `screen_point`, whose coordinates are
+/// those given to [`Camera::ray_to`], or [`None`] if it's outside the image.
+fn pixel_index(screen_point: (f64, f64), image_width: usize, image_height: usize) -> Option<usize> {
+    let (s, t) = screen_point;
+    let i = (s * (image_width - 1) as f64).floor();
+    let j = (t * (image_height - 1) as f64).floor();
+    if i < 0.0 || j < 0.0 || i >= image_width as f64 || j >= image_height as f64 {
+        return None;
+    }
+
+    Some((image_height - j as usize - 1) * image_width + i as usize)
+}
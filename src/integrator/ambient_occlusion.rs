@@ -0,0 +1,111 @@
+use crate::{color::Color, ray::Ray, scene::Scene, vec3::Vec3};
+
+use super::Integrator;
+
+/// An integrator showing how much of the surroundings of the surfaces is open.
+///
+/// From the surface first hit by a ray, a ray is sent toward a random
+/// direction above the surface, cosine-weighted as the light reaching a
+/// diffuse surface. The surface is white if nothing is hit closer than the
+/// radius, black otherwise, so that on average creases and corners are
+/// darker. Rays hitting nothing are white.
+pub struct AmbientOcclusion {
+    radius: f64,
+}
+
+impl AmbientOcclusion {
+    /// Constructs a new `AmbientOcclusion`, ignoring the objects farther than
+    /// `radius` from the surfaces.
+    pub fn new(radius: f64) -> AmbientOcclusion {
+        AmbientOcclusion { radius }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn color(&self, ray: &Ray, scene: &Scene) -> Color {
+        let hit = match scene.world.try_hit(ray, 0.001..=f64::INFINITY) {
+            Some(hit) => hit,
+            None => return Color::new(1.0, 1.0, 1.0),
+        };
+
+        let mut direction = hit.shading_normal + Vec3::random_normalized();
+        if direction.is_near_zero() {
+            direction = hit.shading_normal;
+        }
+        let direction = direction.normalized();
+        if direction.dot(&hit.normal) <= 0.0 {
+            return Color::zero();
+        }
+
+        let occlusion_ray = Ray::with_time(hit.intersection_point, direction, ray.time());
+        if scene.world.occluded(&occlusion_ray, 0.001..=self.radius) {
+            Color::zero()
+        } else {
+            Color::new(1.0, 1.0, 1.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        background::SolidBackground,
+        hittable::{HittableCollection, Plane},
+        material::Lambertian,
+        vec3::Point3,
+    };
+
+    use super::*;
+
+    /// Returns a scene made of a floor under a ceiling at a height of 1.
+    fn between_planes() -> Scene {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut world = HittableCollection::new();
+        world.add(Arc::new(Plane::new(
+            Point3::zero(),
+            Vec3::new(0.0, 1.0, 0.0),
+            Arc::clone(&material) as Arc<dyn crate::material::Material + Sync + Send>,
+        )));
+        world.add(Arc::new(Plane::new(
+            Point3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            material,
+        )));
+
+        Scene::new(
+            Arc::new(world),
+            Arc::new(SolidBackground::new(Color::zero())),
+        )
+    }
+
+    #[test]
+    fn surfaces_farther_than_the_radius_do_not_occlude() {
+        let scene = between_planes();
+        let ray = Ray::new(Point3::new(0.0, 0.5, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        for _ in 0..100 {
+            let color = AmbientOcclusion::new(0.9).color(&ray, &scene);
+
+            assert_eq!(color, Color::new(1.0, 1.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn occlusion_by_a_ceiling() {
+        let scene = between_planes();
+        let ray = Ray::new(Point3::new(0.0, 0.5, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let samples = 10_000;
+
+        let color = (0..samples)
+            .map(|_| AmbientOcclusion::new(2.0).color(&ray, &scene).x())
+            .sum::<f64>()
+            / samples as f64;
+
+        // The ceiling is closer than the radius R along the directions at an
+        // angle θ from the normal where cos θ > 1 / R, which cosine-weighted
+        // directions take with a probability 1 - 1 / R².
+        assert!((color - 0.25).abs() < 0.02);
+    }
+}
@@ -0,0 +1,680 @@
+use std::f64::consts::PI;
+
+use crate::{
+    camera::Camera,
+    color::Color,
+    hittable::HitRecord,
+    random::random,
+    ray::Ray,
+    scene::Scene,
+    vec3::{Point3, Vec3},
+};
+
+use super::{direct_lighting, power_heuristic, Integrator, Splat};
+
+/// A bidirectional path tracer.
+///
+/// For every ray from the camera, a path is traced from the camera and
+/// another from a random point of the scene emitters. Every vertex of the
+/// camera subpath is then connected to every vertex of the light subpath,
+/// and the light subpath to the camera itself, the paths made by these
+/// connections being combined with multiple importance sampling. This finds
+/// light which a path from the camera hardly finds, e.g. coming from an
+/// emitter behind glass.
+///
+/// The light of the scene lights and the background, which light subpaths
+/// don't start from, is gathered along the camera subpath as with a
+/// [`PathTracer`](super::PathTracer).
+///
+/// See [Bidirectional Path Tracing](https://www.pbr-book.org/3ed-2018/Light_Transport_III_Bidirectional_Methods/Bidirectional_Path_Tracing)
+/// in Physically Based Rendering.
+pub struct BidirectionalPathTracer {
+    max_depth: usize,
+    roulette_depth: usize,
+    camera: Camera,
+}
+
+/// What a [`Vertex`] of a path is.
+enum VertexKind<'a> {
+    /// A point of the camera lens.
+    Camera,
+    /// A point of an emitter, from which a light subpath starts.
+    Light(HitRecord<'a>),
+    /// A point where the path hit a hittable, on a surface or in a medium.
+    Scattering(HitRecord<'a>),
+}
+
+/// A vertex of a camera or light subpath.
+struct Vertex<'a> {
+    kind: VertexKind<'a>,
+    point: Point3,
+    /// The direction of the ray arriving at the vertex.
+    incoming: Vec3,
+    time: f64,
+    /// The light (for light subpaths) or the importance (for camera subpaths)
+    /// carried by the subpath up to the vertex, divided by the probability
+    /// density of the subpath.
+    beta: Color,
+    /// Whether the subpath was scattered specularly at the vertex, in which
+    /// case the vertex can't be connected to other ones.
+    specular: bool,
+    /// The probability density with respect to area of sampling the vertex
+    /// from the previous one in the subpath.
+    pdf_forward: f64,
+    /// The probability density with respect to area of sampling the vertex
+    /// from the next one, as a subpath traced the other way would.
+    pdf_reverse: f64,
+}
+
+/// A ray escaping the scene at the end of a subpath.
+struct Escape {
+    direction: Vec3,
+    /// The probability density with respect to solid angle of the scattering
+    /// that sent the ray, 0 if it was specular.
+    pdf: f64,
+    beta: Color,
+}
+
+impl<'a> Vertex<'a> {
+    /// Returns the record of the vertex, unless it's on the camera.
+    fn record(&self) -> Option<&HitRecord<'a>> {
+        match &self.kind {
+            VertexKind::Camera => None,
+            VertexKind::Light(record) | VertexKind::Scattering(record) => Some(record),
+        }
+    }
+
+    /// Returns the absolute cosine of the angle between the normal of the
+    /// vertex and `direction`, or 1 if the vertex isn't on a surface.
+    fn cosine(&self, direction: Vec3) -> f64 {
+        match self.record() {
+            Some(record) if !record.in_medium => record.normal.dot(&direction.normalized()).abs(),
+            _ => 1.0,
+        }
+    }
+
+    /// Converts the probability density with respect to solid angle `pdf` of
+    /// going from the vertex toward `next` to a density with respect to area.
+    fn to_area(&self, pdf: f64, next: &Vertex) -> f64 {
+        let direction = next.point - self.point;
+
+        pdf * next.cosine(direction) / direction.length_squared()
+    }
+
+    /// Returns the ray arriving at the vertex from `previous`, or along its
+    /// incoming direction.
+    fn incoming_ray(&self, previous: Option<&Vertex>) -> Ray {
+        let direction = match previous {
+            Some(previous) => self.point - previous.point,
+            None => self.incoming,
+        };
+
+        Ray::with_time(self.point - direction, direction, self.time)
+    }
+
+    /// Returns the probability density with respect to area of the vertex
+    /// sampling `next`, a subpath arriving from `previous`.
+    fn pdf(&self, camera: &Camera, previous: Option<&Vertex>, next: &Vertex) -> f64 {
+        let direction = next.point - self.point;
+        let pdf = match &self.kind {
+            VertexKind::Camera => camera.direction_pdf(direction),
+            VertexKind::Light(record) => emission_pdf(record, direction),
+            VertexKind::Scattering(record) => {
+                record
+                    .material
+                    .pdf(&self.incoming_ray(previous), record, direction)
+            }
+        };
+
+        self.to_area(pdf, next)
+    }
+
+    /// Returns the fraction of what the vertex carries going toward `next`,
+    /// including the cosine with the surface.
+    fn eval(&self, camera: &Camera, next: &Vertex) -> Color {
+        let direction = next.point - self.point;
+        match &self.kind {
+            VertexKind::Camera => {
+                let importance = camera.direction_pdf(direction);
+                Color::new(importance, importance, importance)
+            }
+            VertexKind::Light(record) => {
+                let cosine = record.normal.dot(&direction.normalized()).max(0.0);
+                Color::new(cosine, cosine, cosine)
+            }
+            VertexKind::Scattering(record) => {
+                record
+                    .material
+                    .eval(&self.incoming_ray(None), record, direction)
+            }
+        }
+    }
+}
+
+/// Returns the probability density with respect to solid angle of an emitter
+/// sending light from the point of the `record` along `direction`. Emitters
+/// send light from their front face, cosine-distributed.
+fn emission_pdf(record: &HitRecord, direction: Vec3) -> f64 {
+    record.normal.dot(&direction.normalized()).max(0.0) / PI
+}
+
+/// Returns the probability density with respect to area of a light subpath
+/// starting from the emitter hit at `vertex`, or 0 if it isn't one of the
+/// scene emitters.
+fn light_origin_pdf(scene: &Scene, vertex: &Vertex) -> f64 {
+    // The ray of the incoming direction hits the vertex at 1.
+    let ray = vertex.incoming_ray(None);
+    let emitter = scene
+        .emitters
+        .iter()
+        .find(|emitter| emitter.try_hit(&ray, 1.0 - 1e-6..=1.0 + 1e-6).is_some());
+
+    match emitter {
+        Some(emitter) => emitter.surface_pdf(vertex.point) / scene.emitters.len() as f64,
+        None => 0.0,
+    }
+}
+
+/// Returns whether nothing stands between the vertices `a` and `b`.
+fn visible(scene: &Scene, a: &Vertex, b: &Vertex) -> bool {
+    let direction = b.point - a.point;
+    let distance = direction.length();
+    let ray = Ray::with_time(a.point, direction / distance, a.time);
+
+    !scene.world.occluded(&ray, 0.001..=distance - 0.001)
+}
+
+impl BidirectionalPathTracer {
+    /// Constructs a new `BidirectionalPathTracer`, whose paths bounce at most
+    /// `max_depth` times. The `camera` must be the one sending the rays, for
+    /// the light subpaths to be connected to it.
+    pub fn new(max_depth: usize, camera: Camera) -> BidirectionalPathTracer {
+        BidirectionalPathTracer {
+            max_depth,
+            roulette_depth: max_depth,
+            camera,
+        }
+    }
+
+    /// Terminates the subpaths randomly after `min_depth` bounces, as
+    /// [`PathTracer::with_russian_roulette`](super::PathTracer::with_russian_roulette)
+    /// does.
+    pub fn with_russian_roulette(self, min_depth: usize) -> BidirectionalPathTracer {
+        BidirectionalPathTracer {
+            roulette_depth: min_depth,
+            ..self
+        }
+    }
+
+    /// Extends the `path` along the `ray` until it has `max_vertices`
+    /// vertices, it's absorbed or it escapes the scene. `beta` is what the ray
+    /// carries and `pdf` the probability density with respect to solid angle of
+    /// its direction.
+    ///
+    /// Returns the ray escaping the scene, if any.
+    fn random_walk<'a>(
+        &self,
+        scene: &'a Scene,
+        mut ray: Ray,
+        mut beta: Color,
+        mut pdf: f64,
+        max_vertices: usize,
+        path: &mut Vec<Vertex<'a>>,
+    ) -> Option<Escape> {
+        while path.len() < max_vertices {
+            let record = match scene.world.try_hit(&ray, 0.001..=f64::INFINITY) {
+                Some(record) => record,
+                None => {
+                    return Some(Escape {
+                        direction: ray.direction(),
+                        pdf,
+                        beta,
+                    })
+                }
+            };
+
+            let mut vertex = Vertex {
+                point: record.intersection_point,
+                kind: VertexKind::Scattering(record),
+                incoming: ray.direction(),
+                time: ray.time(),
+                beta,
+                specular: false,
+                pdf_forward: 0.0,
+                pdf_reverse: 0.0,
+            };
+            let vertices = path.len() + 1;
+            let previous = path.last_mut().expect("subpaths start from a vertex");
+            vertex.pdf_forward = previous.to_area(pdf, &vertex);
+            if vertices == max_vertices {
+                path.push(vertex);
+                break;
+            }
+
+            let record = vertex.record().expect("the vertex has a record");
+            let (attenuation, scattered) = match record.material.scatter(&ray, record) {
+                Some(scattering) => scattering,
+                None => {
+                    path.push(vertex);
+                    break;
+                }
+            };
+
+            pdf = record.material.pdf(&ray, record, scattered.direction());
+            if pdf > 0.0 {
+                let reverse_ray = Ray::with_time(
+                    vertex.point + scattered.direction(),
+                    -scattered.direction(),
+                    ray.time(),
+                );
+                let pdf_reverse = record.material.pdf(&reverse_ray, record, -ray.direction());
+                previous.pdf_reverse = vertex.to_area(pdf_reverse, previous);
+            } else {
+                vertex.specular = true;
+            }
+            path.push(vertex);
+
+            beta = beta * attenuation;
+            if path.len() > self.roulette_depth {
+                let survival = beta.x().max(beta.y()).max(beta.z());
+                if survival < 1.0 {
+                    if random() >= survival {
+                        break;
+                    }
+                    beta /= survival;
+                }
+            }
+
+            ray = scattered;
+        }
+
+        None
+    }
+
+    /// Traces a subpath from a random point of a random emitter of the scene,
+    /// at `time`. The subpath is empty if the scene has no emitters.
+    fn light_subpath<'a>(&self, scene: &'a Scene, time: f64) -> Vec<Vertex<'a>> {
+        let mut path = Vec::new();
+        if scene.emitters.is_empty() || self.max_depth == 0 {
+            return path;
+        }
+
+        let count = scene.emitters.len();
+        let emitter = &scene.emitters[((random() * count as f64) as usize).min(count - 1)];
+        let (record, area_pdf) = match emitter.sample_surface() {
+            Some(sample) => sample,
+            None => return path,
+        };
+        let emitted = record.material.emitted(&record);
+        if emitted.is_near_zero() {
+            return path;
+        }
+
+        let mut direction = record.normal + Vec3::random_normalized();
+        if direction.is_near_zero() {
+            direction = record.normal;
+        }
+        let pdf = emission_pdf(&record, direction);
+        let cosine = record.normal.dot(&direction.normalized());
+        let pdf_origin = area_pdf / count as f64;
+        let vertex = Vertex {
+            point: record.intersection_point,
+            kind: VertexKind::Light(record),
+            incoming: Vec3::zero(),
+            time,
+            beta: emitted / pdf_origin,
+            specular: false,
+            pdf_forward: pdf_origin,
+            pdf_reverse: 0.0,
+        };
+        let ray = Ray::with_time(vertex.point, direction, time);
+        let beta = vertex.beta * cosine / pdf;
+        path.push(vertex);
+
+        self.random_walk(scene, ray, beta, pdf, self.max_depth, &mut path);
+        path
+    }
+
+    /// Returns the light carried by the path made of the `s` first vertices
+    /// of the `light_path` and the `t` first vertices of the `camera_path`,
+    /// connected together, weighted against the other ways to make the path.
+    ///
+    /// With no light vertex, the light is the one emitted by the last camera
+    /// vertex.
+    fn connect(
+        &self,
+        scene: &Scene,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        s: usize,
+        t: usize,
+    ) -> Color {
+        let pt = &camera_path[t - 1];
+        let color = if s == 0 {
+            match &pt.kind {
+                VertexKind::Scattering(record) => pt.beta * record.material.emitted(record),
+                _ => return Color::zero(),
+            }
+        } else {
+            let qs = &light_path[s - 1];
+            if qs.specular || pt.specular {
+                return Color::zero();
+            }
+
+            let distance_squared = (pt.point - qs.point).length_squared();
+            let color = qs.beta * qs.eval(&self.camera, pt) * pt.eval(&self.camera, qs) * pt.beta
+                / distance_squared;
+            if color.is_near_zero() || !visible(scene, qs, pt) {
+                return Color::zero();
+            }
+
+            color
+        };
+        if color.is_near_zero() {
+            return Color::zero();
+        }
+
+        color * self.mis_weight(scene, light_path, camera_path, s, t)
+    }
+
+    /// Returns the weight of the path made by [`BidirectionalPathTracer::connect`]
+    /// with the power heuristic, from the probability densities of making it
+    /// with the other numbers of light and camera vertices.
+    fn mis_weight(
+        &self,
+        scene: &Scene,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        s: usize,
+        t: usize,
+    ) -> f64 {
+        if s + t == 2 {
+            return 1.0;
+        }
+
+        // The densities and specularity of the vertices, updated at the
+        // connection.
+        let mut camera: Vec<_> = camera_path[..t]
+            .iter()
+            .map(|vertex| (vertex.pdf_forward, vertex.pdf_reverse, vertex.specular))
+            .collect();
+        let mut light: Vec<_> = light_path[..s]
+            .iter()
+            .map(|vertex| (vertex.pdf_forward, vertex.pdf_reverse, vertex.specular))
+            .collect();
+
+        let pt = &camera_path[t - 1];
+        let pt_minus = t.checked_sub(2).map(|i| &camera_path[i]);
+        let qs = s.checked_sub(1).map(|i| &light_path[i]);
+        let qs_minus = s.checked_sub(2).map(|i| &light_path[i]);
+
+        match qs {
+            Some(qs) => {
+                camera[t - 1].1 = qs.pdf(&self.camera, qs_minus, pt);
+                if let Some(pt_minus) = pt_minus {
+                    camera[t - 2].1 = pt.pdf(&self.camera, Some(qs), pt_minus);
+                }
+                light[s - 1].1 = pt.pdf(&self.camera, pt_minus, qs);
+                if let Some(qs_minus) = qs_minus {
+                    light[s - 2].1 = qs.pdf(&self.camera, Some(pt), qs_minus);
+                }
+                light[s - 1].2 = false;
+            }
+            None => {
+                // Only the scene emitters start light subpaths.
+                let pdf_origin = light_origin_pdf(scene, pt);
+                if pdf_origin == 0.0 {
+                    return 1.0;
+                }
+                camera[t - 1].1 = pdf_origin;
+                if let (Some(pt_minus), Some(record)) = (pt_minus, pt.record()) {
+                    let pdf = emission_pdf(record, pt_minus.point - pt.point);
+                    camera[t - 2].1 = pt.to_area(pdf, pt_minus);
+                }
+            }
+        }
+        camera[t - 1].2 = false;
+
+        let remap = |pdf: f64| if pdf != 0.0 { pdf } else { 1.0 };
+        let mut sum = 0.0;
+
+        // Making the path with fewer camera vertices.
+        let mut ratio = 1.0;
+        for i in (1..t).rev() {
+            ratio *= remap(camera[i].1) / remap(camera[i].0);
+            if !camera[i].2 && !camera[i - 1].2 {
+                sum += ratio * ratio;
+            }
+        }
+
+        // Making the path with fewer light vertices.
+        let mut ratio = 1.0;
+        for i in (0..s).rev() {
+            ratio *= remap(light[i].1) / remap(light[i].0);
+            let previous_specular = i > 0 && light[i - 1].2;
+            if !light[i].2 && !previous_specular {
+                sum += ratio * ratio;
+            }
+        }
+
+        1.0 / (1.0 + sum)
+    }
+
+    /// Computes the color seen along a ray, connecting a camera subpath
+    /// starting with the ray to the `light_path` with at least one camera
+    /// vertex besides the lens.
+    fn connected_color(&self, ray: &Ray, scene: &Scene, light_path: &[Vertex]) -> Color {
+        let mut camera_path = vec![Vertex {
+            kind: VertexKind::Camera,
+            point: ray.origin(),
+            incoming: Vec3::zero(),
+            time: ray.time(),
+            beta: Color::new(1.0, 1.0, 1.0),
+            specular: false,
+            pdf_forward: 0.0,
+            pdf_reverse: 0.0,
+        }];
+        let escape = self.random_walk(
+            scene,
+            Ray::with_time(ray.origin(), ray.direction(), ray.time()),
+            Color::new(1.0, 1.0, 1.0),
+            self.camera.direction_pdf(ray.direction()),
+            self.max_depth + 1,
+            &mut camera_path,
+        );
+
+        let mut color = Color::zero();
+        for (depth, vertex) in camera_path.iter().enumerate() {
+            if let VertexKind::Scattering(record) = &vertex.kind {
+                let incoming = vertex.incoming_ray(None);
+                color += vertex.beta * direct_lighting(&incoming, scene, record);
+                if !vertex.specular && depth < self.max_depth {
+                    color += vertex.beta * sampled_background(&incoming, scene, record);
+                }
+            }
+        }
+        if let Some(escape) = escape {
+            let weight = if camera_path.len() > 1 && escape.pdf > 0.0 {
+                power_heuristic(escape.pdf, scene.background.pdf(escape.direction))
+            } else {
+                1.0
+            };
+            color += weight * escape.beta * scene.background.radiance(escape.direction);
+        }
+
+        for t in 2..=camera_path.len() {
+            for s in 0..=light_path.len().min(self.max_depth + 1 - t) {
+                color += self.connect(scene, light_path, &camera_path, s, t);
+            }
+        }
+
+        color
+    }
+
+    /// Connects the vertices of the `light_path`, traced at the `time`, to the
+    /// camera.
+    fn splats(&self, scene: &Scene, light_path: &[Vertex], time: f64) -> Vec<Splat> {
+        let mut splats = Vec::new();
+        for s in 2..=light_path.len() {
+            let (origin, screen_point) = match self.camera.project(light_path[s - 1].point) {
+                Some(projection) => projection,
+                None => continue,
+            };
+
+            let camera_vertex = Vertex {
+                kind: VertexKind::Camera,
+                point: origin,
+                incoming: Vec3::zero(),
+                time,
+                beta: Color::new(1.0, 1.0, 1.0),
+                specular: false,
+                pdf_forward: 0.0,
+                pdf_reverse: 0.0,
+            };
+            let color = self.connect(scene, light_path, &[camera_vertex], s, 1);
+            if !color.is_near_zero() {
+                splats.push((screen_point, color));
+            }
+        }
+
+        splats
+    }
+}
+
+impl Integrator for BidirectionalPathTracer {
+    fn color(&self, ray: &Ray, scene: &Scene) -> Color {
+        let light_path = self.light_subpath(scene, ray.time());
+        self.connected_color(ray, scene, &light_path)
+    }
+
+    /// Traces a single light subpath for the ray, both connected to the camera
+    /// subpath and splatted on the screen.
+    fn color_with_splats(&self, ray: &Ray, scene: &Scene) -> (Color, Vec<Splat>) {
+        let light_path = self.light_subpath(scene, ray.time());
+        (
+            self.connected_color(ray, scene, &light_path),
+            self.splats(scene, &light_path, ray.time()),
+        )
+    }
+}
+
+/// Returns the light of the background reaching the `hit` point along a
+/// direction sampled from the background, scattered back along the `ray`.
+///
+/// The light is weighted against the chance of the material scattering the
+/// ray along the same direction.
+fn sampled_background(ray: &Ray, scene: &Scene, hit: &HitRecord) -> Color {
+    if !scene.background.can_sample() {
+        return Color::zero();
+    }
+
+    let (direction, pdf) = match scene.background.sample() {
+        Some(sample) => sample,
+        None => return Color::zero(),
+    };
+    let reflected = hit.material.eval(ray, hit, direction);
+    if reflected.is_near_zero() {
+        return Color::zero();
+    }
+
+    let background_ray = Ray::with_time(hit.intersection_point, direction, ray.time());
+    if scene.world.occluded(&background_ray, 0.001..=f64::INFINITY) {
+        return Color::zero();
+    }
+
+    let material_pdf = hit.material.pdf(ray, hit, direction);
+    power_heuristic(pdf, material_pdf) * reflected * scene.background.radiance(direction) / pdf
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        background::SolidBackground,
+        hittable::{Hittable, HittableCollection, Sphere},
+        integrator::PathTracer,
+        material::{DiffuseLight, Lambertian},
+        random::seed,
+    };
+
+    use super::*;
+
+    /// Returns a scene inside a closed sphere of radius 2 and albedo 0.5, lit
+    /// by an emitter sphere of radius 0.5 at its center, and a camera inside it
+    /// looking away from the emitter.
+    fn lit_room() -> (Scene, Camera) {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let light = Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
+        let emitter: Arc<dyn Hittable + Send + Sync> =
+            Arc::new(Sphere::new(Point3::zero(), 0.5, light));
+        let mut world = HittableCollection::new();
+        world.add(Arc::new(Sphere::new(Point3::zero(), 2.0, material)));
+        world.add(Arc::clone(&emitter));
+        let scene = Scene::new(
+            Arc::new(world),
+            Arc::new(SolidBackground::new(Color::zero())),
+        )
+        .with_emitters(vec![emitter]);
+        let camera = Camera::new(
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.0, 0.0, 2.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.0,
+            0.0,
+            1.0,
+        );
+
+        (scene, camera)
+    }
+
+    /// Returns the mean color of an image covering the screen of the `camera`,
+    /// with the light found from the camera and the light splatted on it.
+    fn mean_color(
+        integrator: &dyn Integrator,
+        scene: &Scene,
+        camera: &Camera,
+        samples: usize,
+    ) -> f64 {
+        let mut sum = 0.0;
+        for _ in 0..samples {
+            let ray = camera.ray_to(random(), random());
+            let (color, splats) = integrator.color_with_splats(&ray, scene);
+            sum += color.x();
+            sum += splats
+                .into_iter()
+                .filter(|((s, t), _)| (0.0..1.0).contains(s) && (0.0..1.0).contains(t))
+                .map(|(_, color)| color.x())
+                .sum::<f64>();
+        }
+
+        sum / samples as f64
+    }
+
+    #[test]
+    fn color_of_surface_lit_by_emitter() {
+        let (scene, camera) = lit_room();
+        let integrator = BidirectionalPathTracer::new(2, camera.clone());
+
+        let color = mean_color(&integrator, &scene, &camera, 40_000);
+
+        // The emitter covers a cone of half angle sin⁻¹(r / R) above the
+        // walls, whose irradiance is π L (r / R)².
+        let expected = 0.5 * 4.0 * 0.0625;
+        assert!((color - expected).abs() < 0.02 * expected);
+    }
+
+    #[test]
+    fn same_mean_as_path_tracer() {
+        let (scene, camera) = lit_room();
+        let bidirectional = BidirectionalPathTracer::new(4, camera.clone());
+        let path_tracer = PathTracer::new(4);
+        seed(23);
+
+        let expected = mean_color(&path_tracer, &scene, &camera, 160_000);
+        let color = mean_color(&bidirectional, &scene, &camera, 80_000);
+
+        assert!((color - expected).abs() < 0.02 * expected);
+    }
+}
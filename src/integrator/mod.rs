@@ -0,0 +1,108 @@
+//! # Integrator module
+//!
+//! Integrators compute the light arriving along the rays sent from the
+//! camera, each with its own light transport algorithm.
+
+pub use ambient_occlusion::AmbientOcclusion;
+pub use bidirectional::BidirectionalPathTracer;
+pub use path_tracer::PathTracer;
+pub use photon_mapper::PhotonMapper;
+pub use visualization::Visualization;
+
+use crate::{color::Color, hittable::HitRecord, ray::Ray, scene::Scene};
+
+mod ambient_occlusion;
+mod bidirectional;
+mod path_tracer;
+mod photon_map;
+mod photon_mapper;
+mod visualization;
+
+/// Light found reaching the camera from the lights, with the screen coordinates
+/// `(s, t)` where it arrives.
+pub type Splat = ((f64, f64), Color);
+
+/// A light transport algorithm.
+pub trait Integrator {
+    /// Returns the color seen along a `ray` sent from the camera into the
+    /// `scene`.
+    fn color(&self, ray: &Ray, scene: &Scene) -> Color;
+
+    /// Returns the color seen along a `ray` sent from the camera into the
+    /// `scene`, with the light found reaching the camera by tracing paths from
+    /// the lights, and the screen coordinates `(s, t)` where it arrives, as
+    /// given to [`Camera::ray_to`](crate::camera::Camera::ray_to).
+    ///
+    /// The light is computed for a screen covering [0, 1] × [0, 1], and must be
+    /// scaled by whoever samples it otherwise. Integrators tracing paths from
+    /// the camera only find no such light by default.
+    fn color_with_splats(&self, ray: &Ray, scene: &Scene) -> (Color, Vec<Splat>) {
+        (self.color(ray, scene), Vec::new())
+    }
+}
+
+/// Returns the light of the scene lights reaching the `hit` point without
+/// being blocked, scattered back along the `ray`.
+fn direct_lighting(ray: &Ray, scene: &Scene, hit: &HitRecord) -> Color {
+    let mut color = Color::zero();
+    for light in &scene.lights {
+        let sample = match light.sample(hit.intersection_point) {
+            Some(sample) => sample,
+            None => continue,
+        };
+
+        let reflected = hit.material.eval(ray, hit, sample.direction);
+        if reflected.is_near_zero() {
+            continue;
+        }
+
+        let shadow_ray = Ray::with_time(hit.intersection_point, sample.direction, ray.time());
+        if !scene
+            .world
+            .occluded(&shadow_ray, 0.001..=sample.distance * (1.0 - 1e-9))
+        {
+            color += reflected * sample.irradiance;
+        }
+    }
+
+    color
+}
+
+/// Returns the light emitted by the scene emitters or background toward the
+/// `hit` point, along a direction sampled from the scene lights, and scattered
+/// back along the `ray`.
+///
+/// The light is weighted against the chance of the material scattering the
+/// ray along the same direction.
+fn sampled_emission(ray: &Ray, scene: &Scene, hit: &HitRecord) -> Color {
+    let direction = match scene.sample_light_direction(hit.intersection_point) {
+        Some(direction) => direction,
+        None => return Color::zero(),
+    };
+
+    let light_pdf = scene.light_pdf(hit.intersection_point, direction);
+    let reflected = hit.material.eval(ray, hit, direction);
+    if light_pdf <= 0.0 || reflected.is_near_zero() {
+        return Color::zero();
+    }
+
+    let light_ray = Ray::with_time(hit.intersection_point, direction, ray.time());
+    let emitted = match scene.world.try_hit(&light_ray, 0.001..=f64::INFINITY) {
+        Some(light_hit) => light_hit.material.emitted(&light_hit),
+        None => scene.background.radiance(direction),
+    };
+
+    let material_pdf = hit.material.pdf(ray, hit, direction);
+    power_heuristic(light_pdf, material_pdf) * reflected * emitted / light_pdf
+}
+
+/// Returns the weight of a sample taken with the probability density `pdf`,
+/// when the same value could have been sampled with the density `other_pdf`.
+///
+/// See [the power heuristic](https://www.pbr-book.org/3ed-2018/Monte_Carlo_Integration/Importance_Sampling#MultipleImportanceSampling)
+/// in Physically Based Rendering.
+fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let (square, other_square) = (pdf * pdf, other_pdf * other_pdf);
+
+    square / (square + other_square)
+}
@@ -0,0 +1,256 @@
+use crate::{color::Color, random::random, ray::Ray, scene::Scene};
+
+use super::{direct_lighting, power_heuristic, sampled_emission, Integrator};
+
+/// An unidirectional path tracer.
+///
+/// Paths are traced from the camera, bouncing off the hit materials until they
+/// escape toward the background or reach the maximum depth. At each bounce,
+/// the light emitted by the scene emitters and background is both sampled
+/// directly and found by the scattered ray, the two being combined with
+/// multiple importance sampling.
+///
+/// Paths carrying little light can also be terminated early with Russian
+/// roulette, see [`PathTracer::with_russian_roulette`].
+pub struct PathTracer {
+    max_depth: usize,
+    roulette_depth: usize,
+}
+
+impl PathTracer {
+    /// Constructs a new `PathTracer`, whose paths bounce at most `max_depth`
+    /// times.
+    pub fn new(max_depth: usize) -> PathTracer {
+        PathTracer {
+            max_depth,
+            roulette_depth: max_depth,
+        }
+    }
+
+    /// Terminates the paths randomly after `min_depth` bounces, with a
+    /// probability growing as the light they carry decreases. The light of the
+    /// surviving paths is increased to make up for the terminated ones, so the
+    /// image stays the same on average.
+    ///
+    /// See [Russian roulette](https://www.pbr-book.org/3ed-2018/Monte_Carlo_Integration/Russian_Roulette_and_Splitting)
+    /// in Physically Based Rendering.
+    pub fn with_russian_roulette(self, min_depth: usize) -> PathTracer {
+        PathTracer {
+            roulette_depth: min_depth,
+            ..self
+        }
+    }
+}
+
+impl Integrator for PathTracer {
+    /// Computes the color seen along a ray.
+    ///
+    /// This will try to hit anything in the world of the `scene`, adding the
+    /// light emitted by the hit material and the light of the scene lights
+    /// directly reaching the hit point to the light it scatters.
+    /// If nothing can be hit, returns the radiance of the scene background.
+    ///
+    /// The light emitted toward a scattered ray is weighted against the chance
+    /// of sampling it from the scene lights, which the previous bounce also
+    /// did. Rays that weren't sampled from a material (camera rays and
+    /// specular reflections) see all of it.
+    fn color(&self, ray: &Ray, scene: &Scene) -> Color {
+        let mut color = Color::zero();
+        // The fraction of the light arriving along the ray that reaches the
+        // camera.
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = Ray::with_time(ray.origin(), ray.direction(), ray.time());
+        let mut scattering_pdf = None;
+
+        for depth in 0..self.max_depth {
+            let weight = match scattering_pdf {
+                Some(pdf) => power_heuristic(pdf, scene.light_pdf(ray.origin(), ray.direction())),
+                None => 1.0,
+            };
+
+            let hit = match scene.world.try_hit(&ray, 0.001..=f64::INFINITY) {
+                Some(hit) => hit,
+                None => {
+                    color += throughput * weight * scene.background.radiance(ray.direction());
+                    break;
+                }
+            };
+
+            color += throughput
+                * (weight * hit.material.emitted(&hit) + direct_lighting(&ray, scene, &hit));
+            let (attenuation, scattered) = match hit.material.scatter(&ray, &hit) {
+                Some(scattering) => scattering,
+                None => break,
+            };
+
+            let pdf = hit.material.pdf(&ray, &hit, scattered.direction());
+            if pdf > 0.0 && depth + 1 < self.max_depth {
+                color += throughput * sampled_emission(&ray, scene, &hit);
+            }
+
+            throughput = throughput * attenuation;
+            if depth + 1 >= self.roulette_depth {
+                let survival = throughput.x().max(throughput.y()).max(throughput.z());
+                if survival < 1.0 {
+                    if random() >= survival {
+                        break;
+                    }
+                    throughput /= survival;
+                }
+            }
+
+            scattering_pdf = if pdf > 0.0 { Some(pdf) } else { None };
+            ray = scattered;
+        }
+
+        color
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{f64::consts::PI, sync::Arc};
+
+    use crate::{
+        background::SolidBackground,
+        hittable::{Hittable, HittableCollection, Plane, Sphere},
+        light::PointLight,
+        material::{DiffuseLight, Lambertian},
+        vec3::{Point3, Vec3},
+    };
+
+    use super::*;
+
+    #[test]
+    fn color_of_surface_lit_by_point_light() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut world = HittableCollection::new();
+        world.add(Arc::new(Plane::new(
+            Point3::zero(),
+            Vec3::new(0.0, 1.0, 0.0),
+            material,
+        )));
+        let scene = Scene::new(
+            Arc::new(world),
+            Arc::new(SolidBackground::new(Color::zero())),
+        )
+        .with_lights(vec![Arc::new(PointLight::new(
+            Point3::new(0.0, 2.0, 0.0),
+            Color::new(4.0, 4.0, 4.0),
+        ))]);
+        let ray = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        // The light scattered by the plane toward the black background is lost.
+        let color = PathTracer::new(1).color(&ray, &scene);
+
+        assert!((color.x() - 0.5 / PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn color_of_surface_in_shadow() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut world = HittableCollection::new();
+        world.add(Arc::new(Plane::new(
+            Point3::zero(),
+            Vec3::new(0.0, 1.0, 0.0),
+            Arc::clone(&material) as Arc<dyn crate::material::Material + Sync + Send>,
+        )));
+        world.add(Arc::new(Sphere::new(
+            Point3::new(1.0, 1.0, 0.0),
+            0.5,
+            material,
+        )));
+        let scene = Scene::new(
+            Arc::new(world),
+            Arc::new(SolidBackground::new(Color::zero())),
+        )
+        .with_lights(vec![Arc::new(PointLight::new(
+            Point3::new(2.0, 2.0, 0.0),
+            Color::new(4.0, 4.0, 4.0),
+        ))]);
+        let ray = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        assert_eq!(PathTracer::new(1).color(&ray, &scene), Color::zero());
+    }
+
+    #[test]
+    fn color_of_surface_lit_by_emitter() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let light = Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
+        let emitter: Arc<dyn Hittable + Send + Sync> =
+            Arc::new(Sphere::new(Point3::new(0.0, 2.0, 0.0), 1.0, light));
+        let mut world = HittableCollection::new();
+        world.add(Arc::new(Plane::new(
+            Point3::zero(),
+            Vec3::new(0.0, 1.0, 0.0),
+            material,
+        )));
+        world.add(Arc::clone(&emitter));
+        let scene = Scene::new(
+            Arc::new(world),
+            Arc::new(SolidBackground::new(Color::zero())),
+        )
+        .with_emitters(vec![emitter]);
+        let ray = Ray::new(Point3::new(0.5, 0.5, 0.0), Vec3::new(-0.5, -0.5, 0.0));
+        let samples = 20_000;
+
+        let color = (0..samples)
+            .map(|_| PathTracer::new(2).color(&ray, &scene))
+            .fold(Color::zero(), |sum, color| sum + color)
+            / samples as f64;
+
+        // A sphere of radius r at a distance d covers a cone of half angle
+        // sin⁻¹(r / d) above the plane, whose irradiance is π L (r / d)².
+        let expected = 0.5 * 4.0 * 0.25;
+        assert!((color.x() - expected).abs() < 0.01 * expected);
+    }
+
+    /// Returns a scene inside a closed sphere of `albedo`, lit by a point light
+    /// at its center giving an irradiance of 1 to the walls.
+    fn furnace(albedo: f64) -> Scene {
+        let material = Arc::new(Lambertian::new(Color::new(albedo, albedo, albedo)));
+        let mut world = HittableCollection::new();
+        world.add(Arc::new(Sphere::new(Point3::zero(), 2.0, material)));
+
+        Scene::new(
+            Arc::new(world),
+            Arc::new(SolidBackground::new(Color::zero())),
+        )
+        .with_lights(vec![Arc::new(PointLight::new(
+            Point3::zero(),
+            Color::new(4.0, 4.0, 4.0),
+        ))])
+    }
+
+    #[test]
+    fn russian_roulette_keeps_the_mean() {
+        let scene = furnace(0.5);
+        let ray = Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0));
+        let mean = |integrator: &PathTracer, samples: usize| {
+            (0..samples)
+                .map(|_| integrator.color(&ray, &scene).x())
+                .sum::<f64>()
+                / samples as f64
+        };
+
+        // Every wall gets the same light, ρ / π of direct light plus ρ times
+        // the light of the walls: ρ / (π (1 - ρ)). Without roulette, every
+        // path gets it.
+        let expected = 1.0 / PI;
+        let without_roulette = mean(&PathTracer::new(50), 10);
+        let with_roulette = mean(&PathTracer::new(50).with_russian_roulette(1), 20_000);
+
+        assert!((without_roulette - expected).abs() < 1e-6);
+        assert!((with_roulette - expected).abs() < 0.02 * expected);
+    }
+
+    #[test]
+    fn deep_paths_do_not_overflow_the_stack() {
+        let scene = furnace(1.0);
+        let ray = Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0));
+
+        let color = PathTracer::new(100_000).color(&ray, &scene);
+
+        assert!((color.x() - 100_000.0 / PI).abs() < 1e-6 * color.x());
+    }
+}
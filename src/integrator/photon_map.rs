@@ -0,0 +1,222 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use crate::{
+    color::Color,
+    vec3::{Point3, Vec3},
+};
+
+/// Light arriving at a point of a surface, carried from an emitter.
+pub struct Photon {
+    pub point: Point3,
+    /// The normalized direction the photon was traveling along.
+    pub direction: Vec3,
+    /// The flux carried by the photon.
+    pub power: Color,
+}
+
+/// Photons stored in a balanced kd-tree, to find the photons nearest to a
+/// point.
+///
+/// The tree is stored in place: the photon at the middle of a range splits the
+/// photons of the range along its axis, the ones before it being on the lower
+/// side and the ones after it on the upper side.
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    /// The split axis of each photon.
+    axes: Vec<usize>,
+}
+
+impl PhotonMap {
+    /// Constructs a new `PhotonMap` from the `photons`.
+    pub fn new(mut photons: Vec<Photon>) -> PhotonMap {
+        let mut axes = vec![0; photons.len()];
+        build(&mut photons, &mut axes);
+
+        PhotonMap { photons, axes }
+    }
+
+    /// Returns the number of photons of the map.
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    /// Returns at most `count` photons nearest to the `point`, among those
+    /// closer than `max_distance`, with the distance to the farthest of them.
+    pub fn nearest(&self, point: Point3, count: usize, max_distance: f64) -> (Vec<&Photon>, f64) {
+        let mut nearest = BinaryHeap::with_capacity(count + 1);
+        if count > 0 {
+            self.search(
+                0..self.photons.len(),
+                point,
+                count,
+                max_distance * max_distance,
+                &mut nearest,
+            );
+        }
+
+        let distance = nearest
+            .peek()
+            .map_or(0.0, |neighbor: &Neighbor| neighbor.distance_squared.sqrt());
+        let photons = nearest
+            .into_iter()
+            .map(|neighbor| &self.photons[neighbor.index])
+            .collect();
+        (photons, distance)
+    }
+
+    /// Adds the photons of the `range` nearer to the `point` than the farthest
+    /// of the `nearest` photons found so far, keeping `count` of them.
+    fn search(
+        &self,
+        range: std::ops::Range<usize>,
+        point: Point3,
+        count: usize,
+        max_distance_squared: f64,
+        nearest: &mut BinaryHeap<Neighbor>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+
+        let middle = (range.start + range.end) / 2;
+        let photon = &self.photons[middle];
+        let axis = self.axes[middle];
+        let offset = point[axis] - photon.point[axis];
+        let (near, far) = if offset < 0.0 {
+            (range.start..middle, middle + 1..range.end)
+        } else {
+            (middle + 1..range.end, range.start..middle)
+        };
+
+        self.search(near, point, count, max_distance_squared, nearest);
+
+        let distance_squared = (photon.point - point).length_squared();
+        if distance_squared <= max_distance_squared {
+            nearest.push(Neighbor {
+                distance_squared,
+                index: middle,
+            });
+            if nearest.len() > count {
+                nearest.pop();
+            }
+        }
+
+        let farthest = if nearest.len() == count {
+            nearest.peek().unwrap().distance_squared
+        } else {
+            max_distance_squared
+        };
+        if offset * offset <= farthest {
+            self.search(far, point, count, max_distance_squared, nearest);
+        }
+    }
+}
+
+/// Sorts the `photons` into a kd-tree, setting the split axis of each one in
+/// `axes`.
+fn build(photons: &mut [Photon], axes: &mut [usize]) {
+    if photons.is_empty() {
+        return;
+    }
+
+    let (minimum, maximum) = photons.iter().fold(
+        (
+            Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        ),
+        |(minimum, maximum), photon| (minimum.min(&photon.point), maximum.max(&photon.point)),
+    );
+    let extent = maximum - minimum;
+    let axis = (0..3)
+        .max_by(|&a, &b| extent[a].total_cmp(&extent[b]))
+        .unwrap();
+
+    let middle = photons.len() / 2;
+    photons.select_nth_unstable_by(middle, |a, b| a.point[axis].total_cmp(&b.point[axis]));
+    axes[middle] = axis;
+
+    let (lower, upper) = photons.split_at_mut(middle);
+    let (lower_axes, upper_axes) = axes.split_at_mut(middle);
+    build(lower, lower_axes);
+    build(&mut upper[1..], &mut upper_axes[1..]);
+}
+
+/// A photon found near a point, ordered by its distance to the point.
+struct Neighbor {
+    distance_squared: f64,
+    index: usize,
+}
+
+impl PartialEq for Neighbor {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Neighbor {}
+
+impl PartialOrd for Neighbor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Neighbor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance_squared.total_cmp(&other.distance_squared)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::random;
+
+    use super::*;
+
+    fn photon(point: Point3) -> Photon {
+        Photon {
+            point,
+            direction: Vec3::new(0.0, -1.0, 0.0),
+            power: Color::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn nearest_photons_are_the_closest() {
+        let points: Vec<_> = (0..1000).map(|_| Point3::random_range(-1.0..1.0)).collect();
+        let map = PhotonMap::new(points.iter().copied().map(photon).collect());
+
+        for _ in 0..20 {
+            let point = Point3::random_range(-1.0..1.0);
+            let max_distance = random();
+            let mut distances: Vec<_> = points
+                .iter()
+                .map(|other| (*other - point).length())
+                .filter(|&distance| distance <= max_distance)
+                .collect();
+            distances.sort_by(f64::total_cmp);
+            distances.truncate(10);
+
+            let (photons, distance) = map.nearest(point, 10, max_distance);
+            let mut found: Vec<_> = photons
+                .iter()
+                .map(|photon| (photon.point - point).length())
+                .collect();
+            found.sort_by(f64::total_cmp);
+
+            assert_eq!(found, distances);
+            assert_eq!(distance, distances.last().copied().unwrap_or(0.0));
+        }
+    }
+
+    #[test]
+    fn empty_map_has_no_nearest_photons() {
+        let map = PhotonMap::new(Vec::new());
+
+        let (photons, distance) = map.nearest(Point3::zero(), 10, 1.0);
+
+        assert_eq!(map.len(), 0);
+        assert!(photons.is_empty());
+        assert_eq!(distance, 0.0);
+    }
+}
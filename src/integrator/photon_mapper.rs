@@ -0,0 +1,620 @@
+use std::f64::consts::PI;
+
+use crate::{
+    color::Color,
+    hittable::{Aabb, HitRecord},
+    random::random,
+    ray::Ray,
+    scene::Scene,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    direct_lighting,
+    photon_map::{Photon, PhotonMap},
+    power_heuristic, sampled_emission, Integrator,
+};
+
+/// The number of photons gathered to estimate the light reflected at a point.
+const GATHERED_PHOTONS: usize = 50;
+
+/// A path tracer finding caustics in a photon map.
+///
+/// Before rendering, photons are shot from the scene emitters, lights and
+/// background toward the [photon targets](Scene::photon_targets) of the scene,
+/// the regions holding the glass and metal objects. The photons reflected or
+/// refracted by the objects in these regions are stored where they land on a
+/// diffuse surface, forming the caustics.
+///
+/// Paths are then traced from the camera as with a
+/// [`PathTracer`](super::PathTracer), gathering the nearest photons at each
+/// diffuse surface instead of finding the caustics with paths bouncing off the
+/// glass and metal objects, which hardly ever reach the light. The caustics
+/// cast by the objects outside of the targets are still found by the paths.
+///
+/// See [A Practical Guide to Global Illumination using Photon Maps](https://graphics.stanford.edu/courses/cs348b-00/course8.pdf),
+/// by Henrik Wann Jensen.
+pub struct PhotonMapper {
+    max_depth: usize,
+    roulette_depth: usize,
+    /// The largest distance photons are gathered from.
+    radius: f64,
+    caustics: PhotonMap,
+    /// The regions the photons of the map come from, the light reaching them
+    /// through specular bounces being only found in the map.
+    targets: Vec<Target>,
+}
+
+impl PhotonMapper {
+    /// Constructs a new `PhotonMapper`, whose paths bounce at most `max_depth`
+    /// times and gather the photons closer than `radius`. It has no photons
+    /// until [`PhotonMapper::with_photons`] is called.
+    pub fn new(max_depth: usize, radius: f64) -> PhotonMapper {
+        PhotonMapper {
+            max_depth,
+            roulette_depth: max_depth,
+            radius,
+            caustics: PhotonMap::new(Vec::new()),
+            targets: Vec::new(),
+        }
+    }
+
+    /// Terminates the camera paths randomly after `min_depth` bounces, as
+    /// with [`PathTracer::with_russian_roulette`](super::PathTracer::with_russian_roulette).
+    pub fn with_russian_roulette(self, min_depth: usize) -> PhotonMapper {
+        PhotonMapper {
+            roulette_depth: min_depth,
+            ..self
+        }
+    }
+
+    /// Shoots `photon_count` photons into the `scene`, each toward one of its
+    /// photon targets, or toward the whole world if there are none. Photons
+    /// are shot from the emitters, the lights and the background with the same
+    /// probability.
+    ///
+    /// The photons coming from far away, from the background and the
+    /// directional lights, start outside of the world, or outside of the
+    /// targets if the world is unbounded. No photons are shot in an unbounded
+    /// world without targets.
+    pub fn with_photons(self, scene: &Scene, photon_count: usize) -> PhotonMapper {
+        let targets_box = scene
+            .photon_targets
+            .iter()
+            .copied()
+            .reduce(|bounding_box, target| bounding_box.surrounding(&target));
+        let bounds = match scene.world.bounding_box().or(targets_box) {
+            Some(bounds) => bounds,
+            None => return self,
+        };
+        let targets = if scene.photon_targets.is_empty() {
+            vec![Target::new(&bounds)]
+        } else {
+            scene.photon_targets.iter().map(Target::new).collect()
+        };
+
+        let bounds = Target::new(&bounds);
+        let mut photons = Vec::new();
+        for _ in 0..photon_count {
+            if let Some((ray, power)) = emit_photon(scene, &targets, &bounds) {
+                self.trace_photon(
+                    scene,
+                    &targets,
+                    ray,
+                    power / photon_count as f64,
+                    &mut photons,
+                );
+            }
+        }
+
+        PhotonMapper {
+            caustics: PhotonMap::new(photons),
+            targets,
+            ..self
+        }
+    }
+
+    /// Returns the number of photons stored in the photon map.
+    pub fn stored_photons(&self) -> usize {
+        self.caustics.len()
+    }
+
+    /// Traces a photon carrying `power` along the `ray` through the specular
+    /// objects of the `scene`, adding it to the `photons` where it lands on a
+    /// diffuse surface. Photons first hitting an object outside of the
+    /// `targets` are dropped, the caustics it casts being found by the paths.
+    fn trace_photon(
+        &self,
+        scene: &Scene,
+        targets: &[Target],
+        ray: Ray,
+        power: Color,
+        photons: &mut Vec<Photon>,
+    ) {
+        let mut ray = ray;
+        let mut power = power;
+        for depth in 0..self.max_depth {
+            let hit = match scene.world.try_hit(&ray, 0.001..=f64::INFINITY) {
+                Some(hit) => hit,
+                None => return,
+            };
+            if depth == 0 && !in_targets(targets, hit.intersection_point) {
+                return;
+            }
+            let (attenuation, scattered) = match hit.material.scatter(&ray, &hit) {
+                Some(scattering) => scattering,
+                None => return,
+            };
+
+            if hit.material.pdf(&ray, &hit, scattered.direction()) > 0.0 {
+                // Photons landing directly on a diffuse surface are direct
+                // light, which is sampled from the camera paths instead.
+                if depth > 0 && !hit.in_medium {
+                    photons.push(Photon {
+                        point: hit.intersection_point,
+                        direction: ray.direction().normalized(),
+                        power,
+                    });
+                }
+                return;
+            }
+
+            power = power * attenuation;
+            ray = scattered;
+        }
+    }
+
+    /// Returns the light of the caustics reaching the `hit` point, scattered
+    /// back along the `ray`, estimated from the density of the nearest
+    /// photons.
+    ///
+    /// When enough photons are found, the farthest one lies on the edge of the
+    /// disk they are gathered in, and is left out to not overestimate their
+    /// density.
+    fn caustics(&self, ray: &Ray, hit: &HitRecord) -> Color {
+        let (photons, distance) =
+            self.caustics
+                .nearest(hit.intersection_point, GATHERED_PHOTONS, self.radius);
+        if photons.is_empty() {
+            return Color::zero();
+        }
+
+        let (radius, full) = if photons.len() < GATHERED_PHOTONS {
+            (self.radius, false)
+        } else {
+            (distance, true)
+        };
+        let mut color = Color::zero();
+        for photon in photons {
+            if full && (photon.point - hit.intersection_point).length() >= distance {
+                continue;
+            }
+
+            let direction = -photon.direction;
+            let cosine = hit.shading_normal.dot(&direction);
+            if cosine <= 0.0 {
+                continue;
+            }
+            color += hit.material.eval(ray, hit, direction) / cosine * photon.power;
+        }
+
+        color / (PI * radius * radius)
+    }
+}
+
+impl Integrator for PhotonMapper {
+    /// Computes the color seen along a ray, as with a
+    /// [`PathTracer`](super::PathTracer), adding the caustics at each diffuse
+    /// surface.
+    ///
+    /// The light found by a path bouncing off a diffuse surface and then only
+    /// off specular ones, the last of them inside a photon target, is part of
+    /// the caustics, so it is left out.
+    fn color(&self, ray: &Ray, scene: &Scene) -> Color {
+        let mut color = Color::zero();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = Ray::with_time(ray.origin(), ray.direction(), ray.time());
+        let mut scattering_pdf = None;
+        // Whether the last diffuse bounce of the path was off a surface, the
+        // light found through the following specular bounces being a caustic.
+        let mut caustic = false;
+
+        for depth in 0..self.max_depth {
+            let weight = match scattering_pdf {
+                Some(pdf) => power_heuristic(pdf, scene.light_pdf(ray.origin(), ray.direction())),
+                None if caustic && in_targets(&self.targets, ray.origin()) => 0.0,
+                None => 1.0,
+            };
+
+            let hit = match scene.world.try_hit(&ray, 0.001..=f64::INFINITY) {
+                Some(hit) => hit,
+                None => {
+                    color += throughput * weight * scene.background.radiance(ray.direction());
+                    break;
+                }
+            };
+
+            color += throughput
+                * (weight * hit.material.emitted(&hit) + direct_lighting(&ray, scene, &hit));
+            let (attenuation, scattered) = match hit.material.scatter(&ray, &hit) {
+                Some(scattering) => scattering,
+                None => break,
+            };
+
+            let pdf = hit.material.pdf(&ray, &hit, scattered.direction());
+            if pdf > 0.0 {
+                if depth + 1 < self.max_depth {
+                    color += throughput * sampled_emission(&ray, scene, &hit);
+                    if !hit.in_medium {
+                        color += throughput * self.caustics(&ray, &hit);
+                    }
+                }
+                caustic = !hit.in_medium;
+            }
+
+            throughput = throughput * attenuation;
+            if depth + 1 >= self.roulette_depth {
+                let survival = throughput.x().max(throughput.y()).max(throughput.z());
+                if survival < 1.0 {
+                    if random() >= survival {
+                        break;
+                    }
+                    throughput /= survival;
+                }
+            }
+
+            scattering_pdf = if pdf > 0.0 { Some(pdf) } else { None };
+            ray = scattered;
+        }
+
+        color
+    }
+}
+
+/// Returns a photon shot toward one of the `targets` from a random emitter,
+/// light or the background of the `scene`, with the power it carries, or
+/// [`None`] if it carries nothing. The photons coming from far away start
+/// outside of the `bounds`.
+fn emit_photon(scene: &Scene, targets: &[Target], bounds: &Target) -> Option<(Ray, Color)> {
+    let sources = scene.emitters.len() + scene.lights.len() + 1;
+    let index = ((random() * sources as f64) as usize).min(sources - 1);
+
+    let (ray, power) = if let Some(emitter) = scene.emitters.get(index) {
+        let (record, area_pdf) = emitter.sample_surface()?;
+        let emitted = record.material.emitted(&record);
+        let (direction, pdf) = aim_from(record.intersection_point, targets);
+        let cosine = record.normal.dot(&direction);
+        if cosine <= 0.0 || pdf == 0.0 {
+            return None;
+        }
+
+        (
+            Ray::new(record.intersection_point, direction),
+            emitted * cosine / (area_pdf * pdf),
+        )
+    } else if let Some(light) = scene.lights.get(index - scene.emitters.len()) {
+        match light.position() {
+            Some(position) => {
+                let (direction, pdf) = aim_from(position, targets);
+                // The irradiance at a distance of 1 is the intensity.
+                let intensity = light.sample(position + direction)?.irradiance;
+                (Ray::new(position, direction), intensity / pdf)
+            }
+            None => {
+                let sample = light.sample(bounds.center)?;
+                let (origin, area_pdf) = aim_along(-sample.direction, targets, bounds);
+                (
+                    Ray::new(origin, -sample.direction),
+                    sample.irradiance / area_pdf,
+                )
+            }
+        }
+    } else {
+        let (direction, pdf) = if scene.background.can_sample() {
+            scene.background.sample()?
+        } else {
+            (Vec3::random_normalized(), 1.0 / (4.0 * PI))
+        };
+        let (origin, area_pdf) = aim_along(-direction, targets, bounds);
+        (
+            Ray::new(origin, -direction),
+            scene.background.radiance(direction) / (pdf * area_pdf),
+        )
+    };
+
+    if power.is_near_zero() {
+        return None;
+    }
+    Some((ray, sources as f64 * power))
+}
+
+/// Samples a direction from `origin` toward one of the `targets`, each picked
+/// with the same probability. Returns it with its probability density with
+/// respect to solid angle.
+fn aim_from(origin: Point3, targets: &[Target]) -> (Vec3, f64) {
+    let direction = pick(targets).sample_direction(origin);
+    let pdf = targets
+        .iter()
+        .map(|target| target.direction_pdf(origin, direction))
+        .sum::<f64>()
+        / targets.len() as f64;
+
+    (direction, pdf)
+}
+
+/// Samples the origin of a ray coming from outside the `bounds` along the
+/// normalized `direction`, toward one of the `targets`, each picked with the
+/// same probability. Returns it with the probability density with respect to
+/// the area of a plane perpendicular to the direction.
+fn aim_along(direction: Vec3, targets: &[Target], bounds: &Target) -> (Point3, f64) {
+    let point = pick(targets).sample_point(direction);
+    let pdf = targets
+        .iter()
+        .map(|target| target.area_pdf(point, direction))
+        .sum::<f64>()
+        / targets.len() as f64;
+    let distance = (point - bounds.center).length() + bounds.radius;
+
+    (point - distance * direction, pdf)
+}
+
+/// Returns whether the `point` is inside one of the `targets`.
+fn in_targets(targets: &[Target], point: Point3) -> bool {
+    targets.iter().any(|target| target.contains(point))
+}
+
+/// Returns one of the `targets`, each picked with the same probability.
+fn pick(targets: &[Target]) -> &Target {
+    let index = ((random() * targets.len() as f64) as usize).min(targets.len() - 1);
+    &targets[index]
+}
+
+/// A sphere photons are shot toward.
+struct Target {
+    center: Point3,
+    radius: f64,
+}
+
+impl Target {
+    /// Constructs a new `Target` bounding the `bounding_box`.
+    fn new(bounding_box: &Aabb) -> Target {
+        Target {
+            center: bounding_box.centroid(),
+            radius: (bounding_box.maximum() - bounding_box.minimum()).length() / 2.0,
+        }
+    }
+
+    /// Returns whether the `point` is inside the target.
+    fn contains(&self, point: Point3) -> bool {
+        (point - self.center).length_squared() <= self.radius * self.radius
+    }
+
+    /// Returns the cosine of the half angle of the cone covered by the target
+    /// from `origin`, or [`None`] if `origin` is inside the target.
+    fn cos_theta_max(&self, origin: Point3) -> Option<f64> {
+        let distance_squared = (self.center - origin).length_squared();
+        if distance_squared <= self.radius * self.radius {
+            return None;
+        }
+
+        Some((1.0 - self.radius * self.radius / distance_squared).sqrt())
+    }
+
+    /// Samples a direction from `origin` uniformly distributed in the cone
+    /// covered by the target, or in every direction from inside it.
+    fn sample_direction(&self, origin: Point3) -> Vec3 {
+        let cos_theta_max = match self.cos_theta_max(origin) {
+            Some(cos_theta_max) => cos_theta_max,
+            None => return Vec3::random_normalized(),
+        };
+
+        let cos_theta = 1.0 + random() * (cos_theta_max - 1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random();
+        let axis = (self.center - origin).normalized();
+        let (tangent, bitangent) = axis.orthonormal_basis();
+
+        cos_theta * axis + sin_theta * phi.cos() * tangent + sin_theta * phi.sin() * bitangent
+    }
+
+    /// Returns the probability density with respect to solid angle of
+    /// [`Target::sample_direction`] giving the normalized `direction` from
+    /// `origin`.
+    fn direction_pdf(&self, origin: Point3, direction: Vec3) -> f64 {
+        match self.cos_theta_max(origin) {
+            Some(cos_theta_max) => {
+                let axis = (self.center - origin).normalized();
+                if axis.dot(&direction) < cos_theta_max {
+                    return 0.0;
+                }
+                1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+            }
+            None => 1.0 / (4.0 * PI),
+        }
+    }
+
+    /// Samples a point uniformly distributed on the disk through the center of
+    /// the target, perpendicular to the normalized `direction`.
+    fn sample_point(&self, direction: Vec3) -> Point3 {
+        let (tangent, bitangent) = direction.orthonormal_basis();
+        let point = Vec3::random_in_unit_disk();
+
+        self.center + self.radius * (point.x() * tangent + point.y() * bitangent)
+    }
+
+    /// Returns the probability density with respect to area of
+    /// [`Target::sample_point`] giving the line through `point` along the
+    /// normalized `direction`.
+    fn area_pdf(&self, point: Point3, direction: Vec3) -> f64 {
+        let offset = self.center - point;
+        let distance_squared = (offset - offset.dot(&direction) * direction).length_squared();
+        if distance_squared > self.radius * self.radius {
+            return 0.0;
+        }
+
+        1.0 / (PI * self.radius * self.radius)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        background::SolidBackground,
+        hittable::{HittableCollection, Plane, Quad},
+        integrator::PathTracer,
+        light::DirectionalLight,
+        material::{Lambertian, Metal},
+    };
+
+    use super::*;
+
+    /// Returns a scene where a directional light shining at 45° is reflected
+    /// by a mirror onto the whole bottom face of a diffuse square of side 1,
+    /// lit from 1 above the mirror. Photons target the region of the mirror
+    /// reflecting onto the square. The world is unbounded if it has a diffuse
+    /// `ground` plane far below the mirror.
+    fn mirror_scene(ground: bool) -> Scene {
+        let mirror = Arc::new(Metal::new(Color::new(1.0, 1.0, 1.0), 0.0));
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut world = HittableCollection::new();
+        world.add(Arc::new(Quad::new(
+            Point3::new(-3.0, 0.0, -3.0),
+            Vec3::new(6.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 6.0),
+            mirror,
+        )));
+        world.add(Arc::new(Quad::new(
+            Point3::new(-0.5, 1.0, -0.5),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Arc::clone(&material) as Arc<dyn crate::material::Material + Sync + Send>,
+        )));
+        if ground {
+            world.add(Arc::new(Plane::new(
+                Point3::new(0.0, -10.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                material,
+            )));
+        }
+        Scene::new(
+            Arc::new(world),
+            Arc::new(SolidBackground::new(Color::zero())),
+        )
+        .with_lights(vec![Arc::new(DirectionalLight::new(
+            Vec3::new(1.0, -1.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))])
+        .with_photon_targets(vec![Aabb::new(
+            Point3::new(-1.5, 0.0, -0.5),
+            Point3::new(-0.5, 0.0, 0.5),
+        )])
+    }
+
+    /// Returns the [`mirror_scene`] lit by a white background instead, whose
+    /// caustics are the background seen from the square through the mirror,
+    /// with the given photon `targets`.
+    fn mirror_under_the_sky(targets: Vec<Aabb>) -> Scene {
+        Scene::new(
+            Arc::clone(&mirror_scene(true).world),
+            Arc::new(SolidBackground::new(Color::new(1.0, 1.0, 1.0))),
+        )
+        .with_photon_targets(targets)
+    }
+
+    /// Returns the mean color seen by the `integrator` looking up at the
+    /// center of the square of the [`mirror_scene`].
+    fn color_under_the_square(integrator: &dyn Integrator, scene: &Scene) -> f64 {
+        let samples = 4_000;
+        let ray = Ray::new(Point3::new(0.0, 0.5, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+        (0..samples)
+            .map(|_| integrator.color(&ray, scene).x())
+            .sum::<f64>()
+            / samples as f64
+    }
+
+    #[test]
+    fn photons_carry_the_reflected_flux() {
+        let scene = mirror_scene(false);
+
+        let integrator = PhotonMapper::new(2, 0.5).with_photons(&scene, 20_000);
+        let (photons, _) = integrator.caustics.nearest(
+            Point3::new(0.0, 1.0, 0.0),
+            integrator.stored_photons(),
+            1.0,
+        );
+        let flux: f64 = photons.iter().map(|photon| photon.power.x()).sum();
+
+        // The light reaching the mirror through a square of side 1 seen at 45°.
+        let expected = 0.5_f64.sqrt();
+        assert!(photons.len() > 3_000);
+        assert!((flux - expected).abs() < 0.04 * expected);
+    }
+
+    #[test]
+    fn color_of_caustic() {
+        let scene = mirror_scene(false);
+        let integrator = PhotonMapper::new(2, 0.5).with_photons(&scene, 80_000);
+        let samples = 400;
+
+        let color = (0..samples)
+            .map(|_| {
+                let origin = Point3::new(random() * 0.6 - 0.3, 0.5, random() * 0.6 - 0.3);
+                let ray = Ray::new(origin, Vec3::new(0.0, 1.0, 0.0));
+                integrator.color(&ray, &scene).x()
+            })
+            .sum::<f64>()
+            / samples as f64;
+
+        // The light of the beam is only found from the photons.
+        let expected = 0.5 / PI * 0.5_f64.sqrt();
+        assert!((color - expected).abs() < 0.06 * expected);
+    }
+
+    #[test]
+    fn photons_are_shot_in_unbounded_worlds() {
+        let scene = mirror_scene(true);
+
+        let integrator = PhotonMapper::new(2, 0.5).with_photons(&scene, 20_000);
+        let (photons, _) = integrator.caustics.nearest(
+            Point3::new(0.0, 1.0, 0.0),
+            integrator.stored_photons(),
+            1.0,
+        );
+        let flux: f64 = photons.iter().map(|photon| photon.power.x()).sum();
+
+        let expected = 0.5_f64.sqrt();
+        assert!(photons.len() > 3_000);
+        assert!((flux - expected).abs() < 0.04 * expected);
+    }
+
+    #[test]
+    fn caustics_are_traced_without_photons() {
+        let scene = mirror_under_the_sky(Vec::new());
+
+        let color = color_under_the_square(&PhotonMapper::new(3, 0.5), &scene);
+
+        let expected = color_under_the_square(&PathTracer::new(3), &scene);
+        assert!(color > 0.3);
+        assert!((color - expected).abs() < 0.05 * expected);
+    }
+
+    #[test]
+    fn caustics_outside_of_the_targets_are_traced() {
+        // The target holds the square but not the mirror.
+        let scene = mirror_under_the_sky(vec![Aabb::new(
+            Point3::new(-0.5, 1.0, -0.5),
+            Point3::new(0.5, 1.0, 0.5),
+        )]);
+        let integrator = PhotonMapper::new(3, 0.5).with_photons(&scene, 20_000);
+
+        let color = color_under_the_square(&integrator, &scene);
+
+        let expected = color_under_the_square(&PathTracer::new(3), &scene);
+        assert_eq!(integrator.stored_photons(), 0);
+        assert!(color > 0.3);
+        assert!((color - expected).abs() < 0.05 * expected);
+    }
+}
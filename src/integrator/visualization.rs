@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use crate::{color::Color, hittable::HitRecord, random::random, ray::Ray, scene::Scene};
+
+use super::Integrator;
+
+/// An integrator showing data of the surfaces hit by the rays instead of the
+/// light, to debug the scenes. Rays hitting nothing are black.
+pub enum Visualization {
+    /// The outward shading normal of the surface, each component mapped from
+    /// [-1, 1] to [0, 1].
+    Normals,
+    /// The distance from the camera, from white for the nearest surfaces to
+    /// black for the surfaces at `max_distance` or farther.
+    Depth { max_distance: f64 },
+    /// The texture coordinates of the surface, as the red and green
+    /// components.
+    Uv,
+    /// A color unique to the material of the surface. The colors change
+    /// between runs.
+    MaterialId,
+    /// The number of times paths bounce before being absorbed or escaping, as
+    /// a heatmap from blue for no bounce to dark red for `max_depth` bounces.
+    /// Paths are terminated with Russian roulette after `roulette_depth`
+    /// bounces, as with [`PathTracer`](super::PathTracer).
+    Bounces {
+        max_depth: usize,
+        roulette_depth: usize,
+    },
+}
+
+impl Integrator for Visualization {
+    fn color(&self, ray: &Ray, scene: &Scene) -> Color {
+        match *self {
+            Visualization::Normals => shade_hit(ray, scene, |hit| {
+                let normal = if hit.front_face {
+                    hit.shading_normal
+                } else {
+                    -hit.shading_normal
+                };
+                0.5 * (normal + Color::new(1.0, 1.0, 1.0))
+            }),
+            Visualization::Depth { max_distance } => shade_hit(ray, scene, |hit| {
+                let distance = hit.t * ray.direction().length();
+                let depth = (1.0 - distance / max_distance).max(0.0);
+                Color::new(depth, depth, depth)
+            }),
+            Visualization::Uv => shade_hit(ray, scene, |hit| Color::new(hit.uv.0, hit.uv.1, 0.0)),
+            Visualization::MaterialId => shade_hit(ray, scene, |hit| {
+                let address = Arc::as_ptr(&hit.material) as *const () as usize;
+                let hash = mix(address as u64);
+                let component = |shift: u32| 0.2 + 0.8 * ((hash >> shift) & 0xff) as f64 / 255.0;
+                Color::new(component(0), component(8), component(16))
+            }),
+            Visualization::Bounces {
+                max_depth,
+                roulette_depth,
+            } => {
+                let bounces = bounces(ray, scene, max_depth, roulette_depth);
+                heat(bounces as f64 / max_depth.max(1) as f64)
+            }
+        }
+    }
+}
+
+/// Returns the color given by `shade` to the surface hit by the `ray` in the
+/// `scene`, or black if the ray hits nothing.
+fn shade_hit(ray: &Ray, scene: &Scene, shade: impl FnOnce(&HitRecord) -> Color) -> Color {
+    match scene.world.try_hit(ray, 0.001..=f64::INFINITY) {
+        Some(hit) => shade(&hit),
+        None => Color::zero(),
+    }
+}
+
+/// Returns the number of times a path starting with the `ray` bounces in the
+/// `scene`, at most `max_depth`, with Russian roulette after `roulette_depth`
+/// bounces.
+fn bounces(ray: &Ray, scene: &Scene, max_depth: usize, roulette_depth: usize) -> usize {
+    let mut throughput = Color::new(1.0, 1.0, 1.0);
+    let mut ray = Ray::with_time(ray.origin(), ray.direction(), ray.time());
+    for depth in 0..max_depth {
+        let hit = match scene.world.try_hit(&ray, 0.001..=f64::INFINITY) {
+            Some(hit) => hit,
+            None => return depth,
+        };
+        let (attenuation, scattered) = match hit.material.scatter(&ray, &hit) {
+            Some(scattering) => scattering,
+            None => return depth,
+        };
+
+        throughput = throughput * attenuation;
+        if depth + 1 >= roulette_depth {
+            let survival = throughput.x().max(throughput.y()).max(throughput.z());
+            if survival < 1.0 {
+                if random() >= survival {
+                    return depth + 1;
+                }
+                throughput /= survival;
+            }
+        }
+        ray = scattered;
+    }
+
+    max_depth
+}
+
+/// Returns the color of the jet colormap at `value` between 0 (dark blue) and
+/// 1 (dark red), through cyan, green, yellow and red.
+fn heat(value: f64) -> Color {
+    let component = |center: f64| (1.5 - (4.0 * value - center).abs()).clamp(0.0, 1.0);
+
+    Color::new(component(3.0), component(2.0), component(1.0))
+}
+
+/// Scrambles the bits of `value`, as in the finalizer of SplitMix64.
+fn mix(value: u64) -> u64 {
+    let mut value = value;
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        background::SolidBackground,
+        hittable::{HittableCollection, Sphere},
+        material::{Lambertian, Material},
+        vec3::{Point3, Vec3},
+    };
+
+    use super::*;
+
+    /// Returns a scene with a sphere of radius 1 at the origin, and a sphere
+    /// of another material at (0, 0, -4).
+    fn spheres() -> Scene {
+        let mut world = HittableCollection::new();
+        world.add(Arc::new(Sphere::new(
+            Point3::zero(),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(1.0, 1.0, 1.0))),
+        )));
+        world.add(Arc::new(Sphere::new(
+            Point3::new(0.0, 0.0, -4.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(1.0, 1.0, 1.0))),
+        )));
+
+        Scene::new(
+            Arc::new(world),
+            Arc::new(SolidBackground::new(Color::new(1.0, 1.0, 1.0))),
+        )
+    }
+
+    #[test]
+    fn normals_are_mapped_to_colors() {
+        let ray = Ray::new(Point3::new(0.0, 3.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        let color = Visualization::Normals.color(&ray, &spheres());
+
+        assert_eq!(color, Color::new(0.5, 1.0, 0.5));
+    }
+
+    #[test]
+    fn depth_fades_with_the_distance() {
+        let scene = spheres();
+        let depth = Visualization::Depth { max_distance: 8.0 };
+        let near = Ray::new(Point3::new(0.0, 3.0, 0.0), Vec3::new(0.0, -2.0, 0.0));
+        let far = Ray::new(Point3::new(0.0, 0.0, -14.0), Vec3::new(0.0, 0.0, 1.0));
+
+        assert_eq!(depth.color(&near, &scene), Color::new(0.75, 0.75, 0.75));
+        assert_eq!(depth.color(&far, &scene), Color::zero());
+    }
+
+    #[test]
+    fn materials_have_different_colors() {
+        let scene = spheres();
+        let first = Ray::new(Point3::new(0.0, 3.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let also_first = Ray::new(Point3::new(3.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let second = Ray::new(Point3::new(0.0, 3.0, -4.0), Vec3::new(0.0, -1.0, 0.0));
+
+        let color = |ray: &Ray| Visualization::MaterialId.color(ray, &scene);
+
+        assert_eq!(color(&first), color(&also_first));
+        assert_ne!(color(&first), color(&second));
+    }
+
+    #[test]
+    fn escaping_rays_do_not_bounce() {
+        let ray = Ray::new(Point3::new(0.0, 3.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+        let color = Visualization::Bounces {
+            max_depth: 10,
+            roulette_depth: 10,
+        }
+        .color(&ray, &spheres());
+
+        assert_eq!(color, Color::new(0.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn closed_rooms_reach_the_max_depth() {
+        let material: Arc<dyn Material + Sync + Send> =
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let mut world = HittableCollection::new();
+        world.add(Arc::new(Sphere::new(Point3::zero(), 2.0, material)));
+        let scene = Scene::new(
+            Arc::new(world),
+            Arc::new(SolidBackground::new(Color::zero())),
+        );
+        let ray = Ray::new(Point3::zero(), Vec3::new(1.0, 0.0, 0.0));
+
+        let color = Visualization::Bounces {
+            max_depth: 10,
+            roulette_depth: 10,
+        }
+        .color(&ray, &scene);
+
+        assert_eq!(color, Color::new(0.5, 0.0, 0.0));
+    }
+}
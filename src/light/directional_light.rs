@@ -0,0 +1,56 @@
+use crate::{
+    color::Color,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Light, LightSample};
+
+/// A light infinitely far away, whose rays are all parallel, like the sun.
+pub struct DirectionalLight {
+    /// The normalized direction toward the light.
+    to_light: Vec3,
+    irradiance: Color,
+}
+
+impl DirectionalLight {
+    /// Constructs a new `DirectionalLight` shining along `direction`, giving
+    /// `irradiance` to surfaces facing it.
+    pub fn new(direction: Vec3, irradiance: Color) -> DirectionalLight {
+        DirectionalLight {
+            to_light: -direction.normalized(),
+            irradiance,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _point: Point3) -> Option<LightSample> {
+        Some(LightSample {
+            direction: self.to_light,
+            distance: f64::INFINITY,
+            irradiance: self.irradiance,
+        })
+    }
+
+    fn position(&self) -> Option<Point3> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample_is_the_same_everywhere() {
+        let light = DirectionalLight::new(Vec3::new(0.0, -2.0, 0.0), Color::new(3.0, 2.0, 1.0));
+
+        for point in &[Point3::zero(), Point3::new(100.0, -50.0, 7.0)] {
+            let sample = light.sample(*point).unwrap();
+
+            assert_eq!(sample.direction, Vec3::new(0.0, 1.0, 0.0));
+            assert_eq!(sample.distance, f64::INFINITY);
+            assert_eq!(sample.irradiance, Color::new(3.0, 2.0, 1.0));
+        }
+    }
+}
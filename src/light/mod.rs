@@ -0,0 +1,41 @@
+//! # Light module
+//!
+//! Lights that can't be hit by rays, as they are infinitely small (point and
+//! spot lights) or infinitely far (directional lights). They are evaluated at
+//! each hit point, by checking with a shadow ray that nothing stands between
+//! the point and the light.
+
+pub use directional_light::DirectionalLight;
+pub use point_light::PointLight;
+pub use spot_light::SpotLight;
+
+use crate::{
+    color::Color,
+    vec3::{Point3, Vec3},
+};
+
+mod directional_light;
+mod point_light;
+mod spot_light;
+
+/// The light arriving at a point from a [`Light`].
+pub struct LightSample {
+    /// The normalized direction from the point toward the light.
+    pub direction: Vec3,
+    /// The distance from the point to the light, infinite for lights
+    /// infinitely far.
+    pub distance: f64,
+    /// The irradiance at the point, on a surface facing the light.
+    pub irradiance: Color,
+}
+
+/// A light source lighting points from a single direction.
+pub trait Light {
+    /// Returns the light arriving at `point`, ignoring what stands between the
+    /// point and the light, or [`None`] if the light doesn't reach the point.
+    fn sample(&self, point: Point3) -> Option<LightSample>;
+
+    /// Returns the point the light is emitted from, or [`None`] if the light
+    /// is infinitely far.
+    fn position(&self) -> Option<Point3>;
+}
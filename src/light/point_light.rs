@@ -0,0 +1,60 @@
+use crate::{color::Color, vec3::Point3};
+
+use super::{Light, LightSample};
+
+/// A light emitting evenly in every direction from a point, like a bare bulb.
+pub struct PointLight {
+    position: Point3,
+    intensity: Color,
+}
+
+impl PointLight {
+    /// Constructs a new `PointLight` at `position`, emitting `intensity` in
+    /// every direction. The irradiance decreases with the square of the
+    /// distance to the light.
+    pub fn new(position: Point3, intensity: Color) -> PointLight {
+        PointLight {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, point: Point3) -> Option<LightSample> {
+        let offset = self.position - point;
+        let distance_squared = offset.length_squared();
+        if distance_squared == 0.0 {
+            return None;
+        }
+
+        let distance = distance_squared.sqrt();
+        Some(LightSample {
+            direction: offset / distance,
+            distance,
+            irradiance: self.intensity / distance_squared,
+        })
+    }
+
+    fn position(&self) -> Option<Point3> {
+        Some(self.position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::vec3::Vec3;
+
+    use super::*;
+
+    #[test]
+    fn irradiance_follows_the_inverse_square_law() {
+        let light = PointLight::new(Point3::new(0.0, 2.0, 0.0), Color::new(8.0, 8.0, 8.0));
+
+        let sample = light.sample(Point3::new(0.0, -2.0, 0.0)).unwrap();
+
+        assert_eq!(sample.direction, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(sample.distance, 4.0);
+        assert_eq!(sample.irradiance, Color::new(0.5, 0.5, 0.5));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    color::Color,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Light, LightSample};
+
+/// A light emitting from a point inside a cone, like a theater spotlight.
+pub struct SpotLight {
+    position: Point3,
+    /// The normalized axis of the cone.
+    direction: Vec3,
+    intensity: Color,
+    /// The cosine of the angle of the cone.
+    cos_total_width: f64,
+    /// The cosine of the angle inside which the light isn't attenuated.
+    cos_falloff_start: f64,
+}
+
+impl SpotLight {
+    /// Constructs a new `SpotLight` at `position`, pointing toward
+    /// `direction` with `intensity` along its axis.
+    ///
+    /// The light fills a cone of `total_width` degrees around its axis (half
+    /// the opening angle). It is attenuated smoothly from `falloff_start`
+    /// degrees to the edge of the cone.
+    pub fn new(
+        position: Point3,
+        direction: Vec3,
+        intensity: Color,
+        total_width: f64,
+        falloff_start: f64,
+    ) -> SpotLight {
+        SpotLight {
+            position,
+            direction: direction.normalized(),
+            intensity,
+            cos_total_width: total_width.to_radians().cos(),
+            cos_falloff_start: falloff_start.min(total_width).to_radians().cos(),
+        }
+    }
+
+    /// Returns the attenuation of the light in the normalized `direction`
+    /// from the light: 1 inside the falloff start, 0 outside the cone.
+    fn falloff(&self, direction: Vec3) -> f64 {
+        let cos_theta = direction.dot(&self.direction);
+        if cos_theta >= self.cos_falloff_start {
+            return 1.0;
+        }
+        if cos_theta <= self.cos_total_width {
+            return 0.0;
+        }
+
+        let x =
+            (cos_theta - self.cos_total_width) / (self.cos_falloff_start - self.cos_total_width);
+        x * x * (3.0 - 2.0 * x)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, point: Point3) -> Option<LightSample> {
+        let offset = self.position - point;
+        let distance_squared = offset.length_squared();
+        if distance_squared == 0.0 {
+            return None;
+        }
+
+        let distance = distance_squared.sqrt();
+        let direction = offset / distance;
+        let falloff = self.falloff(-direction);
+        if falloff == 0.0 {
+            return None;
+        }
+
+        Some(LightSample {
+            direction,
+            distance,
+            irradiance: falloff * self.intensity / distance_squared,
+        })
+    }
+
+    fn position(&self) -> Option<Point3> {
+        Some(self.position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spot() -> SpotLight {
+        SpotLight::new(
+            Point3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+            45.0,
+            30.0,
+        )
+    }
+
+    #[test]
+    fn sample_inside_the_falloff_start() {
+        let sample = spot().sample(Point3::new(0.5, 0.0, 0.0)).unwrap();
+
+        assert!((sample.irradiance.x() - 1.0 / 1.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn sample_in_the_falloff_is_attenuated() {
+        // At 40 degrees from the axis.
+        let point = Point3::new(40.0_f64.to_radians().tan(), 0.0, 0.0);
+        let unattenuated = 1.0 / (point.x() * point.x() + 1.0);
+
+        let sample = spot().sample(point).unwrap();
+
+        assert!(sample.irradiance.x() > 0.0);
+        assert!(sample.irradiance.x() < 0.5 * unattenuated);
+    }
+
+    #[test]
+    fn sample_outside_the_cone() {
+        assert!(spot().sample(Point3::new(1.1, 0.0, 0.0)).is_none());
+        assert!(spot().sample(Point3::new(0.0, 2.0, 0.0)).is_none());
+    }
+}
@@ -1,36 +1,70 @@
 use std::{
     env, f64,
+    fmt::Debug,
     io::{self, Write},
+    str::FromStr,
     sync::Arc,
 };
 
+use background::{
+    Background, EnvironmentMap, GradientBackground, HdrImage, PreethamSky, SolidBackground,
+};
 use camera::Camera;
 use color::Color;
-use hittable::{Hittable, HittableCollection, Sphere};
+use hittable::{
+    Aabb, Bvh, Capsule, Cone, ConstantMedium, Csg, CsgOperation, Cuboid, Cylinder, DensityField,
+    DensityGrid, Disk, DistanceFunction, Heightfield, HeterogeneousMedium, Hittable,
+    HittableCollection, Instance, Mandelbulb, MovingSphere, NoiseDensity, Plane, Quad, RoundedBox,
+    Sdf, SmoothUnion, Sphere, SplitMethod, Torus, TriangleMesh,
+};
 use image::Image;
-use material::{Dielectrics, Lambertian, Material, Metal};
+use integrator::{
+    AmbientOcclusion, BidirectionalPathTracer, Integrator, PathTracer, PhotonMapper, Visualization,
+};
+use light::{DirectionalLight, Light, PointLight, SpotLight};
+use mat4::Mat4;
+use material::{
+    Dielectrics, DiffuseLight, HenyeyGreenstein, Isotropic, Lambertian, Material, Metal,
+};
+use mesh::{HeightMap, Mesh};
 use random::*;
+use scene::Scene;
+use texture::VertexColor;
 use vec3::{Point3, Vec3};
 
+mod background;
 mod camera;
 mod color;
+mod distribution;
 mod hittable;
 mod image;
+mod integrator;
+mod light;
+mod mat4;
 mod material;
+mod mesh;
 mod perf;
+mod perlin;
+mod polynomial;
 mod random;
 mod ray;
+mod scene;
+mod texture;
 mod vec3;
 
-/// Creates a random scene. Returns an [`Arc`] of [`Hittable`].
+/// Creates a random scene. Returns an [`HittableCollection`] of all its
+/// spheres, with the bounding boxes of the glass and metal ones.
 ///
 /// The scene contains multiple sheres of multiple materials (glass, metal and
-/// diffuse).
+/// diffuse). If `bouncing` is `true`, the diffuse spheres move up during the
+/// `[0, 1]` time interval, as in [chapter 2](https://raytracing.github.io/books/RayTracingTheNextWeek.html#motionblur)
+/// of *Ray Tracing: The Next Week*.
 ///
 /// The scene come from [chapter 13](https://raytracing.github.io/books/RayTracingInOneWeekend.html#wherenext?/afinalrender)
 /// of *Ray Tracing in One Weekend*.
-fn random_scene() -> Arc<dyn Hittable + Sync + Send> {
+fn random_scene(bouncing: bool) -> (HittableCollection, Vec<Aabb>) {
     let mut world = HittableCollection::new();
+    let mut photon_targets = Vec::new();
 
     let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
     world.add(Arc::new(Sphere::new(
@@ -47,7 +81,22 @@ fn random_scene() -> Arc<dyn Hittable + Sync + Send> {
             if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
                 let sphere_material = if choose_mat < 0.8 {
                     let albedo = Color::random() * Color::random();
-                    Arc::new(Lambertian::new(albedo)) as Arc<dyn Material + Sync + Send>
+                    let material = Arc::new(Lambertian::new(albedo));
+
+                    if bouncing {
+                        let destination = center + Vec3::new(0.0, random_range(0.0..0.5), 0.0);
+                        world.add(Arc::new(MovingSphere::new(
+                            center,
+                            destination,
+                            0.0,
+                            1.0,
+                            0.2,
+                            material,
+                        )));
+                        continue;
+                    }
+
+                    material as Arc<dyn Material + Sync + Send>
                 } else if choose_mat < 0.95 {
                     let albedo = Color::random_range(0.5..1.0);
                     let fuzz = random_range(0.0..0.5);
@@ -56,52 +105,702 @@ fn random_scene() -> Arc<dyn Hittable + Sync + Send> {
                     Arc::new(Dielectrics::new(1.5)) as Arc<dyn Material + Sync + Send>
                 };
 
-                world.add(Arc::new(Sphere::new(center, 0.2, sphere_material)));
+                let sphere = Arc::new(Sphere::new(center, 0.2, sphere_material));
+                if choose_mat >= 0.8 {
+                    photon_targets.extend(sphere.bounding_box());
+                }
+                world.add(sphere);
             }
         }
     }
 
     let material = Arc::new(Dielectrics::new(1.5));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Sphere::new(Point3::new(0.0, 1.0, 0.0), 1.0, material)),
+    );
+
+    let material = Arc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1)));
     world.add(Arc::new(Sphere::new(
-        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(-4.0, 1.0, 0.0),
         1.0,
         material,
     )));
 
+    let material = Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material)),
+    );
+
+    (world, photon_targets)
+}
+
+/// Adds a glass or metal `object` to the `world`, and its bounding box to the
+/// `photon_targets`, toward which photons are shot to find the caustics it
+/// casts.
+fn add_specular(
+    world: &mut HittableCollection,
+    photon_targets: &mut Vec<Aabb>,
+    object: Arc<dyn Hittable + Sync + Send>,
+) {
+    photon_targets.extend(object.bounding_box());
+    world.add(object);
+}
+
+/// Creates a scene made of analytic shapes. Returns an [`HittableCollection`]
+/// of all its objects, with the bounding boxes of the glass and metal ones.
+///
+/// The scene contains an infinite ground plane, a rotated box, a mirror disk,
+/// a glass quad, a cylinder, a cone, a torus and a block drilled twice made by
+/// constructive solid geometry, framed by the same camera as [`random_scene`].
+fn shapes_scene() -> (HittableCollection, Vec<Aabb>) {
+    let mut world = HittableCollection::new();
+    let mut photon_targets = Vec::new();
+
+    let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Plane::new(
+        Point3::zero(),
+        Vec3::new(0.0, 1.0, 0.0),
+        ground_material,
+    )));
+
     let material = Arc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1)));
-    world.add(Arc::new(Sphere::new(
-        Point3::new(-4.0, 1.0, 0.0),
-        1.0,
+    let cuboid = Arc::new(Cuboid::new(
+        Point3::new(-0.75, 0.0, -0.75),
+        Point3::new(0.75, 1.5, 0.75),
         material,
+    ));
+    world.add(Arc::new(Instance::new(
+        cuboid,
+        Mat4::translation(Vec3::new(-0.25, 0.0, -1.75))
+            * Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), 20.0),
     )));
 
     let material = Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Disk::new(
+            Point3::new(-2.0, 1.2, 1.0),
+            Vec3::new(1.0, 0.0, 0.2),
+            1.2,
+            material,
+        )),
+    );
+
+    let material = Arc::new(Dielectrics::new(1.5));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Quad::axis_aligned(
+            Point3::new(1.0, 0.0, 0.5),
+            Point3::new(1.0, 1.0, 1.5),
+            material,
+        )),
+    );
+
+    let material = Arc::new(Lambertian::new(Color::new(0.1, 0.2, 0.5)));
     world.add(Arc::new(Sphere::new(
-        Point3::new(4.0, 1.0, 0.0),
-        1.0,
+        Point3::new(1.5, 0.5, -0.5),
+        0.5,
         material,
     )));
 
-    Arc::new(world)
+    let material = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.9), 0.2));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Cylinder::new(
+            Point3::new(2.5, 0.0, -2.0),
+            Point3::new(2.5, 1.2, -2.0),
+            0.4,
+            material,
+        )),
+    );
+
+    let material = Arc::new(Lambertian::new(Color::new(0.6, 0.1, 0.1)));
+    world.add(Arc::new(Cone::new(
+        Point3::new(2.5, 0.0, 1.8),
+        Point3::new(2.5, 1.0, 1.8),
+        0.4,
+        material,
+    )));
+
+    // A rounded block with two crossing holes drilled through it.
+    let material = Arc::new(Metal::new(Color::new(0.6, 0.6, 0.6), 0.3));
+    let block = Arc::new(Csg::new(
+        CsgOperation::Intersection,
+        Arc::new(Cuboid::new(
+            Point3::new(-0.1, 0.0, 1.6),
+            Point3::new(1.1, 1.2, 2.8),
+            Arc::clone(&material) as Arc<dyn Material + Sync + Send>,
+        )),
+        Arc::new(Sphere::new(
+            Point3::new(0.5, 0.6, 2.2),
+            0.8,
+            Arc::clone(&material) as Arc<dyn Material + Sync + Send>,
+        )),
+    ));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Csg::new(
+            CsgOperation::Difference,
+            block,
+            Arc::new(Csg::new(
+                CsgOperation::Union,
+                Arc::new(Cylinder::new(
+                    Point3::new(-0.2, 0.6, 2.2),
+                    Point3::new(1.2, 0.6, 2.2),
+                    0.3,
+                    Arc::clone(&material) as Arc<dyn Material + Sync + Send>,
+                )),
+                Arc::new(Cylinder::new(
+                    Point3::new(0.5, 0.6, 1.5),
+                    Point3::new(0.5, 0.6, 2.9),
+                    0.3,
+                    material,
+                )),
+            )),
+        )),
+    );
+
+    let material = Arc::new(Metal::new(Color::new(0.9, 0.7, 0.3), 0.05));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Torus::new(
+            Point3::new(3.0, 0.15, 0.5),
+            Vec3::new(0.0, 1.0, 0.0),
+            0.45,
+            0.15,
+            material,
+        )),
+    );
+
+    (world, photon_targets)
 }
 
-const THREADS_AMOUNT_VARIABLE: &str = "THREADS_AMOUNT";
+/// A regular octahedron, i.e. the points whose taxicab distance to the
+/// `center` is at most `size`.
+struct Octahedron {
+    center: Point3,
+    size: f64,
+}
 
-fn main() {
-    let threads_amount: usize = match env::var(THREADS_AMOUNT_VARIABLE) {
-        Ok(var) => var.trim().parse().unwrap_or_else(|_| {
-            panic!(
-                "Unexpected {} environment variable format",
-                THREADS_AMOUNT_VARIABLE
-            )
+impl DistanceFunction for Octahedron {
+    fn distance(&self, point: Point3) -> f64 {
+        let p = point - self.center;
+        // The distance to the plane of the closest face, which underestimates
+        // the distance to the edges and vertices.
+        (p.x().abs() + p.y().abs() + p.z().abs() - self.size) / 3.0_f64.sqrt()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.center, self.center).expanded(self.size))
+    }
+}
+
+/// Creates a scene of signed distance field shapes next to spheres. Returns an
+/// [`HittableCollection`] of all its objects, with the bounding boxes of the
+/// glass and metal ones.
+///
+/// The scene contains a Mandelbulb fractal, a rounded box, two capsules
+/// blended together, a bumpy sphere and an [`Octahedron`], framed by the same
+/// camera as [`random_scene`].
+fn sdf_scene() -> (HittableCollection, Vec<Aabb>) {
+    let mut world = HittableCollection::new();
+    let mut photon_targets = Vec::new();
+
+    let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        ground_material,
+    )));
+
+    let material = Arc::new(Lambertian::new(Color::new(0.7, 0.4, 0.2)));
+    let mandelbulb = Arc::new(Sdf::new(Arc::new(Mandelbulb::new(8.0, 10)), material));
+    world.add(Arc::new(Instance::new(
+        mandelbulb,
+        Mat4::translation(Vec3::new(0.0, 1.2, 0.0)),
+    )));
+
+    let material = Arc::new(Metal::new(Color::new(0.7, 0.7, 0.8), 0.1));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Sdf::new(
+            Arc::new(RoundedBox::new(
+                Point3::new(0.0, 0.5, -2.6),
+                Vec3::new(1.0, 1.0, 1.0),
+                0.15,
+            )),
+            material,
+        )),
+    );
+
+    let material = Arc::new(Lambertian::new(Color::new(0.1, 0.3, 0.6)));
+    world.add(Arc::new(Sdf::new(
+        Arc::new(SmoothUnion::new(
+            Arc::new(Capsule::new(
+                Point3::new(0.0, 0.3, 2.2),
+                Point3::new(0.0, 1.3, 2.2),
+                0.3,
+            )),
+            Arc::new(Capsule::new(
+                Point3::new(-0.5, 0.3, 2.6),
+                Point3::new(0.5, 0.3, 3.0),
+                0.25,
+            )),
+            0.3,
+        )),
+        material,
+    )));
+
+    // A bumpy sphere, whose distance is only approximated by the displacement.
+    let material = Arc::new(Lambertian::new(Color::new(0.3, 0.6, 0.3)));
+    let center = Point3::new(1.5, 0.5, -1.0);
+    let bumpy_sphere = move |point: Point3| {
+        let p = 12.0 * (point - center);
+        (point - center).length() - 0.42 + 0.03 * p.x().sin() * p.y().sin() * p.z().sin()
+    };
+    world.add(Arc::new(Sdf::with_bounds(
+        Arc::new(bumpy_sphere),
+        Aabb::new(center, center).expanded(0.5),
+        material,
+    )));
+
+    let material = Arc::new(Lambertian::new(Color::new(0.8, 0.7, 0.2)));
+    world.add(Arc::new(Sdf::new(
+        Arc::new(Octahedron {
+            center: Point3::new(-2.5, 0.7, 1.5),
+            size: 0.7,
+        }),
+        material,
+    )));
+
+    let material = Arc::new(Dielectrics::new(1.5));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Sphere::new(Point3::new(2.0, 0.4, 1.2), 0.4, material)),
+    );
+
+    (world, photon_targets)
+}
+
+/// A puff of smoke, whose `density` at its `center` fades out to nothing at
+/// `radius` from it.
+struct Puff {
+    center: Point3,
+    radius: f64,
+    density: f64,
+}
+
+impl DensityField for Puff {
+    fn density(&self, point: Point3) -> f64 {
+        let distance_squared = (point - self.center).length_squared();
+        self.density * (1.0 - distance_squared / (self.radius * self.radius)).max(0.0)
+    }
+
+    fn max_density(&self) -> Option<f64> {
+        Some(self.density)
+    }
+}
+
+/// Creates a scene of participating media. Returns an [`HittableCollection`]
+/// of all its elements, with the bounding box of its glass sphere.
+///
+/// The scene contains a glass sphere filled with a blue medium, a box of
+/// smoke scattering forward, a cloud of Perlin turbulence, a plume sampled on
+/// a density grid, a [`Puff`] of smoke and a layer of ground fog, framed by the
+/// same camera as [`random_scene`].
+fn media_scene() -> (HittableCollection, Vec<Aabb>) {
+    let mut world = HittableCollection::new();
+    let mut photon_targets = Vec::new();
+
+    let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        ground_material,
+    )));
+
+    // A glass sphere filled with a thin medium looks like subsurface
+    // scattering.
+    let boundary = Arc::new(Sphere::new(
+        Point3::new(3.5, 1.0, -1.3),
+        1.0,
+        Arc::new(Dielectrics::new(1.5)),
+    ));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::clone(&boundary) as Arc<dyn Hittable + Sync + Send>,
+    );
+    world.add(Arc::new(ConstantMedium::new(
+        boundary,
+        0.6,
+        Arc::new(Isotropic::new(Color::new(0.2, 0.4, 0.9))),
+    )));
+
+    let boundary = Arc::new(Cuboid::new(
+        Point3::new(-0.7, 0.0, 0.6),
+        Point3::new(0.7, 1.4, 2.0),
+        Arc::new(Lambertian::new(Color::zero())),
+    ));
+    world.add(Arc::new(ConstantMedium::new(
+        boundary,
+        1.5,
+        Arc::new(HenyeyGreenstein::new(Color::new(0.9, 0.9, 0.9), 0.6)),
+    )));
+
+    let boundary = Arc::new(Sphere::new(
+        Point3::new(-4.0, 1.2, 0.0),
+        1.2,
+        Arc::new(Lambertian::new(Color::zero())),
+    ));
+    world.add(Arc::new(HeterogeneousMedium::new(
+        boundary,
+        Arc::new(NoiseDensity::new(4.0, 1.5, 5)),
+        Arc::new(Isotropic::new(Color::new(0.95, 0.95, 0.95))),
+    )));
+
+    // A plume of smoke, thinning as it rises and spreads.
+    let bounds = Aabb::new(Point3::new(1.2, 0.0, -2.8), Point3::new(2.4, 2.5, -1.6));
+    let axis = Point3::new(1.8, 0.0, -2.2);
+    let plume = DensityGrid::from_fn(bounds, [16, 32, 16], |point| {
+        let radius = 0.15 + 0.15 * point.y();
+        let offset = Vec3::new(point.x() - axis.x(), 0.0, point.z() - axis.z());
+        let falloff = (1.0 - offset.length() / radius).max(0.0);
+        6.0 * falloff * (1.0 - point.y() / 2.5)
+    });
+    world.add(Arc::new(HeterogeneousMedium::new(
+        Arc::new(Cuboid::new(
+            bounds.minimum(),
+            bounds.maximum(),
+            Arc::new(Lambertian::new(Color::zero())),
+        )),
+        Arc::new(plume),
+        Arc::new(Isotropic::new(Color::new(0.3, 0.3, 0.3))),
+    )));
+
+    let center = Point3::new(-3.0, 1.5, 2.0);
+    world.add(Arc::new(HeterogeneousMedium::new(
+        Arc::new(Sphere::new(
+            center,
+            0.7,
+            Arc::new(Lambertian::new(Color::zero())),
+        )),
+        Arc::new(Puff {
+            center,
+            radius: 0.7,
+            density: 8.0,
         }),
-        Err(_) => 1,
+        Arc::new(Isotropic::new(Color::new(0.8, 0.5, 0.4))),
+    )));
+
+    // Ground fog, thinning with height.
+    let boundary = Arc::new(Cuboid::new(
+        Point3::new(-6.0, 0.0, -6.0),
+        Point3::new(6.0, 0.6, 6.0),
+        Arc::new(Lambertian::new(Color::zero())),
+    ));
+    world.add(Arc::new(HeterogeneousMedium::with_max_density(
+        boundary,
+        Arc::new(|point: Point3| 0.1 * (1.0 - point.y() / 0.6)),
+        0.1,
+        Arc::new(Isotropic::new(Color::new(1.0, 1.0, 1.0))),
+    )));
+
+    (world, photon_targets)
+}
+
+/// Creates the Cornell box scene. Returns an [`HittableCollection`] of all its
+/// elements, with no glass nor metal objects.
+///
+/// The scene contains a red, a white and a green wall around two white boxes,
+/// lit by a light in the ceiling. It is meant to be seen with
+/// [`cornell_box_camera`], in front of a black background.
+///
+/// The scene come from [chapter 7](https://raytracing.github.io/books/RayTracingTheNextWeek.html#rectanglesandlights/creatinganemptycornellbox)
+/// of *Ray Tracing: The Next Week*.
+fn cornell_box_scene() -> (HittableCollection, Vec<Aabb>) {
+    let mut world = cornell_box_walls();
+    world.add(cornell_box_light());
+
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let tall_box = Arc::new(Cuboid::new(
+        Point3::zero(),
+        Point3::new(165.0, 330.0, 165.0),
+        Arc::clone(&white) as Arc<dyn Material + Sync + Send>,
+    ));
+    world.add(Arc::new(Instance::new(
+        tall_box,
+        Mat4::translation(Vec3::new(265.0, 0.0, 295.0))
+            * Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), 15.0),
+    )));
+    let short_box = Arc::new(Cuboid::new(
+        Point3::zero(),
+        Point3::new(165.0, 165.0, 165.0),
+        white,
+    ));
+    world.add(Arc::new(Instance::new(
+        short_box,
+        Mat4::translation(Vec3::new(130.0, 0.0, 65.0))
+            * Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), -18.0),
+    )));
+
+    (world, Vec::new())
+}
+
+/// Returns the red, white and green walls of the [`cornell_box_scene`],
+/// without its light.
+fn cornell_box_walls() -> HittableCollection {
+    let mut world = HittableCollection::new();
+
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+
+    world.add(Arc::new(Quad::axis_aligned(
+        Point3::new(555.0, 0.0, 0.0),
+        Point3::new(555.0, 555.0, 555.0),
+        green,
+    )));
+    world.add(Arc::new(Quad::axis_aligned(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(0.0, 555.0, 555.0),
+        red,
+    )));
+    world.add(Arc::new(Quad::axis_aligned(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(555.0, 0.0, 555.0),
+        Arc::clone(&white) as Arc<dyn Material + Sync + Send>,
+    )));
+    world.add(Arc::new(Quad::axis_aligned(
+        Point3::new(0.0, 555.0, 0.0),
+        Point3::new(555.0, 555.0, 555.0),
+        Arc::clone(&white) as Arc<dyn Material + Sync + Send>,
+    )));
+    world.add(Arc::new(Quad::axis_aligned(
+        Point3::new(0.0, 0.0, 555.0),
+        Point3::new(555.0, 555.0, 555.0),
+        white,
+    )));
+
+    world
+}
+
+/// Returns the light in the ceiling of the [`cornell_box_scene`], facing
+/// down.
+fn cornell_box_light() -> Arc<dyn Hittable + Send + Sync> {
+    let light = Arc::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0)));
+
+    Arc::new(Quad::new(
+        Point3::new(213.0, 554.0, 227.0),
+        Vec3::new(130.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 105.0),
+        light,
+    ))
+}
+
+/// Creates a scene lit by a bulb. Returns an [`HittableCollection`] of all its
+/// elements, with the bounding box of the glass of the bulb.
+///
+/// The scene contains the walls of the [`cornell_box_scene`] around a white
+/// box, lit by a small light enclosed in a glass sphere. Light can only leave
+/// the bulb through the glass, which makes it hard to find from the camera.
+/// It is meant to be seen with [`cornell_box_camera`], in front of a black
+/// background.
+fn bulb_scene() -> (HittableCollection, Vec<Aabb>) {
+    let mut world = cornell_box_walls();
+    let mut photon_targets = Vec::new();
+    world.add(bulb_light());
+
+    let glass = Arc::new(Dielectrics::new(1.5));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Sphere::new(Point3::new(278.0, 400.0, 278.0), 80.0, glass)),
+    );
+
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let short_box = Arc::new(Cuboid::new(
+        Point3::zero(),
+        Point3::new(165.0, 165.0, 165.0),
+        white,
+    ));
+    world.add(Arc::new(Instance::new(
+        short_box,
+        Mat4::translation(Vec3::new(130.0, 0.0, 65.0))
+            * Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), -18.0),
+    )));
+
+    (world, photon_targets)
+}
+
+/// Returns the light inside the glass sphere of the [`bulb_scene`].
+fn bulb_light() -> Arc<dyn Hittable + Send + Sync> {
+    let light = Arc::new(DiffuseLight::new(Color::new(200.0, 180.0, 140.0)));
+
+    Arc::new(Sphere::new(Point3::new(278.0, 400.0, 278.0), 15.0, light))
+}
+
+/// Creates a scene lit by lights that can't be hit. Returns an
+/// [`HittableCollection`] of all its elements, with the bounding box of its
+/// metal sphere.
+///
+/// The scene contains a few spheres and a box on a ground plane, meant to be
+/// lit at night by [`studio_lights`].
+fn lights_scene() -> (HittableCollection, Vec<Aabb>) {
+    let mut world = HittableCollection::new();
+    let mut photon_targets = Vec::new();
+
+    let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Plane::new(
+        Point3::zero(),
+        Vec3::new(0.0, 1.0, 0.0),
+        ground_material,
+    )));
+
+    let material = Arc::new(Lambertian::new(Color::new(0.8, 0.3, 0.2)));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        material,
+    )));
+
+    let material = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.2));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Sphere::new(Point3::new(-3.0, 1.0, -1.0), 1.0, material)),
+    );
+
+    let material = Arc::new(Lambertian::new(Color::new(0.2, 0.5, 0.8)));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(3.0, 0.6, 1.0),
+        0.6,
+        material,
+    )));
+
+    let material = Arc::new(Lambertian::new(Color::new(0.7, 0.7, 0.3)));
+    world.add(Arc::new(Instance::new(
+        Arc::new(Cuboid::new(
+            Point3::new(-0.6, 0.0, -0.6),
+            Point3::new(0.6, 1.2, 0.6),
+            material,
+        )),
+        Mat4::translation(Vec3::new(1.5, 0.0, -2.5))
+            * Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), 30.0),
+    )));
+
+    (world, photon_targets)
+}
+
+/// Returns the lights of the [`lights_scene`]: a warm bulb, a cold spotlight
+/// on the blue sphere and dim moonlight.
+fn studio_lights() -> Vec<Arc<dyn Light + Send + Sync>> {
+    vec![
+        Arc::new(PointLight::new(
+            Point3::new(2.0, 3.0, 2.5),
+            Color::new(20.0, 14.0, 8.0),
+        )),
+        Arc::new(SpotLight::new(
+            Point3::new(5.0, 5.0, 3.0),
+            Vec3::new(-2.0, -4.4, -2.0),
+            Color::new(30.0, 40.0, 60.0),
+            12.0,
+            8.0,
+        )),
+        Arc::new(DirectionalLight::new(
+            Vec3::new(-1.0, -2.0, -0.5),
+            Color::new(0.1, 0.12, 0.2),
+        )),
+    ]
+}
+
+/// Creates a terrain scene from a `height_map`. Returns an
+/// [`HittableCollection`] of all its elements, with the bounding boxes of its
+/// spheres.
+///
+/// The scene contains a heightfield made of the `height_map`, with a metal
+/// sphere and a glass sphere resting on it, framed by the same camera as
+/// [`random_scene`].
+fn terrain_scene(height_map: &HeightMap) -> (HittableCollection, Vec<Aabb>) {
+    let mut world = HittableCollection::new();
+    let mut photon_targets = Vec::new();
+
+    let corner = Point3::new(-12.0, -1.5, -12.0);
+    let size = Vec3::new(24.0, 3.0, 24.0);
+    let ground_material = Arc::new(Lambertian::new(Color::new(0.4, 0.5, 0.3)));
+    world.add(Arc::new(Heightfield::new(
+        height_map,
+        corner,
+        size,
+        ground_material,
+    )));
+
+    // The height of the closest grid point, to rest the spheres on.
+    let ground_height = |x: f64, z: f64| {
+        let grid_x = (x - corner.x()) / size.x() * (height_map.width() - 1) as f64;
+        let grid_z = (z - corner.z()) / size.z() * (height_map.depth() - 1) as f64;
+        corner.y() + size.y() * height_map.height(grid_x.round() as usize, grid_z.round() as usize)
     };
 
-    // World
-    let world = random_scene();
+    let material = Arc::new(Metal::new(Color::new(0.8, 0.6, 0.5), 0.05));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Sphere::new(
+            Point3::new(0.0, ground_height(0.0, -1.0) + 0.5, -1.0),
+            0.6,
+            material,
+        )),
+    );
 
-    // Camera
+    let material = Arc::new(Dielectrics::new(1.5));
+    add_specular(
+        &mut world,
+        &mut photon_targets,
+        Arc::new(Sphere::new(
+            Point3::new(3.0, ground_height(3.0, 1.0) + 0.4, 1.0),
+            0.5,
+            material,
+        )),
+    );
+
+    (world, photon_targets)
+}
+
+/// Returns a procedural height map of rolling hills, made of a few octaves of
+/// sine waves.
+fn hills_height_map() -> HeightMap {
+    let size = 512;
+    HeightMap::from_fn(size, size, |x, z| {
+        let (x, z) = (x as f64 / size as f64, z as f64 / size as f64);
+        let mut height = 0.5;
+        let mut amplitude = 0.25;
+        let mut frequency = 6.0;
+        for octave in 0..5 {
+            let phase = octave as f64 * 1.7;
+            height +=
+                amplitude * (frequency * x + phase).sin() * (frequency * z * 1.3 - phase).cos();
+            amplitude *= 0.45;
+            frequency *= 2.1;
+        }
+        height.clamp(0.0, 1.0)
+    })
+}
+
+/// Returns the aspect ratio and the camera used by most scenes, looking at the
+/// origin from slightly above the ground.
+fn default_camera() -> (f64, Camera) {
     let aspect_ratio = 3.0 / 2.0;
     let look_from = Point3::new(13.0, 2.0, 3.0);
     let look_at = Point3::zero();
@@ -109,6 +808,30 @@ fn main() {
     let dist_to_focus = 10.0;
     let aperture = 0.1;
     let fov = 20.0;
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        vup,
+        fov,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+    )
+    .with_shutter(0.0, 1.0);
+
+    (aspect_ratio, camera)
+}
+
+/// Returns the aspect ratio and the camera looking into the
+/// [`cornell_box_scene`].
+fn cornell_box_camera() -> (f64, Camera) {
+    let aspect_ratio = 1.0;
+    let look_from = Point3::new(278.0, 278.0, -800.0);
+    let look_at = Point3::new(278.0, 278.0, 0.0);
+    let vup = Vec3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 10.0;
+    let aperture = 0.0;
+    let fov = 40.0;
     let camera = Camera::new(
         look_from,
         look_at,
@@ -119,17 +842,196 @@ fn main() {
         dist_to_focus,
     );
 
+    (aspect_ratio, camera)
+}
+
+const THREADS_AMOUNT_VARIABLE: &str = "THREADS_AMOUNT";
+const BVH_SPLIT_METHOD_VARIABLE: &str = "BVH_SPLIT_METHOD";
+const BVH_MAX_OBJECTS_PER_LEAF_VARIABLE: &str = "BVH_MAX_OBJECTS_PER_LEAF";
+const MESH_PATH_VARIABLE: &str = "MESH_PATH";
+const HEIGHT_MAP_PATH_VARIABLE: &str = "HEIGHT_MAP_PATH";
+const SCENE_VARIABLE: &str = "SCENE";
+const ENVIRONMENT_MAP_PATH_VARIABLE: &str = "ENVIRONMENT_MAP_PATH";
+const ENVIRONMENT_MAP_ROTATION_VARIABLE: &str = "ENVIRONMENT_MAP_ROTATION";
+const ENVIRONMENT_MAP_INTENSITY_VARIABLE: &str = "ENVIRONMENT_MAP_INTENSITY";
+const SUN_ELEVATION_VARIABLE: &str = "SUN_ELEVATION";
+const SUN_AZIMUTH_VARIABLE: &str = "SUN_AZIMUTH";
+const TURBIDITY_VARIABLE: &str = "TURBIDITY";
+const SKY_INTENSITY_VARIABLE: &str = "SKY_INTENSITY";
+const SAMPLES_PER_PIXEL_VARIABLE: &str = "SAMPLES_PER_PIXEL";
+const RUSSIAN_ROULETTE_DEPTH_VARIABLE: &str = "RUSSIAN_ROULETTE_DEPTH";
+const INTEGRATOR_VARIABLE: &str = "INTEGRATOR";
+const PHOTONS_VARIABLE: &str = "PHOTONS";
+const PHOTON_RADIUS_VARIABLE: &str = "PHOTON_RADIUS";
+const AMBIENT_OCCLUSION_RADIUS_VARIABLE: &str = "AMBIENT_OCCLUSION_RADIUS";
+const MAX_DISTANCE_VARIABLE: &str = "MAX_DISTANCE";
+
+/// Returns the parsed value of the environment variable `name`, or `default`
+/// if it is not set.
+///
+/// # Panics
+///
+/// The `env_var_or` function will panic if the variable can't be parsed.
+fn env_var_or<T>(name: &str, default: T) -> T
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    match env::var(name) {
+        Ok(var) => var.trim().parse().unwrap_or_else(|error| {
+            panic!(
+                "Unexpected {} environment variable format: {:?}",
+                name, error
+            )
+        }),
+        Err(_) => default,
+    }
+}
+
+fn main() {
+    let threads_amount: usize = env_var_or(THREADS_AMOUNT_VARIABLE, 1);
+    let split_method = env_var_or(BVH_SPLIT_METHOD_VARIABLE, SplitMethod::Sah);
+    let max_objects_per_leaf = env_var_or(BVH_MAX_OBJECTS_PER_LEAF_VARIABLE, 4);
+
+    // World
+    let scene_name = env::var(SCENE_VARIABLE);
+    let (mut world, photon_targets) = match scene_name.as_deref() {
+        Ok("random") | Err(_) => random_scene(false),
+        Ok("bouncing") => random_scene(true),
+        Ok("shapes") => shapes_scene(),
+        Ok("sdf") => sdf_scene(),
+        Ok("media") => media_scene(),
+        Ok("cornell") => cornell_box_scene(),
+        Ok("lights") => lights_scene(),
+        Ok("bulb") => bulb_scene(),
+        Ok("terrain") => {
+            let height_map = match env::var(HEIGHT_MAP_PATH_VARIABLE) {
+                Ok(path) => HeightMap::load_pgm(&path).unwrap_or_else(|error| panic!("{}", error)),
+                Err(_) => hills_height_map(),
+            };
+            terrain_scene(&height_map)
+        }
+        Ok(scene) => panic!(
+            "Unexpected {} environment variable value: {:?}, expected \"random\", \"bouncing\", \"shapes\", \"sdf\", \"terrain\", \"media\", \"cornell\", \"lights\" or \"bulb\"",
+            SCENE_VARIABLE, scene
+        ),
+    };
+    if let Ok(path) = env::var(MESH_PATH_VARIABLE) {
+        let mesh = Mesh::load(&path).unwrap_or_else(|error| panic!("{}", error));
+        let material = Arc::new(Lambertian::with_texture(Arc::new(VertexColor::new(
+            Color::new(0.4, 0.4, 0.4),
+        ))));
+        world.add(Arc::new(TriangleMesh::new(mesh, material)));
+    }
+
+    let world = Bvh::with_split_method(world.into_objects(), split_method, max_objects_per_leaf);
+    eprintln!("{}", world.stats());
+    let world = Arc::new(world);
+
+    // Background
+    let background: Arc<dyn Background + Send + Sync> =
+        match env::var(ENVIRONMENT_MAP_PATH_VARIABLE) {
+            Ok(path) => {
+                let image = HdrImage::load(&path).unwrap_or_else(|error| panic!("{}", error));
+                Arc::new(
+                    EnvironmentMap::new(image)
+                        .with_rotation(env_var_or(ENVIRONMENT_MAP_ROTATION_VARIABLE, 0.0))
+                        .with_intensity(env_var_or(ENVIRONMENT_MAP_INTENSITY_VARIABLE, 1.0)),
+                )
+            }
+            Err(_) if env::var(SUN_ELEVATION_VARIABLE).is_ok() => Arc::new(
+                PreethamSky::new(
+                    env_var_or(SUN_ELEVATION_VARIABLE, 45.0),
+                    env_var_or(SUN_AZIMUTH_VARIABLE, 0.0),
+                    env_var_or(TURBIDITY_VARIABLE, 3.0),
+                )
+                .with_intensity(env_var_or(SKY_INTENSITY_VARIABLE, 1.0)),
+            ),
+            Err(_) if matches!(scene_name.as_deref(), Ok("cornell") | Ok("bulb")) => {
+                Arc::new(SolidBackground::new(Color::zero()))
+            }
+            Err(_) if scene_name.as_deref() == Ok("lights") => {
+                Arc::new(SolidBackground::new(Color::new(0.01, 0.01, 0.02)))
+            }
+            Err(_) => Arc::new(GradientBackground::sky()),
+        };
+    let lights = match scene_name.as_deref() {
+        Ok("lights") => studio_lights(),
+        _ => Vec::new(),
+    };
+    let emitters = match scene_name.as_deref() {
+        Ok("cornell") => vec![cornell_box_light()],
+        Ok("bulb") => vec![bulb_light()],
+        _ => Vec::new(),
+    };
+    let scene = Arc::new(
+        Scene::new(world, background)
+            .with_lights(lights)
+            .with_emitters(emitters)
+            .with_photon_targets(photon_targets),
+    );
+
+    // Camera
+    let (aspect_ratio, camera) = match scene_name.as_deref() {
+        Ok("cornell") | Ok("bulb") => cornell_box_camera(),
+        _ => default_camera(),
+    };
+
     // Image
     let image_width = 1200;
-    let samples_per_pixel = 500;
+    let samples_per_pixel = env_var_or(SAMPLES_PER_PIXEL_VARIABLE, 500);
     let max_depth = 50;
+    let roulette_depth = env_var_or(RUSSIAN_ROULETTE_DEPTH_VARIABLE, 5);
+    // The Cornell box is about 500 times as large as the other scenes.
+    let in_box = matches!(scene_name.as_deref(), Ok("cornell") | Ok("bulb"));
+    let integrator: Arc<dyn Integrator + Send + Sync> =
+        match env::var(INTEGRATOR_VARIABLE).as_deref() {
+            Ok("path") | Err(_) => {
+                Arc::new(PathTracer::new(max_depth).with_russian_roulette(roulette_depth))
+            }
+            Ok("bidirectional") => Arc::new(
+                BidirectionalPathTracer::new(max_depth, camera.clone())
+                    .with_russian_roulette(roulette_depth),
+            ),
+            Ok("photon") => {
+                let integrator = PhotonMapper::new(
+                    max_depth,
+                    env_var_or(PHOTON_RADIUS_VARIABLE, if in_box { 5.0 } else { 0.2 }),
+                )
+                .with_russian_roulette(roulette_depth)
+                .with_photons(&scene, env_var_or(PHOTONS_VARIABLE, 5_000_000));
+                eprintln!("Stored {} caustic photons", integrator.stored_photons());
+                Arc::new(integrator)
+            }
+            Ok("ao") => Arc::new(AmbientOcclusion::new(env_var_or(
+                AMBIENT_OCCLUSION_RADIUS_VARIABLE,
+                if in_box { 100.0 } else { 1.0 },
+            ))),
+            Ok("normals") => Arc::new(Visualization::Normals),
+            Ok("depth") => Arc::new(Visualization::Depth {
+                max_distance: env_var_or(
+                    MAX_DISTANCE_VARIABLE,
+                    if in_box { 1500.0 } else { 30.0 },
+                ),
+            }),
+            Ok("uv") => Arc::new(Visualization::Uv),
+            Ok("material") => Arc::new(Visualization::MaterialId),
+            Ok("bounces") => Arc::new(Visualization::Bounces {
+                max_depth,
+                roulette_depth,
+            }),
+            Ok(integrator) => panic!(
+                "Unexpected {} environment variable value: {:?}, expected \"path\", \"bidirectional\", \"photon\", \"ao\", \"normals\", \"depth\", \"uv\", \"material\" or \"bounces\"",
+                INTEGRATOR_VARIABLE, integrator
+            ),
+        };
     let mut image = Image::new(
         camera,
         aspect_ratio,
         image_width,
         samples_per_pixel,
-        max_depth,
-        world,
+        integrator,
+        scene,
     );
 
     // Render
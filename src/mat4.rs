@@ -0,0 +1,265 @@
+use std::ops::Mul;
+
+use crate::vec3::{Point3, Vec3};
+
+/// A 4x4 matrix representing an affine transform of the 3-dimensional space.
+///
+/// Points and vectors are column vectors multiplied on the right, so the
+/// transform `a * b` applies `b` first, then `a`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Mat4 {
+    rows: [[f64; 4]; 4],
+}
+
+impl Mat4 {
+    /// Constructs a new `Mat4` from its rows.
+    pub fn new(rows: [[f64; 4]; 4]) -> Mat4 {
+        Mat4 { rows }
+    }
+
+    /// Constructs the identity matrix, which leaves points and vectors
+    /// unchanged.
+    pub fn identity() -> Mat4 {
+        Mat4::scaling(Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    /// Constructs a matrix moving points by `offset`.
+    pub fn translation(offset: Vec3) -> Mat4 {
+        Mat4::new([
+            [1.0, 0.0, 0.0, offset.x()],
+            [0.0, 1.0, 0.0, offset.y()],
+            [0.0, 0.0, 1.0, offset.z()],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Constructs a matrix scaling each axis by the matching component of
+    /// `factors`.
+    pub fn scaling(factors: Vec3) -> Mat4 {
+        Mat4::new([
+            [factors.x(), 0.0, 0.0, 0.0],
+            [0.0, factors.y(), 0.0, 0.0],
+            [0.0, 0.0, factors.z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Constructs a matrix rotating by `angle` degrees around `axis`,
+    /// counter-clockwise when the axis points toward the viewer.
+    ///
+    /// See [Rotation matrix from axis and angle on Wikipedia](https://en.wikipedia.org/wiki/Rotation_matrix#Rotation_matrix_from_axis_and_angle).
+    pub fn rotation(axis: Vec3, angle: f64) -> Mat4 {
+        let axis = axis.normalized();
+        let (x, y, z) = (axis.x(), axis.y(), axis.z());
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let c = 1.0 - cos;
+
+        Mat4::new([
+            [
+                cos + x * x * c,
+                x * y * c - z * sin,
+                x * z * c + y * sin,
+                0.0,
+            ],
+            [
+                y * x * c + z * sin,
+                cos + y * y * c,
+                y * z * c - x * sin,
+                0.0,
+            ],
+            [
+                z * x * c - y * sin,
+                z * y * c + x * sin,
+                cos + z * z * c,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Returns the transposed matrix, whose rows are the columns of the
+    /// matrix.
+    pub fn transposed(&self) -> Mat4 {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.rows[j][i];
+            }
+        }
+
+        Mat4::new(rows)
+    }
+
+    /// Returns the inverse of the matrix, or [`None`] if the matrix is
+    /// singular (e.g. a scaling by 0).
+    ///
+    /// See [Gauss-Jordan elimination on Wikipedia](https://en.wikipedia.org/wiki/Gaussian_elimination#Finding_the_inverse_of_a_matrix).
+    pub fn inverse(&self) -> Option<Mat4> {
+        let mut left = self.rows;
+        let mut right = Mat4::identity().rows;
+
+        for column in 0..4 {
+            // Pick the largest pivot for numerical stability.
+            let pivot = (column..4)
+                .max_by(|&a, &b| left[a][column].abs().total_cmp(&left[b][column].abs()))
+                .expect("there should be a pivot candidate");
+            if left[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            left.swap(column, pivot);
+            right.swap(column, pivot);
+
+            let inverse_pivot = 1.0 / left[column][column];
+            for j in 0..4 {
+                left[column][j] *= inverse_pivot;
+                right[column][j] *= inverse_pivot;
+            }
+
+            for row in 0..4 {
+                let factor = left[row][column];
+                if row == column || factor == 0.0 {
+                    continue;
+                }
+                for j in 0..4 {
+                    left[row][j] -= factor * left[column][j];
+                    right[row][j] -= factor * right[column][j];
+                }
+            }
+        }
+
+        Some(Mat4::new(right))
+    }
+
+    /// Returns the `point` transformed by the matrix.
+    pub fn transform_point(&self, point: &Point3) -> Point3 {
+        let m = &self.rows;
+        let (x, y, z) = (point.x(), point.y(), point.z());
+        let w = m[3][0] * x + m[3][1] * y + m[3][2] * z + m[3][3];
+
+        Point3::new(
+            m[0][0] * x + m[0][1] * y + m[0][2] * z + m[0][3],
+            m[1][0] * x + m[1][1] * y + m[1][2] * z + m[1][3],
+            m[2][0] * x + m[2][1] * y + m[2][2] * z + m[2][3],
+        ) / w
+    }
+
+    /// Returns the `vector` transformed by the matrix. Unlike points, vectors
+    /// are not affected by translations.
+    pub fn transform_vector(&self, vector: &Vec3) -> Vec3 {
+        let m = &self.rows;
+        let (x, y, z) = (vector.x(), vector.y(), vector.z());
+
+        Vec3::new(
+            m[0][0] * x + m[0][1] * y + m[0][2] * z,
+            m[1][0] * x + m[1][1] * y + m[1][2] * z,
+            m[2][0] * x + m[2][1] * y + m[2][2] * z,
+        )
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+
+    /// Composes two transforms: `rhs` is applied first.
+    fn mul(self, rhs: Mat4) -> Mat4 {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.rows[i][k] * rhs.rows[k][j]).sum();
+            }
+        }
+
+        Mat4::new(rows)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-12, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn translation_moves_points_but_not_vectors() {
+        let translation = Mat4::translation(Vec3::new(1.0, 2.0, 3.0));
+
+        assert_eq!(
+            translation.transform_point(&Point3::new(1.0, 1.0, 1.0)),
+            Point3::new(2.0, 3.0, 4.0)
+        );
+        assert_eq!(
+            translation.transform_vector(&Vec3::new(1.0, 1.0, 1.0)),
+            Vec3::new(1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn scaling_works() {
+        let scaling = Mat4::scaling(Vec3::new(2.0, 3.0, -1.0));
+
+        assert_eq!(
+            scaling.transform_point(&Point3::new(1.0, 1.0, 1.0)),
+            Point3::new(2.0, 3.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn rotation_works() {
+        let rotation = Mat4::rotation(Vec3::new(0.0, 0.0, 2.0), 90.0);
+
+        assert_near(
+            rotation.transform_vector(&Vec3::new(1.0, 0.0, 0.0)),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        assert_near(
+            rotation.transform_vector(&Vec3::new(0.0, 1.0, 0.0)),
+            Vec3::new(-1.0, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn mul_applies_right_hand_side_first() {
+        let transform =
+            Mat4::translation(Vec3::new(1.0, 0.0, 0.0)) * Mat4::scaling(Vec3::new(2.0, 2.0, 2.0));
+
+        assert_eq!(
+            transform.transform_point(&Point3::new(1.0, 1.0, 1.0)),
+            Point3::new(3.0, 2.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn transposed_works() {
+        let matrix = Mat4::translation(Vec3::new(1.0, 2.0, 3.0));
+
+        assert_eq!(matrix.transposed().rows[3], [1.0, 2.0, 3.0, 1.0]);
+        assert_eq!(matrix.transposed().transposed(), matrix);
+    }
+
+    #[test]
+    fn inverse_works() {
+        let matrix = Mat4::translation(Vec3::new(1.0, -2.0, 3.0))
+            * Mat4::rotation(Vec3::new(1.0, 1.0, 0.0), 30.0)
+            * Mat4::scaling(Vec3::new(2.0, 0.5, 4.0));
+        let inverse = matrix.inverse().expect("matrix should be invertible");
+
+        let point = Point3::new(0.3, -1.2, 5.0);
+        assert_near(
+            inverse.transform_point(&matrix.transform_point(&point)),
+            point,
+        );
+        let identity = matrix * inverse;
+        for (row, expected) in identity.rows.iter().zip(&Mat4::identity().rows) {
+            for (value, expected) in row.iter().zip(expected) {
+                assert!((value - expected).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_of_singular_matrix_is_none() {
+        assert!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+    }
+}
@@ -23,20 +23,20 @@ impl Material for Dielectrics {
 
         let unit_direction = ray_in.direction().normalized();
 
-        let cos_theta = -unit_direction.dot(&record.normal).min(1.0);
+        let cos_theta = -unit_direction.dot(&record.shading_normal).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
         let cannot_recraft = refraction_ratio * sin_theta > 1.0;
         let direction =
             if cannot_recraft || reflectance(cos_theta, refraction_ratio) > random::random() {
-                unit_direction.reflected(&record.normal)
+                unit_direction.reflected(&record.shading_normal)
             } else {
-                unit_direction.refracted(&record.normal, refraction_ratio)
+                unit_direction.refracted(&record.shading_normal, refraction_ratio)
             };
 
         Some((
             Color::new(1.0, 1.0, 1.0),
-            Ray::new(record.intersection_point, direction),
+            Ray::with_time(record.intersection_point, direction, ray_in.time()),
         ))
     }
 }
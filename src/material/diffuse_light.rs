@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    ray::Ray,
+    texture::{SolidColor, Texture},
+};
+
+use super::Material;
+
+/// A material emitting light evenly in every direction from the front face of
+/// a surface, and scattering nothing.
+pub struct DiffuseLight {
+    emission: Arc<dyn Texture + Sync + Send>,
+}
+
+impl DiffuseLight {
+    /// Constructs a new DiffuseLight material emitting the `color` radiance.
+    pub fn new(color: Color) -> DiffuseLight {
+        DiffuseLight::with_texture(Arc::new(SolidColor::new(color)))
+    }
+
+    /// Constructs a new DiffuseLight material whose emitted radiance is given
+    /// by a texture.
+    pub fn with_texture(emission: Arc<dyn Texture + Sync + Send>) -> DiffuseLight {
+        DiffuseLight { emission }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _ray_in: &Ray, _record: &HitRecord) -> Option<(Color, Ray)> {
+        None
+    }
+
+    fn emitted(&self, record: &HitRecord) -> Color {
+        if record.front_face {
+            self.emission.value(record)
+        } else {
+            Color::zero()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hittable::{Hittable, Quad},
+        vec3::{Point3, Vec3},
+    };
+
+    use super::*;
+
+    #[test]
+    fn emitted_only_from_the_front_face() {
+        let light = Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
+        let quad = Quad::new(
+            Point3::new(-1.0, 0.0, -1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            light,
+        );
+        let from_below = Ray::new(Point3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let from_above = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+
+        let below = quad.try_hit(&from_below, 0.0..=f64::INFINITY).unwrap();
+        let above = quad.try_hit(&from_above, 0.0..=f64::INFINITY).unwrap();
+
+        assert_eq!(below.material.emitted(&below), Color::new(4.0, 4.0, 4.0));
+        assert_eq!(above.material.emitted(&above), Color::zero());
+        assert!(below.material.scatter(&from_below, &below).is_none());
+    }
+}
@@ -0,0 +1,116 @@
+use std::f64::consts::PI;
+
+use crate::{color::Color, hittable::HitRecord, random::random, ray::Ray, vec3::Vec3};
+
+use super::Material;
+
+/// The phase function of a participating medium scattering light mostly
+/// forward (e.g. clouds) or backward.
+///
+/// See [Henyey-Greenstein phase function](https://www.pbr-book.org/3ed-2018/Volume_Scattering/Phase_Functions#TheHenyeyndashGreensteinPhaseFunction)
+/// in Physically Based Rendering.
+pub struct HenyeyGreenstein {
+    albedo: Color,
+    asymmetry: f64,
+}
+
+impl HenyeyGreenstein {
+    /// Constructs a new HenyeyGreenstein material.
+    ///
+    /// `asymmetry` is the average cosine of the scattering angle, in (-1, 1):
+    /// positive values scatter forward, negative ones backward and 0 equally
+    /// in every direction.
+    pub fn new(albedo: Color, asymmetry: f64) -> HenyeyGreenstein {
+        HenyeyGreenstein {
+            albedo,
+            asymmetry: asymmetry.clamp(-0.999, 0.999),
+        }
+    }
+
+    /// Returns the cosine of a scattering angle sampled from the phase
+    /// function, the angle between the incoming and the scattered directions.
+    fn sample_cos_theta(&self) -> f64 {
+        let g = self.asymmetry;
+        let u = random();
+        if g.abs() < 1e-3 {
+            return 1.0 - 2.0 * u;
+        }
+
+        let square = (1.0 - g * g) / (1.0 - g + 2.0 * g * u);
+        ((1.0 + g * g - square * square) / (2.0 * g)).clamp(-1.0, 1.0)
+    }
+
+    /// Returns the phase function for a scattering angle whose cosine is
+    /// `cos_theta`. Its integral over the sphere is 1.
+    fn phase(&self, cos_theta: f64) -> f64 {
+        let g = self.asymmetry;
+        let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+
+        (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, ray_in: &Ray, record: &HitRecord) -> Option<(Color, Ray)> {
+        let forward = ray_in.direction().normalized();
+        let (tangent, bitangent) = forward.orthonormal_basis();
+
+        let cos_theta = self.sample_cos_theta();
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random();
+        let direction: Vec3 = cos_theta * forward
+            + sin_theta * phi.cos() * tangent
+            + sin_theta * phi.sin() * bitangent;
+
+        Some((
+            self.albedo,
+            Ray::with_time(record.intersection_point, direction, ray_in.time()),
+        ))
+    }
+
+    fn eval(&self, ray_in: &Ray, _record: &HitRecord, direction: Vec3) -> Color {
+        let cos_theta = ray_in.direction().normalized().dot(&direction.normalized());
+
+        self.albedo * self.phase(cos_theta)
+    }
+
+    fn pdf(&self, ray_in: &Ray, _record: &HitRecord, direction: Vec3) -> f64 {
+        let cos_theta = ray_in.direction().normalized().dot(&direction.normalized());
+
+        self.phase(cos_theta)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sampled_angles_average_to_the_asymmetry() {
+        for &asymmetry in &[-0.6, 0.0, 0.3, 0.9] {
+            let phase = HenyeyGreenstein::new(Color::zero(), asymmetry);
+            let samples = 100_000;
+
+            let average =
+                (0..samples).map(|_| phase.sample_cos_theta()).sum::<f64>() / samples as f64;
+
+            assert!((average - asymmetry).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn phase_integrates_to_one() {
+        let phase = HenyeyGreenstein::new(Color::zero(), 0.7);
+        let steps = 100_000;
+
+        // The integral over the sphere of a function of θ only.
+        let integral = (0..steps)
+            .map(|i| {
+                let theta = PI * (i as f64 + 0.5) / steps as f64;
+                phase.phase(theta.cos()) * 2.0 * PI * theta.sin() * PI / steps as f64
+            })
+            .sum::<f64>();
+
+        assert!((integral - 1.0).abs() < 1e-4);
+    }
+}
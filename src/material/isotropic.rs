@@ -0,0 +1,50 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    ray::Ray,
+    texture::{SolidColor, Texture},
+    vec3::Vec3,
+};
+
+use super::Material;
+
+/// The phase function of a participating medium scattering light equally in
+/// every direction.
+pub struct Isotropic {
+    albedo: Arc<dyn Texture + Sync + Send>,
+}
+
+impl Isotropic {
+    /// Constructs a new Isotropic material.
+    pub fn new(color: Color) -> Isotropic {
+        Isotropic::with_texture(Arc::new(SolidColor::new(color)))
+    }
+
+    /// Constructs a new Isotropic material whose albedo is given by a texture.
+    pub fn with_texture(albedo: Arc<dyn Texture + Sync + Send>) -> Isotropic {
+        Isotropic { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, ray_in: &Ray, record: &HitRecord) -> Option<(Color, Ray)> {
+        Some((
+            self.albedo.value(record),
+            Ray::with_time(
+                record.intersection_point,
+                Vec3::random_normalized(),
+                ray_in.time(),
+            ),
+        ))
+    }
+
+    fn eval(&self, _ray_in: &Ray, record: &HitRecord, _direction: Vec3) -> Color {
+        self.albedo.value(record) / (4.0 * PI)
+    }
+
+    fn pdf(&self, _ray_in: &Ray, _record: &HitRecord, _direction: Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+}
@@ -1,30 +1,126 @@
-use crate::{color::Color, hittable::HitRecord, ray::Ray, vec3::Vec3};
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    color::Color,
+    hittable::HitRecord,
+    ray::Ray,
+    texture::{SolidColor, Texture},
+    vec3::Vec3,
+};
 
 use super::Material;
 
 /// A simple diffuse material.
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Arc<dyn Texture + Sync + Send>,
 }
 
 impl Lambertian {
     /// Constructs a new Lambertian (diffuse) material.
     pub fn new(color: Color) -> Lambertian {
-        Lambertian { albedo: color }
+        Lambertian::with_texture(Arc::new(SolidColor::new(color)))
+    }
+
+    /// Constructs a new Lambertian (diffuse) material whose albedo is given by
+    /// a texture.
+    pub fn with_texture(albedo: Arc<dyn Texture + Sync + Send>) -> Lambertian {
+        Lambertian { albedo }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray_in: &Ray, record: &HitRecord) -> Option<(Color, Ray)> {
-        let mut scatter_direction = record.normal + Vec3::random_normalized();
+    fn scatter(&self, ray_in: &Ray, record: &HitRecord) -> Option<(Color, Ray)> {
+        let mut scatter_direction = record.shading_normal + Vec3::random_normalized();
 
         if scatter_direction.is_near_zero() {
-            scatter_direction = record.normal;
+            scatter_direction = record.shading_normal;
         }
 
         Some((
-            self.albedo,
-            Ray::new(record.intersection_point, scatter_direction),
+            self.albedo.value(record),
+            Ray::with_time(record.intersection_point, scatter_direction, ray_in.time()),
         ))
     }
+
+    /// Returns `albedo / π` times the cosine of the angle between the
+    /// `direction` and the normal, or black from below the surface.
+    fn eval(&self, _ray_in: &Ray, record: &HitRecord, direction: Vec3) -> Color {
+        let direction = direction.normalized();
+        let cos_theta = direction.dot(&record.shading_normal);
+        if cos_theta <= 0.0 || direction.dot(&record.normal) <= 0.0 {
+            return Color::zero();
+        }
+
+        self.albedo.value(record) * cos_theta / PI
+    }
+
+    /// Returns the cosine of the angle between the `direction` and the normal
+    /// divided by π, as scattered directions are cosine-distributed.
+    fn pdf(&self, _ray_in: &Ray, record: &HitRecord, direction: Vec3) -> f64 {
+        let cos_theta = direction.normalized().dot(&record.shading_normal);
+
+        cos_theta.max(0.0) / PI
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hittable::{Hittable, Sphere},
+        vec3::Point3,
+    };
+
+    use super::*;
+
+    #[test]
+    fn eval_follows_the_cosine() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let sphere = Sphere::new(Point3::zero(), 1.0, material);
+        let ray = Ray::new(Point3::new(0.0, 3.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let record = sphere.try_hit(&ray, 0.0..=f64::INFINITY).unwrap();
+
+        let straight = record
+            .material
+            .eval(&ray, &record, Vec3::new(0.0, 2.0, 0.0));
+        let slanted = record
+            .material
+            .eval(&ray, &record, Vec3::new(1.0, 1.0, 0.0));
+        let below = record
+            .material
+            .eval(&ray, &record, Vec3::new(1.0, -0.1, 0.0));
+
+        assert_eq!(straight, Color::new(0.5, 0.5, 0.5) / PI);
+        assert!((slanted.x() - 0.5 / PI * 0.5_f64.sqrt()).abs() < 1e-12);
+        assert_eq!(below, Color::zero());
+    }
+
+    #[test]
+    fn scattered_directions_follow_the_pdf() {
+        let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let sphere = Sphere::new(Point3::zero(), 1.0, material);
+        let ray = Ray::new(Point3::new(0.0, 3.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let record = sphere.try_hit(&ray, 0.0..=f64::INFINITY).unwrap();
+        let samples = 100_000;
+
+        // Directions within 60° of the normal cover 2π (1 - cos 60°) sr, in
+        // which the pdf integrates to sin² 60°.
+        let inside = (0..samples)
+            .filter(|_| {
+                let (_, scattered) = record.material.scatter(&ray, &record).unwrap();
+                scattered.direction().normalized().y() > 0.5
+            })
+            .count();
+
+        assert!((inside as f64 / samples as f64 - 0.75).abs() < 0.01);
+        assert_eq!(
+            record.material.pdf(&ray, &record, Vec3::new(0.0, 1.0, 0.0)),
+            1.0 / PI
+        );
+        assert_eq!(
+            record
+                .material
+                .pdf(&ray, &record, Vec3::new(0.0, -1.0, 0.0)),
+            0.0
+        );
+    }
 }
@@ -20,11 +20,15 @@ impl Metal {
 
 impl Material for Metal {
     fn scatter(&self, ray_in: &Ray, record: &HitRecord) -> Option<(Color, Ray)> {
-        let reflected = ray_in.direction().normalized().reflected(&record.normal);
+        let reflected = ray_in
+            .direction()
+            .normalized()
+            .reflected(&record.shading_normal);
 
-        let scattered = Ray::new(
+        let scattered = Ray::with_time(
             record.intersection_point,
             reflected + self.fuzz * Vec3::random_in_unit_sphere(),
+            ray_in.time(),
         );
         if scattered.direction().dot(&record.normal) > 0.0 {
             let attenuation = self.albedo;
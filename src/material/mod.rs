@@ -1,10 +1,16 @@
 pub use dielectrics::Dielectrics;
+pub use diffuse_light::DiffuseLight;
+pub use henyey_greenstein::HenyeyGreenstein;
+pub use isotropic::Isotropic;
 pub use lambertian::Lambertian;
 pub use metal::Metal;
 
-use crate::{color::Color, hittable::HitRecord, ray::Ray};
+use crate::{color::Color, hittable::HitRecord, ray::Ray, vec3::Vec3};
 
 mod dielectrics;
+mod diffuse_light;
+mod henyey_greenstein;
+mod isotropic;
 mod lambertian;
 mod metal;
 
@@ -12,4 +18,31 @@ mod metal;
 pub trait Material {
     /// Returns the attenuation and scattered ray.
     fn scatter(&self, ray_in: &Ray, record: &HitRecord) -> Option<(Color, Ray)>;
+
+    /// Returns the radiance emitted by the material at the point of
+    /// intersection of the `record`. Materials emit no light by default.
+    fn emitted(&self, _record: &HitRecord) -> Color {
+        Color::zero()
+    }
+
+    /// Returns the fraction of the radiance coming from `direction` that is
+    /// scattered back along `ray_in`, including the cosine of the angle with
+    /// the surface. This is what lights coming from a single direction are
+    /// evaluated with.
+    ///
+    /// Perfectly specular materials (mirrors, glass) scatter light from a
+    /// single direction only, and return black by default.
+    fn eval(&self, _ray_in: &Ray, _record: &HitRecord, _direction: Vec3) -> Color {
+        Color::zero()
+    }
+
+    /// Returns the probability density with respect to solid angle of
+    /// [`Material::scatter`] scattering `ray_in` toward `direction`.
+    ///
+    /// The scattered directions of perfectly specular materials can't be
+    /// given a density, so they return 0 by default and aren't sampled toward
+    /// lights.
+    fn pdf(&self, _ray_in: &Ray, _record: &HitRecord, _direction: Vec3) -> f64 {
+        0.0
+    }
 }
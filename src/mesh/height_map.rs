@@ -0,0 +1,342 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use super::MeshError;
+
+/// A regular grid of heights, e.g. the elevation of a terrain.
+///
+/// Heights are stored row by row: `width` heights along X for each of the
+/// `depth` rows along Z.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightMap {
+    width: usize,
+    depth: usize,
+    heights: Vec<f64>,
+}
+
+impl HeightMap {
+    /// Constructs a new `HeightMap` of `width` by `depth` heights, stored row
+    /// by row.
+    ///
+    /// # Panics
+    ///
+    /// The `new` function will panic if the grid is smaller than 2 by 2 or if
+    /// there isn't `width * depth` heights.
+    pub fn new(width: usize, depth: usize, heights: Vec<f64>) -> HeightMap {
+        assert!(
+            width >= 2 && depth >= 2,
+            "A height map must be at least 2 by 2"
+        );
+        assert_eq!(
+            heights.len(),
+            width * depth,
+            "A {} by {} height map needs {} heights",
+            width,
+            depth,
+            width * depth
+        );
+
+        HeightMap {
+            width,
+            depth,
+            heights,
+        }
+    }
+
+    /// Constructs a new `HeightMap` of `width` by `depth` heights, whose height
+    /// at the `(x, z)` grid coordinates is `height(x, z)`.
+    ///
+    /// # Panics
+    ///
+    /// The `from_fn` function will panic if the grid is smaller than 2 by 2.
+    pub fn from_fn<F>(width: usize, depth: usize, height: F) -> HeightMap
+    where
+        F: Fn(usize, usize) -> f64,
+    {
+        let heights = (0..depth)
+            .flat_map(|z| (0..width).map(move |x| (x, z)))
+            .map(|(x, z)| height(x, z))
+            .collect();
+
+        HeightMap::new(width, depth, heights)
+    }
+
+    /// Loads a height map from a grayscale image in PGM format.
+    ///
+    /// See [`HeightMap::read_pgm`].
+    pub fn load_pgm<P: AsRef<Path>>(path: P) -> Result<HeightMap, MeshError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|source| MeshError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        HeightMap::read_pgm(BufReader::new(file), path)
+    }
+
+    /// Reads a height map in PGM format from a `reader`. `path` is only used to
+    /// report errors.
+    ///
+    /// Both the plain (`P2`) and raw (`P5`) variants are supported. Gray
+    /// levels are mapped to heights in [0, 1], the top row of the image being
+    /// the first row of the map.
+    ///
+    /// See [Netpbm on Wikipedia](https://en.wikipedia.org/wiki/Netpbm).
+    pub fn read_pgm<R: Read>(mut reader: R, path: &Path) -> Result<HeightMap, MeshError> {
+        let mut content = Vec::new();
+        reader
+            .read_to_end(&mut content)
+            .map_err(|source| MeshError::Io {
+                path: path.to_owned(),
+                source,
+            })?;
+        let mut tokens = Tokens {
+            content: &content,
+            offset: 0,
+            line: 1,
+        };
+        let parse_error = |line: usize, message: String| MeshError::Parse {
+            path: path.to_owned(),
+            line,
+            message,
+        };
+
+        let binary = match tokens.next() {
+            Some("P2") => false,
+            Some("P5") => true,
+            _ => {
+                return Err(parse_error(
+                    1,
+                    String::from("not a PGM file, expected `P2` or `P5`"),
+                ))
+            }
+        };
+
+        let mut header = [0; 3];
+        for (value, name) in header.iter_mut().zip(&["width", "height", "maximum value"]) {
+            *value = tokens
+                .next_number()
+                .map_err(|message| parse_error(tokens.line, message))?
+                .filter(|&value| value > 0)
+                .ok_or_else(|| parse_error(tokens.line, format!("invalid {}", name)))?;
+        }
+        let [width, depth, max_value] = header;
+        if width < 2 || depth < 2 {
+            return Err(parse_error(
+                tokens.line,
+                format!(
+                    "the image is {} by {}, at least 2 by 2 is needed",
+                    width, depth
+                ),
+            ));
+        }
+        if max_value > u16::MAX as usize {
+            return Err(parse_error(
+                tokens.line,
+                format!("invalid maximum value {}", max_value),
+            ));
+        }
+
+        let mut heights = Vec::with_capacity(width * depth);
+        if binary {
+            // A single whitespace separates the header from the raster.
+            let raster = &content[(tokens.offset + 1).min(content.len())..];
+            let sample_size = if max_value < 256 { 1 } else { 2 };
+            let expected = width * depth * sample_size;
+            if raster.len() < expected {
+                return Err(MeshError::ParseBinary {
+                    path: path.to_owned(),
+                    offset: content.len() as u64,
+                    message: format!(
+                        "unexpected end of file, expected {} bytes of raster",
+                        expected
+                    ),
+                });
+            }
+
+            for sample in raster[..expected].chunks_exact(sample_size) {
+                let value = sample
+                    .iter()
+                    .fold(0, |value, &byte| (value << 8) | byte as usize);
+                heights.push(value as f64 / max_value as f64);
+            }
+        } else {
+            for _ in 0..width * depth {
+                let value = tokens
+                    .next_number()
+                    .map_err(|message| parse_error(tokens.line, message))?
+                    .ok_or_else(|| {
+                        parse_error(
+                            tokens.line,
+                            format!("expected {} gray levels", width * depth),
+                        )
+                    })?;
+                if value > max_value {
+                    return Err(parse_error(
+                        tokens.line,
+                        format!("gray level {} is above the maximum {}", value, max_value),
+                    ));
+                }
+                heights.push(value as f64 / max_value as f64);
+            }
+        }
+
+        Ok(HeightMap::new(width, depth, heights))
+    }
+
+    /// Returns the number of heights along X.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of heights along Z.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the height at the `(x, z)` grid coordinates.
+    pub fn height(&self, x: usize, z: usize) -> f64 {
+        self.heights[z * self.width + x]
+    }
+
+    /// Returns the smallest and the largest heights.
+    pub fn range(&self) -> (f64, f64) {
+        self.heights
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &height| {
+                (min.min(height), max.max(height))
+            })
+    }
+}
+
+/// The whitespace-separated tokens of a Netpbm file, skipping comments.
+struct Tokens<'a> {
+    content: &'a [u8],
+    /// The offset of the byte following the last token.
+    offset: usize,
+    /// The line of the last token.
+    line: usize,
+}
+
+impl<'a> Tokens<'a> {
+    /// Returns the next token, or [`None`] at the end of the content.
+    fn next(&mut self) -> Option<&'a str> {
+        // Skip whitespace and comments, which go to the end of the line.
+        let mut in_comment = false;
+        while let Some(&byte) = self.content.get(self.offset) {
+            match byte {
+                b'\n' => {
+                    self.line += 1;
+                    in_comment = false;
+                }
+                b'#' => in_comment = true,
+                _ if in_comment || byte.is_ascii_whitespace() => {}
+                _ => break,
+            }
+            self.offset += 1;
+        }
+
+        let start = self.offset;
+        while self
+            .content
+            .get(self.offset)
+            .is_some_and(|byte| !byte.is_ascii_whitespace() && *byte != b'#')
+        {
+            self.offset += 1;
+        }
+
+        if start == self.offset {
+            None
+        } else {
+            std::str::from_utf8(&self.content[start..self.offset]).ok()
+        }
+    }
+
+    /// Returns the next token parsed as a number, or [`None`] at the end of
+    /// the content.
+    fn next_number(&mut self) -> Result<Option<usize>, String> {
+        match self.next() {
+            Some(token) => token
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("invalid number `{}`", token)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read(content: &[u8]) -> Result<HeightMap, MeshError> {
+        HeightMap::read_pgm(content, Path::new("test.pgm"))
+    }
+
+    #[test]
+    fn from_fn_works() {
+        let height_map = HeightMap::from_fn(3, 2, |x, z| (x + 10 * z) as f64);
+
+        assert_eq!(height_map.width(), 3);
+        assert_eq!(height_map.depth(), 2);
+        assert_eq!(height_map.height(2, 1), 12.0);
+        assert_eq!(height_map.range(), (0.0, 12.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_with_wrong_heights_count_panics() {
+        HeightMap::new(2, 2, vec![0.0; 3]);
+    }
+
+    #[test]
+    fn read_plain_pgm() {
+        let height_map = read(b"P2\n# A comment\n3 2\n4\n0 1 2\n3 4 0\n").unwrap();
+
+        assert_eq!(
+            height_map,
+            HeightMap::new(3, 2, vec![0.0, 0.25, 0.5, 0.75, 1.0, 0.0])
+        );
+    }
+
+    #[test]
+    fn read_raw_pgm() {
+        let height_map = read(b"P5 2 2 255\n\x00\x33\xcc\xff").unwrap();
+
+        assert_eq!(height_map, HeightMap::new(2, 2, vec![0.0, 0.2, 0.8, 1.0]));
+    }
+
+    #[test]
+    fn read_raw_pgm_with_16_bits_samples() {
+        let height_map = read(b"P5 2 2 65535\n\x00\x00\xff\xff\x00\x00\xff\xff").unwrap();
+
+        assert_eq!(height_map, HeightMap::new(2, 2, vec![0.0, 1.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn read_truncated_raw_pgm_reports_offset() {
+        let error = read(b"P5 2 2 255\n\x00\x33").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "test.pgm: byte 13: unexpected end of file, expected 4 bytes of raster"
+        );
+    }
+
+    #[test]
+    fn read_plain_pgm_with_invalid_level_reports_line() {
+        let error = read(b"P2\n2 2\n4\n0 1\n2 five\n").unwrap_err();
+
+        assert_eq!(error.to_string(), "test.pgm:5: invalid number `five`");
+    }
+
+    #[test]
+    fn read_pgm_without_magic_number() {
+        let error = read(b"P3\n2 2\n4\n").unwrap_err();
+
+        assert!(matches!(error, MeshError::Parse { line: 1, .. }));
+    }
+}
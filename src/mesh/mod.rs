@@ -0,0 +1,392 @@
+//! # Mesh module
+//!
+//! Triangle meshes and loaders for the file formats they are stored in.
+
+use std::{
+    error::Error,
+    fmt, io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    color::Color,
+    vec3::{Point3, Vec3},
+};
+
+mod height_map;
+mod obj;
+mod ply;
+
+pub use height_map::HeightMap;
+
+/// A corner of a mesh triangle, made of indices into the [`Mesh`] vertex
+/// attributes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MeshVertex {
+    /// The index of the vertex position in [`Mesh::positions`].
+    pub position: usize,
+    /// The index of the vertex normal in [`Mesh::normals`], if any.
+    pub normal: Option<usize>,
+    /// The index of the vertex texture coordinates in [`Mesh::uvs`], if any.
+    pub uv: Option<usize>,
+    /// The index of the vertex color in [`Mesh::colors`], if any.
+    pub color: Option<usize>,
+}
+
+/// A named range of triangles of a [`Mesh`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeshGroup {
+    /// The name of the group.
+    pub name: String,
+    /// The range of the group triangles in [`Mesh::triangles`].
+    pub triangles: Range<usize>,
+}
+
+/// A triangle mesh.
+///
+/// Vertex attributes are stored once and shared by the triangles indexing
+/// them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    /// The vertex positions.
+    pub positions: Vec<Point3>,
+    /// The vertex normals.
+    pub normals: Vec<Vec3>,
+    /// The vertex texture coordinates.
+    pub uvs: Vec<(f64, f64)>,
+    /// The vertex colors.
+    pub colors: Vec<Color>,
+    /// The triangles, whose vertices are in counter-clockwise order when seen
+    /// from the outside.
+    pub triangles: Vec<[MeshVertex; 3]>,
+    /// The groups the triangles are split into. Every triangle belongs to a
+    /// group.
+    pub groups: Vec<MeshGroup>,
+}
+
+impl Mesh {
+    /// Constructs a new empty `Mesh`.
+    pub fn new() -> Mesh {
+        Mesh::default()
+    }
+
+    /// Loads a mesh from a file, whose format is guessed from its extension:
+    /// `.obj` for Wavefront OBJ and `.ply` for PLY.
+    ///
+    /// See [`Mesh::load_obj`] and [`Mesh::load_ply`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Mesh, MeshError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+
+        match extension.as_deref() {
+            Some("obj") => Mesh::load_obj(path),
+            Some("ply") => Mesh::load_ply(path),
+            _ => Err(MeshError::UnsupportedFormat {
+                path: path.to_owned(),
+            }),
+        }
+    }
+
+    /// Adds a polygon to the mesh, splitting it into triangles. The polygon
+    /// vertices must be in counter-clockwise order when seen from the outside.
+    ///
+    /// The triangles are added to the last group, which is created if there is
+    /// none yet.
+    pub fn add_polygon(&mut self, polygon: &[MeshVertex]) {
+        if self.groups.is_empty() {
+            self.start_group("default");
+        }
+
+        let positions: Vec<Point3> = polygon
+            .iter()
+            .map(|vertex| self.positions[vertex.position])
+            .collect();
+        for [a, b, c] in triangulate(&positions) {
+            self.triangles.push([polygon[a], polygon[b], polygon[c]]);
+        }
+
+        let end = self.triangles.len();
+        if let Some(group) = self.groups.last_mut() {
+            group.triangles.end = end;
+        }
+    }
+
+    /// Starts a new group named `name`. Triangles added afterwards belong to
+    /// it.
+    ///
+    /// The last group is replaced if it doesn't contain any triangle yet.
+    pub fn start_group(&mut self, name: &str) {
+        let start = self.triangles.len();
+        if let Some(group) = self.groups.last_mut() {
+            if group.triangles.is_empty() {
+                group.name = name.to_owned();
+                return;
+            }
+        }
+
+        self.groups.push(MeshGroup {
+            name: name.to_owned(),
+            triangles: start..start,
+        });
+    }
+}
+
+/// Splits a simple polygon (convex or not) into triangles using ear clipping.
+/// Returns the triangles as indices into `polygon`, with the same winding as
+/// the polygon.
+///
+/// Degenerate polygons, for which no ear can be found, are split as a fan.
+///
+/// See [polygon triangulation on Wikipedia](https://en.wikipedia.org/wiki/Polygon_triangulation#Ear_clipping_method).
+pub fn triangulate(polygon: &[Point3]) -> Vec<[usize; 3]> {
+    if polygon.len() < 3 {
+        return Vec::new();
+    }
+
+    // Project the polygon on the plane it is the most aligned with. The sum of
+    // the cross products of consecutive vertices is along the polygon normal.
+    let mut normal = Vec3::zero();
+    for (i, current) in polygon.iter().enumerate() {
+        normal += current.cross(&polygon[(i + 1) % polygon.len()]);
+    }
+    let (x_axis, y_axis, orientation) = {
+        let (x, y, z) = (normal.x().abs(), normal.y().abs(), normal.z().abs());
+        let drop_axis = if x > y && x > z {
+            0
+        } else if y > z {
+            1
+        } else {
+            2
+        };
+
+        // Keep the (x, y, drop) axes right-handed so counter-clockwise
+        // polygons stay counter-clockwise once projected.
+        (
+            (drop_axis + 1) % 3,
+            (drop_axis + 2) % 3,
+            normal[drop_axis].signum(),
+        )
+    };
+    let points: Vec<(f64, f64)> = polygon
+        .iter()
+        .map(|point| (point[x_axis], point[y_axis]))
+        .collect();
+
+    // Twice the signed area of the (a, b, c) triangle, positive when it has the
+    // same winding as the polygon.
+    let area = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (points[a], points[b], points[c]);
+        orientation * ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0))
+    };
+
+    let mut remaining: Vec<usize> = (0..polygon.len()).collect();
+    let mut triangles = Vec::with_capacity(polygon.len() - 2);
+
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let ear = (0..count).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + count - 1) % count],
+                remaining[i],
+                remaining[(i + 1) % count],
+            );
+
+            area(a, b, c) > 0.0
+                && remaining.iter().all(|&p| {
+                    p == a
+                        || p == b
+                        || p == c
+                        || area(a, b, p) < 0.0
+                        || area(b, c, p) < 0.0
+                        || area(c, a, p) < 0.0
+                })
+        });
+
+        match ear {
+            Some(i) => {
+                triangles.push([
+                    remaining[(i + count - 1) % count],
+                    remaining[i],
+                    remaining[(i + 1) % count],
+                ]);
+                remaining.remove(i);
+            }
+            None => break,
+        }
+    }
+
+    for i in 1..remaining.len() - 1 {
+        triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+    }
+
+    triangles
+}
+
+/// An error that occurred while loading a mesh file.
+#[derive(Debug)]
+pub enum MeshError {
+    /// The file could not be read.
+    Io { path: PathBuf, source: io::Error },
+    /// The content of the text file is invalid.
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    /// The content of the binary file is invalid.
+    ParseBinary {
+        path: PathBuf,
+        offset: u64,
+        message: String,
+    },
+    /// The format of the file is not supported.
+    UnsupportedFormat { path: PathBuf },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Io { path, source } => {
+                write!(f, "{}: {}", path.display(), source)
+            }
+            MeshError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+            MeshError::ParseBinary {
+                path,
+                offset,
+                message,
+            } => write!(f, "{}: byte {}: {}", path.display(), offset, message),
+            MeshError::UnsupportedFormat { path } => {
+                write!(f, "{}: unsupported mesh format", path.display())
+            }
+        }
+    }
+}
+
+impl Error for MeshError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MeshError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns the area of the triangles, which are indices into `polygon`.
+    fn area(polygon: &[Point3], triangles: &[[usize; 3]]) -> f64 {
+        triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                0.5 * (polygon[b] - polygon[a])
+                    .cross(&(polygon[c] - polygon[a]))
+                    .length()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn triangulate_triangle() {
+        let polygon = [
+            Point3::zero(),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ];
+
+        assert_eq!(triangulate(&polygon), vec![[0, 1, 2]]);
+    }
+
+    #[test]
+    fn triangulate_too_small_polygon() {
+        assert!(triangulate(&[Point3::zero(), Point3::new(1.0, 0.0, 0.0)]).is_empty());
+    }
+
+    #[test]
+    fn triangulate_square() {
+        let polygon = [
+            Point3::zero(),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ];
+        let triangles = triangulate(&polygon);
+
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(area(&polygon, &triangles), 1.0);
+    }
+
+    #[test]
+    fn triangulate_concave_polygon_keeps_winding() {
+        // An L shape in the XZ plane, seen counter-clockwise from below.
+        let polygon = [
+            Point3::zero(),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(1.0, 0.0, 2.0),
+            Point3::new(0.0, 0.0, 2.0),
+        ];
+        let triangles = triangulate(&polygon);
+
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(area(&polygon, &triangles), 3.0);
+        for &[a, b, c] in &triangles {
+            let normal = (polygon[b] - polygon[a]).cross(&(polygon[c] - polygon[a]));
+            assert!(normal.y() < 0.0);
+        }
+    }
+
+    #[test]
+    fn load_unsupported_format() {
+        let error = Mesh::load("mesh.stl").unwrap_err();
+
+        assert!(matches!(error, MeshError::UnsupportedFormat { .. }));
+    }
+
+    #[test]
+    fn add_polygon_creates_default_group() {
+        let mut mesh = Mesh::new();
+        mesh.positions = vec![
+            Point3::zero(),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ];
+        let polygon: Vec<MeshVertex> = (0..4)
+            .map(|position| MeshVertex {
+                position,
+                normal: None,
+                uv: None,
+                color: None,
+            })
+            .collect();
+
+        mesh.add_polygon(&polygon);
+        mesh.start_group("second");
+        mesh.add_polygon(&polygon[..3]);
+
+        assert_eq!(
+            mesh.groups,
+            vec![
+                MeshGroup {
+                    name: String::from("default"),
+                    triangles: 0..2,
+                },
+                MeshGroup {
+                    name: String::from("second"),
+                    triangles: 2..3,
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::{FromStr, SplitWhitespace},
+};
+
+use crate::vec3::{Point3, Vec3};
+
+use super::{Mesh, MeshError, MeshVertex};
+
+impl Mesh {
+    /// Loads a mesh from a Wavefront OBJ file.
+    ///
+    /// See [`Mesh::read_obj`].
+    pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<Mesh, MeshError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|source| MeshError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        Mesh::read_obj(BufReader::new(file), path)
+    }
+
+    /// Reads a mesh in Wavefront OBJ format from a `reader`. `path` is only
+    /// used to report errors.
+    ///
+    /// Vertex positions (`v`), normals (`vn`), texture coordinates (`vt`),
+    /// polygonal faces (`f`) and groups (`g` and `o`) are supported. Faces with
+    /// more than three vertices are split into triangles. Other statements
+    /// (materials, smoothing groups, lines...) are ignored.
+    ///
+    /// See [Wavefront .obj file on Wikipedia](https://en.wikipedia.org/wiki/Wavefront_.obj_file).
+    pub fn read_obj<R: BufRead>(reader: R, path: &Path) -> Result<Mesh, MeshError> {
+        let mut mesh = Mesh::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line_number = index + 1;
+            let parse_error = |message: String| MeshError::Parse {
+                path: path.to_owned(),
+                line: line_number,
+                message,
+            };
+
+            let line = line.map_err(|source| MeshError::Io {
+                path: path.to_owned(),
+                source,
+            })?;
+            let line = match line.find('#') {
+                Some(comment) => &line[..comment],
+                None => &line,
+            };
+
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("v") => {
+                    let [x, y, z] = parse_floats(&mut tokens, 3, 4).map_err(parse_error)?;
+                    mesh.positions.push(Point3::new(x, y, z));
+                }
+                Some("vn") => {
+                    let [x, y, z] = parse_floats(&mut tokens, 3, 3).map_err(parse_error)?;
+                    mesh.normals.push(Vec3::new(x, y, z));
+                }
+                Some("vt") => {
+                    let [u, v, _] = parse_floats(&mut tokens, 1, 3).map_err(parse_error)?;
+                    mesh.uvs.push((u, v));
+                }
+                Some("f") => {
+                    let polygon = tokens
+                        .map(|token| parse_face_vertex(token, &mesh))
+                        .collect::<Result<Vec<MeshVertex>, String>>()
+                        .map_err(parse_error)?;
+                    if polygon.len() < 3 {
+                        return Err(parse_error(format!(
+                            "a face needs at least 3 vertices, found {}",
+                            polygon.len()
+                        )));
+                    }
+
+                    mesh.add_polygon(&polygon);
+                }
+                Some("g") | Some("o") => {
+                    let name = tokens.collect::<Vec<&str>>().join(" ");
+                    mesh.start_group(if name.is_empty() { "default" } else { &name });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(mesh)
+    }
+}
+
+/// Parses between `min` and `max` (at most 3) whitespace-separated floats.
+/// Missing values are set to 0.
+fn parse_floats(tokens: &mut SplitWhitespace, min: usize, max: usize) -> Result<[f64; 3], String> {
+    let mut values = [0.0; 3];
+    let mut count = 0;
+
+    for token in tokens {
+        if count == max {
+            return Err(format!("expected at most {} values", max));
+        }
+
+        let value = f64::from_str(token).map_err(|_| format!("invalid number `{}`", token))?;
+        if count < values.len() {
+            values[count] = value;
+        }
+        count += 1;
+    }
+
+    if count < min {
+        return Err(format!("expected at least {} values, found {}", min, count));
+    }
+
+    Ok(values)
+}
+
+/// Parses a face vertex, formatted as `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+fn parse_face_vertex(token: &str, mesh: &Mesh) -> Result<MeshVertex, String> {
+    let mut indices = token.split('/');
+
+    let position = match indices.next() {
+        Some(index) => parse_index(index, mesh.positions.len(), "vertex")?,
+        None => return Err(format!("invalid face vertex `{}`", token)),
+    };
+    let uv = match indices.next() {
+        Some("") | None => None,
+        Some(index) => Some(parse_index(index, mesh.uvs.len(), "texture coordinate")?),
+    };
+    let normal = match indices.next() {
+        Some("") | None => None,
+        Some(index) => Some(parse_index(index, mesh.normals.len(), "normal")?),
+    };
+
+    if indices.next().is_some() {
+        return Err(format!("invalid face vertex `{}`", token));
+    }
+
+    Ok(MeshVertex {
+        position,
+        normal,
+        uv,
+        color: None,
+    })
+}
+
+/// Parses an OBJ index into a zero-based index. OBJ indices start at 1, and
+/// negative ones are relative to the `count` elements defined so far.
+fn parse_index(token: &str, count: usize, element: &str) -> Result<usize, String> {
+    let index =
+        i64::from_str(token).map_err(|_| format!("invalid {} index `{}`", element, token))?;
+
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        count as i64 + index
+    };
+
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(format!(
+            "{} index {} is out of bounds, {} {}(s) are defined",
+            element, index, count, element
+        ));
+    }
+
+    Ok(resolved as usize)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read(content: &str) -> Result<Mesh, MeshError> {
+        Mesh::read_obj(content.as_bytes(), Path::new("test.obj"))
+    }
+
+    #[test]
+    fn read_obj_with_all_vertex_attributes() {
+        let mesh = read(
+            "# A single triangle\n\
+             v 0 0 0\n\
+             v 1 0 0\n\
+             v 0 1 0 1.0\n\
+             vt 0.5 0.25\n\
+             vn 0 0 1\n\
+             f 1/1/1 2/1/1 -1/-1/-1\n",
+        )
+        .unwrap();
+
+        assert_eq!(mesh.positions.len(), 3);
+        assert_eq!(mesh.positions[2], Point3::new(0.0, 1.0, 0.0));
+        assert_eq!(mesh.uvs, vec![(0.5, 0.25)]);
+        assert_eq!(mesh.normals, vec![Vec3::new(0.0, 0.0, 1.0)]);
+        assert_eq!(
+            mesh.triangles[0][2],
+            MeshVertex {
+                position: 2,
+                normal: Some(0),
+                uv: Some(0),
+                color: None,
+            }
+        );
+    }
+
+    #[test]
+    fn read_obj_with_optional_attributes() {
+        let mesh = read(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nvt 1\n\
+             f 1//1 2/1 3\n",
+        )
+        .unwrap();
+
+        assert_eq!(mesh.uvs, vec![(1.0, 0.0)]);
+        assert_eq!(
+            mesh.triangles[0],
+            [
+                MeshVertex {
+                    position: 0,
+                    normal: Some(0),
+                    uv: None,
+                    color: None,
+                },
+                MeshVertex {
+                    position: 1,
+                    normal: None,
+                    uv: Some(0),
+                    color: None,
+                },
+                MeshVertex {
+                    position: 2,
+                    normal: None,
+                    uv: None,
+                    color: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn read_obj_triangulates_polygons() {
+        let mesh = read("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv -1 0.5 0\nf 1 2 3 4 5\n").unwrap();
+
+        assert_eq!(mesh.triangles.len(), 3);
+    }
+
+    #[test]
+    fn read_obj_with_groups() {
+        let mesh = read(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+             f 1 2 3\n\
+             g first group\n\
+             o object\n\
+             f 1 2 3\n\
+             f 1 2 3\n\
+             g\n\
+             f 1 2 3\n",
+        )
+        .unwrap();
+
+        let groups: Vec<(&str, usize)> = mesh
+            .groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.triangles.len()))
+            .collect();
+        assert_eq!(groups, vec![("default", 1), ("object", 2), ("default", 1)]);
+    }
+
+    #[test]
+    fn read_obj_with_invalid_number_reports_line() {
+        let error = read("v 0 0 0\nv 1 zero 0\n").unwrap_err();
+
+        assert_eq!(error.to_string(), "test.obj:2: invalid number `zero`");
+    }
+
+    #[test]
+    fn read_obj_with_out_of_bounds_index_reports_line() {
+        let error = read("v 0 0 0\nv 1 0 0\nv 0 1 0\n\nf 1 2 4\n").unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "test.obj:5: vertex index 4 is out of bounds, 3 vertex(s) are defined"
+        );
+    }
+
+    #[test]
+    fn read_obj_with_too_small_face_reports_line() {
+        let error = read("v 0 0 0\nv 1 0 0\nf 1 2\n").unwrap_err();
+
+        assert!(matches!(error, MeshError::Parse { line: 3, .. }));
+    }
+
+    #[test]
+    fn load_obj_with_missing_file() {
+        let error = Mesh::load_obj("does/not/exist.obj").unwrap_err();
+
+        assert!(matches!(error, MeshError::Io { .. }));
+    }
+}
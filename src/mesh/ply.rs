@@ -0,0 +1,768 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    color::Color,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Mesh, MeshError, MeshVertex};
+
+/// The encoding of the elements following the PLY header.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+/// The type of a PLY scalar property.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl FromStr for ScalarType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ScalarType, String> {
+        match s {
+            "char" | "int8" => Ok(ScalarType::Int8),
+            "uchar" | "uint8" => Ok(ScalarType::UInt8),
+            "short" | "int16" => Ok(ScalarType::Int16),
+            "ushort" | "uint16" => Ok(ScalarType::UInt16),
+            "int" | "int32" => Ok(ScalarType::Int32),
+            "uint" | "uint32" => Ok(ScalarType::UInt32),
+            "float" | "float32" => Ok(ScalarType::Float32),
+            "double" | "float64" => Ok(ScalarType::Float64),
+            _ => Err(format!("unknown property type `{}`", s)),
+        }
+    }
+}
+
+/// A property of a PLY element: either a single scalar or a list of scalars
+/// preceded by their count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PropertyType {
+    Scalar(ScalarType),
+    List { count: ScalarType, item: ScalarType },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Property {
+    name: String,
+    property_type: PropertyType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    /// Returns the position of the property named `name`, if any.
+    fn property(&self, name: &str) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|property| property.name == name)
+    }
+
+    /// Returns the position of the first property named after one of the
+    /// `names`, if any.
+    fn any_property(&self, names: &[&str]) -> Option<usize> {
+        names.iter().find_map(|name| self.property(name))
+    }
+}
+
+/// The value of a property of an element instance.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Scalar(f64),
+    List(Vec<f64>),
+}
+
+impl Value {
+    fn scalar(&self) -> f64 {
+        match self {
+            Value::Scalar(value) => *value,
+            Value::List(values) => values.first().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+/// A source of element values, which are encoded either as text or as binary.
+trait ValueSource {
+    /// Reads a scalar of type `scalar_type`.
+    fn scalar(&mut self, scalar_type: ScalarType) -> Result<f64, MeshError>;
+
+    /// Returns an error at the current position of the source.
+    fn error(&self, message: String) -> MeshError;
+
+    /// Called before reading each element instance.
+    fn start_instance(&mut self) -> Result<(), MeshError> {
+        Ok(())
+    }
+
+    /// Reads the value of a `property`.
+    fn value(&mut self, property: &Property) -> Result<Value, MeshError> {
+        match property.property_type {
+            PropertyType::Scalar(scalar_type) => Ok(Value::Scalar(self.scalar(scalar_type)?)),
+            PropertyType::List { count, item } => {
+                let count = self.scalar(count)?;
+                if count < 0.0 || count.fract() != 0.0 {
+                    return Err(self.error(format!("invalid list length {}", count)));
+                }
+
+                (0..count as usize)
+                    .map(|_| self.scalar(item))
+                    .collect::<Result<Vec<f64>, MeshError>>()
+                    .map(Value::List)
+            }
+        }
+    }
+}
+
+/// Reads values written as whitespace-separated text, one element instance per
+/// line.
+struct AsciiSource<'a, R> {
+    reader: R,
+    path: &'a Path,
+    line: usize,
+    tokens: std::vec::IntoIter<String>,
+}
+
+impl<'a, R: BufRead> ValueSource for AsciiSource<'a, R> {
+    fn scalar(&mut self, scalar_type: ScalarType) -> Result<f64, MeshError> {
+        let token = match self.tokens.next() {
+            Some(token) => token,
+            None => return Err(self.error(String::from("missing value"))),
+        };
+
+        // Integers are parsed as such, to reject fractions and values out of
+        // the range of their type.
+        let value = match scalar_type {
+            ScalarType::Int8 => i8::from_str(&token).ok().map(f64::from),
+            ScalarType::UInt8 => u8::from_str(&token).ok().map(f64::from),
+            ScalarType::Int16 => i16::from_str(&token).ok().map(f64::from),
+            ScalarType::UInt16 => u16::from_str(&token).ok().map(f64::from),
+            ScalarType::Int32 => i32::from_str(&token).ok().map(f64::from),
+            ScalarType::UInt32 => u32::from_str(&token).ok().map(f64::from),
+            ScalarType::Float32 | ScalarType::Float64 => f64::from_str(&token).ok(),
+        };
+
+        value.ok_or_else(|| self.error(format!("invalid number `{}`", token)))
+    }
+
+    fn error(&self, message: String) -> MeshError {
+        MeshError::Parse {
+            path: self.path.to_owned(),
+            line: self.line,
+            message,
+        }
+    }
+
+    fn start_instance(&mut self) -> Result<(), MeshError> {
+        if self.tokens.len() > 0 {
+            return Err(self.error(String::from("unexpected value")));
+        }
+
+        loop {
+            let mut line = String::new();
+            let read = self
+                .reader
+                .read_line(&mut line)
+                .map_err(|source| io_error(self.path, source))?;
+            self.line += 1;
+
+            if read == 0 {
+                return Err(self.error(String::from("unexpected end of file")));
+            }
+
+            let tokens: Vec<String> = line.split_whitespace().map(String::from).collect();
+            if !tokens.is_empty() {
+                self.tokens = tokens.into_iter();
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Reads values written as binary, in little or big endian byte order.
+struct BinarySource<'a, R> {
+    reader: R,
+    path: &'a Path,
+    offset: u64,
+    big_endian: bool,
+}
+
+impl<'a, R: Read> BinarySource<'a, R> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], MeshError> {
+        let mut bytes = [0; N];
+        self.reader.read_exact(&mut bytes).map_err(|source| {
+            if source.kind() == std::io::ErrorKind::UnexpectedEof {
+                self.error(String::from("unexpected end of file"))
+            } else {
+                io_error(self.path, source)
+            }
+        })?;
+        self.offset += N as u64;
+
+        Ok(bytes)
+    }
+}
+
+/// Decodes a number from its bytes in the byte order of the source.
+macro_rules! decode {
+    ($source:expr, $type:ty) => {{
+        let bytes = $source.bytes()?;
+        if $source.big_endian {
+            <$type>::from_be_bytes(bytes) as f64
+        } else {
+            <$type>::from_le_bytes(bytes) as f64
+        }
+    }};
+}
+
+impl<'a, R: Read> ValueSource for BinarySource<'a, R> {
+    fn scalar(&mut self, scalar_type: ScalarType) -> Result<f64, MeshError> {
+        Ok(match scalar_type {
+            ScalarType::Int8 => decode!(self, i8),
+            ScalarType::UInt8 => decode!(self, u8),
+            ScalarType::Int16 => decode!(self, i16),
+            ScalarType::UInt16 => decode!(self, u16),
+            ScalarType::Int32 => decode!(self, i32),
+            ScalarType::UInt32 => decode!(self, u32),
+            ScalarType::Float32 => decode!(self, f32),
+            ScalarType::Float64 => decode!(self, f64),
+        })
+    }
+
+    fn error(&self, message: String) -> MeshError {
+        MeshError::ParseBinary {
+            path: self.path.to_owned(),
+            offset: self.offset,
+            message,
+        }
+    }
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> MeshError {
+    MeshError::Io {
+        path: path.to_owned(),
+        source,
+    }
+}
+
+impl Mesh {
+    /// Loads a mesh from a PLY file.
+    ///
+    /// See [`Mesh::read_ply`].
+    pub fn load_ply<P: AsRef<Path>>(path: P) -> Result<Mesh, MeshError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|source| io_error(path, source))?;
+
+        Mesh::read_ply(BufReader::new(file), path)
+    }
+
+    /// Reads a mesh in PLY format, either ASCII or binary (little or big
+    /// endian), from a `reader`. `path` is only used to report errors.
+    ///
+    /// The `vertex` element provides the vertex positions (`x`, `y`, `z`) and
+    /// optionally their normals (`nx`, `ny`, `nz`), colors (`red`, `green`,
+    /// `blue`) and texture coordinates (`u`, `v` or `s`, `t`). Colors stored as
+    /// integers range from 0 to 255, colors stored as floats from 0 to 1.
+    /// The `face` element provides the polygons as lists of vertex indices
+    /// (`vertex_indices`). Other elements and properties are ignored.
+    ///
+    /// See [PLY on Wikipedia](https://en.wikipedia.org/wiki/PLY_(file_format)).
+    pub fn read_ply<R: BufRead>(mut reader: R, path: &Path) -> Result<Mesh, MeshError> {
+        let (format, elements, header_lines, header_length) = read_header(&mut reader, path)?;
+
+        match format {
+            Format::Ascii => read_elements(
+                &elements,
+                &mut AsciiSource {
+                    reader,
+                    path,
+                    line: header_lines,
+                    tokens: Vec::new().into_iter(),
+                },
+            ),
+            Format::BinaryLittleEndian | Format::BinaryBigEndian => read_elements(
+                &elements,
+                &mut BinarySource {
+                    reader,
+                    path,
+                    offset: header_length,
+                    big_endian: format == Format::BinaryBigEndian,
+                },
+            ),
+        }
+    }
+}
+
+/// Reads the PLY header. Returns the format of the elements, their
+/// description, and the number of lines and bytes of the header.
+fn read_header<R: BufRead>(
+    reader: &mut R,
+    path: &Path,
+) -> Result<(Format, Vec<Element>, usize, u64), MeshError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut line_number = 0;
+    let mut length = 0;
+    let error = |line: usize, message: String| MeshError::Parse {
+        path: PathBuf::from(path),
+        line,
+        message,
+    };
+
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|source| io_error(path, source))?;
+        line_number += 1;
+        length += read as u64;
+        if read == 0 {
+            return Err(error(line_number, String::from("missing `end_header`")));
+        }
+
+        let mut tokens = line.split_whitespace();
+        let keyword = tokens.next();
+        if line_number == 1 {
+            if keyword != Some("ply") {
+                return Err(error(
+                    line_number,
+                    String::from("missing `ply` magic number"),
+                ));
+            }
+            continue;
+        }
+
+        match keyword {
+            Some("format") => {
+                format = Some(match (tokens.next(), tokens.next()) {
+                    (Some("ascii"), Some("1.0")) => Format::Ascii,
+                    (Some("binary_little_endian"), Some("1.0")) => Format::BinaryLittleEndian,
+                    (Some("binary_big_endian"), Some("1.0")) => Format::BinaryBigEndian,
+                    _ => {
+                        return Err(error(
+                            line_number,
+                            format!("unsupported format `{}`", line.trim()),
+                        ))
+                    }
+                });
+            }
+            Some("element") => {
+                let name = tokens.next();
+                let count = tokens.next().and_then(|count| usize::from_str(count).ok());
+                match (name, count) {
+                    (Some(name), Some(count)) => elements.push(Element {
+                        name: name.to_owned(),
+                        count,
+                        properties: Vec::new(),
+                    }),
+                    _ => {
+                        return Err(error(
+                            line_number,
+                            format!("invalid element `{}`", line.trim()),
+                        ))
+                    }
+                }
+            }
+            Some("property") => {
+                let property = parse_property(tokens.collect())
+                    .map_err(|message| error(line_number, message))?;
+                match elements.last_mut() {
+                    Some(element) => element.properties.push(property),
+                    None => {
+                        return Err(error(
+                            line_number,
+                            String::from("property defined before any element"),
+                        ))
+                    }
+                }
+            }
+            Some("end_header") => break,
+            Some("comment") | Some("obj_info") | None => {}
+            Some(keyword) => {
+                return Err(error(
+                    line_number,
+                    format!("unknown header keyword `{}`", keyword),
+                ))
+            }
+        }
+    }
+
+    match format {
+        Some(format) => Ok((format, elements, line_number, length)),
+        None => Err(error(line_number, String::from("missing `format`"))),
+    }
+}
+
+/// Parses a property definition, without its `property` keyword.
+fn parse_property(tokens: Vec<&str>) -> Result<Property, String> {
+    match tokens.as_slice() {
+        ["list", count, item, name] => Ok(Property {
+            name: (*name).to_owned(),
+            property_type: PropertyType::List {
+                count: count.parse()?,
+                item: item.parse()?,
+            },
+        }),
+        [scalar_type, name] => Ok(Property {
+            name: (*name).to_owned(),
+            property_type: PropertyType::Scalar(scalar_type.parse()?),
+        }),
+        _ => Err(format!("invalid property `{}`", tokens.join(" "))),
+    }
+}
+
+/// Reads the elements described in the header from the `source`, and builds
+/// a mesh from the `vertex` and `face` ones.
+fn read_elements<S: ValueSource>(elements: &[Element], source: &mut S) -> Result<Mesh, MeshError> {
+    let mut mesh = Mesh::new();
+    let mut has_normals = false;
+    let mut has_uvs = false;
+    let mut has_colors = false;
+
+    for element in elements {
+        match element.name.as_str() {
+            "vertex" => {
+                let position = match (
+                    element.property("x"),
+                    element.property("y"),
+                    element.property("z"),
+                ) {
+                    (Some(x), Some(y), Some(z)) => [x, y, z],
+                    _ => return Err(source.error(String::from("vertices have no position"))),
+                };
+                let normal = match (
+                    element.property("nx"),
+                    element.property("ny"),
+                    element.property("nz"),
+                ) {
+                    (Some(x), Some(y), Some(z)) => Some([x, y, z]),
+                    _ => None,
+                };
+                let color = match (
+                    element.any_property(&["red", "r", "diffuse_red"]),
+                    element.any_property(&["green", "g", "diffuse_green"]),
+                    element.any_property(&["blue", "b", "diffuse_blue"]),
+                ) {
+                    (Some(r), Some(g), Some(b)) => Some([r, g, b]),
+                    _ => None,
+                };
+                let uv = match (
+                    element.any_property(&["u", "s", "texture_u", "texture_s"]),
+                    element.any_property(&["v", "t", "texture_v", "texture_t"]),
+                ) {
+                    (Some(u), Some(v)) => Some([u, v]),
+                    _ => None,
+                };
+
+                has_normals = normal.is_some();
+                has_colors = color.is_some();
+                has_uvs = uv.is_some();
+
+                for _ in 0..element.count {
+                    let values = read_instance(element, source)?;
+
+                    let [x, y, z] = position.map(|i| values[i].scalar());
+                    mesh.positions.push(Point3::new(x, y, z));
+
+                    if let Some(normal) = normal {
+                        let [x, y, z] = normal.map(|i| values[i].scalar());
+                        mesh.normals.push(Vec3::new(x, y, z));
+                    }
+                    if let Some(color) = color {
+                        let [r, g, b] = color.map(|i| {
+                            let component = values[i].scalar();
+                            match &element.properties[i].property_type {
+                                PropertyType::Scalar(ScalarType::Float32)
+                                | PropertyType::Scalar(ScalarType::Float64) => component,
+                                _ => component / 255.0,
+                            }
+                        });
+                        // Colors are stored gamma-corrected, undo the gamma = 2.0
+                        // correction applied when writing images.
+                        mesh.colors.push(Color::new(r * r, g * g, b * b));
+                    }
+                    if let Some(uv) = uv {
+                        let [u, v] = uv.map(|i| values[i].scalar());
+                        mesh.uvs.push((u, v));
+                    }
+                }
+            }
+            "face" => {
+                let indices = element
+                    .any_property(&["vertex_indices", "vertex_index"])
+                    .ok_or_else(|| source.error(String::from("faces have no vertex indices")))?;
+
+                for _ in 0..element.count {
+                    let values = read_instance(element, source)?;
+                    let polygon = match &values[indices] {
+                        Value::List(polygon) => polygon,
+                        Value::Scalar(_) => {
+                            return Err(source.error(String::from("vertex indices are not a list")))
+                        }
+                    };
+                    if polygon.len() < 3 {
+                        return Err(source.error(format!(
+                            "a face needs at least 3 vertices, found {}",
+                            polygon.len()
+                        )));
+                    }
+
+                    let polygon = polygon
+                        .iter()
+                        .map(|&index| {
+                            if index.fract() != 0.0 {
+                                return Err(source
+                                    .error(format!("vertex index {} is not an integer", index)));
+                            }
+                            if index < 0.0 || index >= mesh.positions.len() as f64 {
+                                return Err(source.error(format!(
+                                    "vertex index {} is out of bounds, {} vertex(s) are defined",
+                                    index,
+                                    mesh.positions.len()
+                                )));
+                            }
+
+                            let index = index as usize;
+                            Ok(MeshVertex {
+                                position: index,
+                                normal: if has_normals { Some(index) } else { None },
+                                uv: if has_uvs { Some(index) } else { None },
+                                color: if has_colors { Some(index) } else { None },
+                            })
+                        })
+                        .collect::<Result<Vec<MeshVertex>, MeshError>>()?;
+
+                    mesh.add_polygon(&polygon);
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    read_instance(element, source)?;
+                }
+            }
+        }
+    }
+
+    Ok(mesh)
+}
+
+/// Reads the values of all properties of an instance of `element`.
+fn read_instance<S: ValueSource>(
+    element: &Element,
+    source: &mut S,
+) -> Result<Vec<Value>, MeshError> {
+    source.start_instance()?;
+
+    element
+        .properties
+        .iter()
+        .map(|property| source.value(property))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HEADER: &str = "element vertex 4\n\
+                          property float x\n\
+                          property float y\n\
+                          property float z\n\
+                          property float nx\n\
+                          property float ny\n\
+                          property float nz\n\
+                          property uchar red\n\
+                          property uchar green\n\
+                          property uchar blue\n\
+                          element face 1\n\
+                          property list uchar int vertex_indices\n\
+                          end_header\n";
+
+    const VERTICES: [[f64; 9]; 4] = [
+        [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 255.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 255.0, 0.0],
+        [1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 255.0],
+        [0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0],
+    ];
+
+    fn read(content: &[u8]) -> Result<Mesh, MeshError> {
+        Mesh::read_ply(content, Path::new("test.ply"))
+    }
+
+    /// Encodes the square made of the test vertices in binary.
+    fn binary_square(big_endian: bool) -> Vec<u8> {
+        let format = if big_endian {
+            "binary_big_endian"
+        } else {
+            "binary_little_endian"
+        };
+        let mut content = format!("ply\nformat {} 1.0\n{}", format, HEADER).into_bytes();
+
+        for vertex in &VERTICES {
+            for &value in &vertex[..6] {
+                let value = value as f32;
+                content.extend_from_slice(&if big_endian {
+                    value.to_be_bytes()
+                } else {
+                    value.to_le_bytes()
+                });
+            }
+            content.extend(vertex[6..].iter().map(|&value| value as u8));
+        }
+
+        content.push(4);
+        for index in 0..4i32 {
+            content.extend_from_slice(&if big_endian {
+                index.to_be_bytes()
+            } else {
+                index.to_le_bytes()
+            });
+        }
+
+        content
+    }
+
+    fn assert_square(mesh: &Mesh) {
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.positions[2], Point3::new(1.0, 1.0, 0.0));
+        assert_eq!(mesh.normals[3], Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(mesh.colors[1], Color::new(0.0, 1.0, 0.0));
+        assert_eq!(mesh.triangles.len(), 2);
+        assert_eq!(
+            mesh.triangles[0][0],
+            MeshVertex {
+                position: mesh.triangles[0][0].position,
+                normal: Some(mesh.triangles[0][0].position),
+                uv: None,
+                color: Some(mesh.triangles[0][0].position),
+            }
+        );
+    }
+
+    #[test]
+    fn read_ascii_ply() {
+        let mut content = format!("ply\nformat ascii 1.0\ncomment test\n{}", HEADER);
+        for vertex in &VERTICES {
+            let values: Vec<String> = vertex.iter().map(|value| value.to_string()).collect();
+            content.push_str(&values.join(" "));
+            content.push('\n');
+        }
+        content.push_str("4 0 1 2 3\n");
+
+        assert_square(&read(content.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn read_binary_little_endian_ply() {
+        assert_square(&read(&binary_square(false)).unwrap());
+    }
+
+    #[test]
+    fn read_binary_big_endian_ply() {
+        assert_square(&read(&binary_square(true)).unwrap());
+    }
+
+    #[test]
+    fn read_ply_skips_unknown_elements() {
+        let content = "ply\nformat ascii 1.0\n\
+                       element camera 1\nproperty float fov\nproperty list uchar float data\n\
+                       element vertex 3\nproperty double x\nproperty double y\nproperty double z\n\
+                       property float s\nproperty float t\n\
+                       element face 1\nproperty list uchar uint vertex_index\n\
+                       end_header\n\
+                       45 2 1 2\n\
+                       0 0 0 0 0\n1 0 0 1 0\n0 1 0 0 1\n\
+                       3 0 1 2\n";
+        let mesh = read(content.as_bytes()).unwrap();
+
+        assert_eq!(mesh.uvs, vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+        assert!(mesh.normals.is_empty());
+        assert!(mesh.colors.is_empty());
+        assert_eq!(mesh.triangles.len(), 1);
+    }
+
+    #[test]
+    fn read_ply_without_magic_number() {
+        let error = read(b"format ascii 1.0\nend_header\n").unwrap_err();
+
+        assert_eq!(error.to_string(), "test.ply:1: missing `ply` magic number");
+    }
+
+    #[test]
+    fn read_ascii_ply_with_invalid_value_reports_line() {
+        let content = "ply\nformat ascii 1.0\n\
+                       element vertex 2\nproperty float x\nproperty float y\nproperty float z\n\
+                       end_header\n\
+                       0 0 0\n0 one 0\n";
+        let error = read(content.as_bytes()).unwrap_err();
+
+        assert_eq!(error.to_string(), "test.ply:9: invalid number `one`");
+    }
+
+    #[test]
+    fn read_ascii_ply_with_out_of_bounds_index_reports_line() {
+        let content = "ply\nformat ascii 1.0\n\
+                       element vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+                       element face 1\nproperty list uchar int vertex_indices\n\
+                       end_header\n\
+                       0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n";
+        let error = read(content.as_bytes()).unwrap_err();
+
+        assert!(matches!(error, MeshError::Parse { line: 13, .. }));
+    }
+
+    #[test]
+    fn read_ascii_ply_with_fractional_index_reports_line() {
+        let content = "ply\nformat ascii 1.0\n\
+                       element vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+                       element face 1\nproperty list uchar uint vertex_indices\n\
+                       end_header\n\
+                       0 0 0\n1 0 0\n0 1 0\n3 0 1.5 2\n";
+        let error = read(content.as_bytes()).unwrap_err();
+
+        assert_eq!(error.to_string(), "test.ply:13: invalid number `1.5`");
+    }
+
+    #[test]
+    fn read_ascii_ply_with_negative_index_reports_line() {
+        let content = "ply\nformat ascii 1.0\n\
+                       element vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+                       element face 1\nproperty list uchar uint vertex_indices\n\
+                       end_header\n\
+                       0 0 0\n1 0 0\n0 1 0\n3 0 -1 2\n";
+        let error = read(content.as_bytes()).unwrap_err();
+
+        assert_eq!(error.to_string(), "test.ply:13: invalid number `-1`");
+    }
+
+    #[test]
+    fn read_truncated_binary_ply_reports_offset() {
+        let mut content = binary_square(false);
+        content.truncate(content.len() - 2);
+        let error = read(&content).unwrap_err();
+
+        // The 283 bytes of the header, then the 121 bytes of values read.
+        assert!(matches!(error, MeshError::ParseBinary { offset: 404, .. }));
+    }
+}
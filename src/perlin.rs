@@ -0,0 +1,136 @@
+//! # Perlin module
+//!
+//! Gradient noise, giving smooth pseudo-random values over the 3-dimensional
+//! space, used to vary densities and colors procedurally.
+
+use rand::{seq::SliceRandom, thread_rng};
+
+use crate::vec3::{Point3, Vec3};
+
+/// The number of gradients, and the period of the noise along each axis.
+const POINT_COUNT: usize = 256;
+
+/// A Perlin noise generator.
+///
+/// See [Ray Tracing: The Next Week, chapter 5](https://raytracing.github.io/books/RayTracingTheNextWeek.html#perlinnoise).
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    permutations: [Vec<usize>; 3],
+}
+
+impl Perlin {
+    /// Constructs a new `Perlin` noise generator with random gradients.
+    pub fn new() -> Perlin {
+        let gradients = (0..POINT_COUNT)
+            .map(|_| Vec3::random_normalized())
+            .collect();
+        let permutation = || {
+            let mut permutation: Vec<usize> = (0..POINT_COUNT).collect();
+            permutation.shuffle(&mut thread_rng());
+            permutation
+        };
+
+        Perlin {
+            gradients,
+            permutations: [permutation(), permutation(), permutation()],
+        }
+    }
+
+    /// Returns the noise at `point`, in [-1, 1].
+    pub fn noise(&self, point: Point3) -> f64 {
+        let floor = [point.x().floor(), point.y().floor(), point.z().floor()];
+        let fraction = [
+            point.x() - floor[0],
+            point.y() - floor[1],
+            point.z() - floor[2],
+        ];
+        // Hermite smoothing removes the grid artifacts of linear interpolation.
+        let smoothed = fraction.map(|f| f * f * (3.0 - 2.0 * f));
+        let cell = floor.map(|f| f as i64);
+
+        let mut accumulator = 0.0;
+        for corner in 0..8 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let index = (0..3)
+                .map(|axis| {
+                    let coordinate =
+                        (cell[axis] + offset[axis] as i64).rem_euclid(POINT_COUNT as i64) as usize;
+                    self.permutations[axis][coordinate]
+                })
+                .fold(0, |index, permuted| index ^ permuted);
+
+            let weight = Vec3::new(
+                fraction[0] - offset[0] as f64,
+                fraction[1] - offset[1] as f64,
+                fraction[2] - offset[2] as f64,
+            );
+            let interpolation: f64 = (0..3)
+                .map(|axis| {
+                    if offset[axis] == 1 {
+                        smoothed[axis]
+                    } else {
+                        1.0 - smoothed[axis]
+                    }
+                })
+                .product();
+
+            accumulator += interpolation * self.gradients[index].dot(&weight);
+        }
+
+        accumulator.clamp(-1.0, 1.0)
+    }
+
+    /// Returns the turbulence at `point`: the absolute value of the sum of
+    /// `octaves` noises of doubling frequencies and halving amplitudes.
+    ///
+    /// The turbulence is in [0, 2).
+    pub fn turbulence(&self, point: Point3, octaves: usize) -> f64 {
+        let mut accumulator = 0.0;
+        let mut point = point;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            accumulator += weight * self.noise(point);
+            weight *= 0.5;
+            point *= 2.0;
+        }
+
+        accumulator.abs()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn noise_is_zero_on_the_lattice() {
+        let perlin = Perlin::new();
+
+        assert_eq!(perlin.noise(Point3::new(3.0, -2.0, 7.0)), 0.0);
+    }
+
+    #[test]
+    fn noise_is_continuous_and_bounded() {
+        let perlin = Perlin::new();
+
+        for i in 0..1000 {
+            let point = Point3::new(i as f64 * 0.173, i as f64 * -0.071, i as f64 * 0.029);
+            let noise = perlin.noise(point);
+            let nearby = perlin.noise(point + Vec3::new(1e-6, 1e-6, 1e-6));
+
+            assert!((-1.0..=1.0).contains(&noise));
+            assert!((noise - nearby).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn turbulence_is_bounded() {
+        let perlin = Perlin::new();
+
+        for i in 0..1000 {
+            let point = Point3::new(i as f64 * 0.31, i as f64 * 0.17, i as f64 * -0.11);
+
+            assert!((0.0..2.0).contains(&perlin.turbulence(point, 7)));
+        }
+    }
+}
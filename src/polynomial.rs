@@ -0,0 +1,342 @@
+//! # Polynomial module
+//!
+//! Solvers for the real roots of polynomials up to the fourth degree, used to
+//! intersect rays with analytic surfaces.
+//!
+//! Every solver takes the coefficients from the highest degree to the constant
+//! term and returns the real roots in increasing order. Roots with a
+//! multiplicity greater than one are returned once.
+
+use std::f64::consts::PI;
+
+/// Coefficients closer to zero than this are considered to be zero.
+const EPSILON: f64 = 1e-12;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+/// Returns the real roots of `a * x + b`.
+pub fn solve_linear(a: f64, b: f64) -> Vec<f64> {
+    if is_zero(a) {
+        Vec::new()
+    } else {
+        vec![-b / a]
+    }
+}
+
+/// Returns the real roots of `a * x² + b * x + c`.
+///
+/// The roots are computed in a way that avoids the catastrophic cancellation
+/// of the textbook formula.
+/// See [Numerical Recipes](http://numerical.recipes/book/book.html), section
+/// 5.6.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if is_zero(a) {
+        return solve_linear(b, c);
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    if discriminant == 0.0 {
+        return vec![-0.5 * b / a];
+    }
+
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let (x0, x1) = if q == 0.0 {
+        // Both `b` and `c` are zero.
+        (0.0, 0.0)
+    } else {
+        (q / a, c / q)
+    };
+
+    if x0 < x1 {
+        vec![x0, x1]
+    } else if x1 < x0 {
+        vec![x1, x0]
+    } else {
+        vec![x0]
+    }
+}
+
+/// Returns the real roots of `a * x³ + b * x² + c * x + d`.
+///
+/// See [Cardano's method on Wikipedia](https://en.wikipedia.org/wiki/Cubic_equation#Cardano's_formula)
+/// and the trigonometric method for three real roots.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if is_zero(a) {
+        return solve_quadratic(b, c, d);
+    }
+
+    // Normal form `x³ + A * x² + B * x + C`, then substitute `x = y - A / 3`
+    // to get the depressed cubic `y³ + 3 * p * y + 2 * q`.
+    let (a, b, c) = (b / a, c / a, d / a);
+    let a_squared = a * a;
+    let p = (-a_squared / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * a_squared - a * b / 3.0 + c) / 2.0;
+
+    let p_cubed = p * p * p;
+    let discriminant = q * q + p_cubed;
+
+    // The discriminant is a difference of terms of the order of `q²`, so it
+    // is compared to them.
+    let mut roots = if discriminant.abs() <= EPSILON * (q * q).max(p_cubed.abs()) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // Three real roots.
+        let phi = (-q / (-p_cubed).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.0).cos(),
+            -t * (phi - PI / 3.0).cos(),
+        ]
+    } else {
+        let sqrt_discriminant = discriminant.sqrt();
+        vec![(sqrt_discriminant - q).cbrt() - (sqrt_discriminant + q).cbrt()]
+    };
+
+    for root in roots.iter_mut() {
+        *root -= a / 3.0;
+    }
+    sorted(roots)
+}
+
+/// Returns the real roots of `a * x⁴ + b * x³ + c * x² + d * x + e`.
+///
+/// The roots are found with Ferrari's method, then refined with a few
+/// iterations of Newton's method as the method is numerically unstable.
+/// See [Ferrari's solution on Wikipedia](https://en.wikipedia.org/wiki/Quartic_function#Ferrari's_solution).
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if is_zero(a) {
+        return solve_cubic(b, c, d, e);
+    }
+
+    // Normal form `x⁴ + A * x³ + B * x² + C * x + D`, then substitute
+    // `x = y - A / 4` to get the depressed quartic `y⁴ + p * y² + q * y + r`.
+    let coefficients = [1.0, b / a, c / a, d / a, e / a];
+    let [_, a, b, c, d] = coefficients;
+    let a_squared = a * a;
+    let p = -3.0 / 8.0 * a_squared + b;
+    let q = a_squared * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * a_squared * a_squared + a_squared * b / 16.0 - a * c / 4.0 + d;
+
+    // Take the largest root of the resolvent cubic to split the quartic
+    // into two quadratics, for which both `z² - r` and `2 * z - p` are
+    // positive.
+    let resolvent = [1.0, -p / 2.0, -r, r * p / 2.0 - q * q / 8.0];
+    let z = *solve_cubic(resolvent[0], resolvent[1], resolvent[2], resolvent[3])
+        .last()
+        .expect("cubics have a real root");
+    let z = polish(&resolvent, z);
+
+    // `u` and `v` are related by `q = 2 * u * v`, the smaller one being
+    // computed from the larger one as it is less accurate by itself.
+    let u_squared = match non_negative(z * z - r, (z * z).max(r.abs())) {
+        Some(u_squared) => u_squared,
+        None => return Vec::new(),
+    };
+    let v_squared = match non_negative(2.0 * z - p, (2.0 * z).abs().max(p.abs())) {
+        Some(v_squared) => v_squared,
+        None => return Vec::new(),
+    };
+    let (u, v) = if u_squared > v_squared {
+        let u = u_squared.sqrt();
+        (u, q / (2.0 * u))
+    } else if v_squared > 0.0 {
+        let v = v_squared.sqrt();
+        (q / (2.0 * v), v)
+    } else {
+        (0.0, 0.0)
+    };
+
+    let mut roots = solve_quadratic(1.0, v, z - u);
+    roots.extend(solve_quadratic(1.0, -v, z + u));
+
+    for root in roots.iter_mut() {
+        *root = polish(&coefficients, *root - a / 4.0);
+    }
+    sorted(roots)
+}
+
+/// Returns `x`, or 0 if it is negative because of rounding errors on values of
+/// the order of `scale`, or [`None`] if it is clearly negative.
+fn non_negative(x: f64, scale: f64) -> Option<f64> {
+    if x >= 0.0 {
+        Some(x)
+    } else if x >= -1e-9 * scale {
+        Some(0.0)
+    } else {
+        None
+    }
+}
+
+/// Refines a root of the polynomial with the given `coefficients` with
+/// Newton's method, keeping the best approximation found.
+fn polish(coefficients: &[f64], root: f64) -> f64 {
+    let evaluate = |x: f64| {
+        let (mut value, mut derivative) = (0.0, 0.0);
+        for &coefficient in coefficients {
+            derivative = derivative * x + value;
+            value = value * x + coefficient;
+        }
+        (value, derivative)
+    };
+
+    let (mut best, mut best_value) = (root, evaluate(root).0.abs());
+    let mut x = root;
+    for _ in 0..8 {
+        let (value, derivative) = evaluate(x);
+        if value == 0.0 || derivative == 0.0 {
+            break;
+        }
+        x -= value / derivative;
+
+        let value = evaluate(x).0.abs();
+        if value < best_value {
+            best = x;
+            best_value = value;
+        }
+    }
+
+    best
+}
+
+/// Sorts the roots in increasing order and removes duplicates.
+fn sorted(mut roots: Vec<f64>) -> Vec<f64> {
+    roots.sort_by(|a, b| a.partial_cmp(b).expect("roots shouldn't be NaN"));
+    roots.dedup();
+    roots
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_roots(roots: Vec<f64>, expected: &[f64]) {
+        assert_eq!(roots.len(), expected.len(), "roots: {:?}", roots);
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!((root - expected).abs() < 1e-9, "roots: {:?}", roots);
+        }
+    }
+
+    #[test]
+    fn solve_linear_works() {
+        assert_roots(solve_linear(2.0, -1.0), &[0.5]);
+        assert_roots(solve_linear(0.0, 1.0), &[]);
+    }
+
+    #[test]
+    fn solve_quadratic_works() {
+        assert_roots(solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(solve_quadratic(2.0, 4.0, 2.0), &[-1.0]);
+        assert_roots(solve_quadratic(1.0, 0.0, 1.0), &[]);
+        assert_roots(solve_quadratic(1.0, 0.0, 0.0), &[0.0]);
+        assert_roots(solve_quadratic(0.0, 2.0, -1.0), &[0.5]);
+    }
+
+    #[test]
+    fn solve_quadratic_with_cancellation() {
+        let roots = solve_quadratic(1.0, -1e8, 1.0);
+
+        assert!((roots[0] - 1e-8).abs() < 1e-20);
+    }
+
+    #[test]
+    fn solve_cubic_works() {
+        // (x - 1)(x - 2)(x + 3)
+        assert_roots(solve_cubic(1.0, 0.0, -7.0, 6.0), &[-3.0, 1.0, 2.0]);
+        // (x - 1)(x² + 1)
+        assert_roots(solve_cubic(2.0, -2.0, 2.0, -2.0), &[1.0]);
+        // (x - 1)²(x + 2)
+        assert_roots(solve_cubic(1.0, 0.0, -3.0, 2.0), &[-2.0, 1.0]);
+        // x³
+        assert_roots(solve_cubic(1.0, 0.0, 0.0, 0.0), &[0.0]);
+    }
+
+    #[test]
+    fn solve_quartic_works() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(
+            solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        // (x² - 4)(x² + 1)
+        assert_roots(solve_quartic(3.0, 0.0, -9.0, 0.0, -12.0), &[-2.0, 2.0]);
+        // (x² + 1)(x² + 2)
+        assert_roots(solve_quartic(1.0, 0.0, 3.0, 0.0, 2.0), &[]);
+        // x(x - 1)(x + 1)(x - 5)
+        assert_roots(
+            solve_quartic(1.0, -5.0, -1.0, 5.0, 0.0),
+            &[-1.0, 0.0, 1.0, 5.0],
+        );
+    }
+
+    #[test]
+    fn solve_quartic_with_double_roots() {
+        // (x - 1)²(x + 2)²
+        let roots = solve_quartic(1.0, 2.0, -3.0, -4.0, 4.0);
+
+        assert!(roots.iter().any(|root| (root - 1.0).abs() < 1e-6));
+        assert!(roots.iter().any(|root| (root + 2.0).abs() < 1e-6));
+    }
+
+    /// Returns the coefficients of the monic quartic `(x - r0)(x - r1)(x² +
+    /// bx + c)`.
+    fn quartic(r0: f64, r1: f64, b: f64, c: f64) -> [f64; 5] {
+        let (s, p) = (-(r0 + r1), r0 * r1);
+        [1.0, s + b, p + s * b + c, p * b + s * c, p * c]
+    }
+
+    #[test]
+    fn solve_quartic_with_clustered_roots() {
+        // (x - 1)(x - 1.001)(x - 1.002)(x - 1.003)
+        let [a, b, c, d, e] = quartic(1.0, 1.001, -2.005, 1.002 * 1.003);
+        let roots = solve_quartic(a, b, c, d, e);
+
+        assert_eq!(roots.len(), 4, "roots: {:?}", roots);
+        for (root, expected) in roots.iter().zip([1.0, 1.001, 1.002, 1.003]) {
+            assert!((root - expected).abs() < 1e-4, "roots: {:?}", roots);
+        }
+    }
+
+    #[test]
+    fn solve_quartic_with_a_double_root_and_simple_roots() {
+        // (x - 1)²(x - 2)(x - 3)
+        let [a, b, c, d, e] = quartic(1.0, 1.0, -5.0, 6.0);
+        let roots = solve_quartic(a, b, c, d, e);
+
+        for expected in [1.0, 2.0, 3.0] {
+            assert!(
+                roots.iter().any(|root| (root - expected).abs() < 1e-6),
+                "roots: {:?}",
+                roots
+            );
+        }
+    }
+
+    #[test]
+    fn solve_quartic_with_small_resolvent_coefficients() {
+        // Two real roots and two complex ones, as with rays grazing a torus,
+        // for which the resolvent cubic has small coefficients.
+        let (re, im) = (0.052609748504633735, 0.04473038070143667);
+        let [a, b, c, d, e] = quartic(
+            0.2536754109398984,
+            -0.1304935856293854,
+            -2.0 * re,
+            re * re + im * im,
+        );
+
+        assert_roots(
+            solve_quartic(a, b, c, d, e),
+            &[-0.1304935856293854, 0.2536754109398984],
+        );
+    }
+}
@@ -1,15 +1,26 @@
-use std::ops::Range;
+use std::{cell::RefCell, ops::Range};
 
-use rand::{thread_rng, Rng};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-// TODO: Cache the ThreadRng for each thread.
+thread_local! {
+    /// The random number generator of each thread, seeded by the operating
+    /// system.
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
 
 /// Returns a random number in [0.0, 1.0).
 pub fn random() -> f64 {
-    thread_rng().gen_range(0.0..1.0)
+    RNG.with(|rng| rng.borrow_mut().gen_range(0.0..1.0))
 }
 
 /// Returns a random number in the given range.
 pub fn random_range(range: Range<f64>) -> f64 {
-    thread_rng().gen_range(range)
+    RNG.with(|rng| rng.borrow_mut().gen_range(range))
+}
+
+/// Seeds the random number generator of the current thread, so that the
+/// numbers it returns next are always the same.
+#[cfg(test)]
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
 }
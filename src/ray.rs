@@ -1,20 +1,25 @@
-use std::sync::Arc;
-
-use crate::{
-    color::Color,
-    hittable::Hittable,
-    vec3::{Point3, Vec3},
-};
+use crate::vec3::{Point3, Vec3};
 
 pub struct Ray {
     origin: Point3,
     direction: Vec3,
+    time: f64,
 }
 
 impl Ray {
-    /// Constructs a new `Ray`.
+    /// Constructs a new `Ray`, sent at time 0.
     pub fn new(origin: Point3, direction: Vec3) -> Self {
-        Ray { origin, direction }
+        Ray::with_time(origin, direction, 0.0)
+    }
+
+    /// Constructs a new `Ray` sent at `time`. Moving hittables are hit where
+    /// they are at that time.
+    pub fn with_time(origin: Point3, direction: Vec3, time: f64) -> Self {
+        Ray {
+            origin,
+            direction,
+            time,
+        }
     }
 
     /// Returns the origin of the vector.
@@ -27,33 +32,14 @@ impl Ray {
         self.direction
     }
 
+    /// Returns the time at which the ray is sent.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
     /// Returns the 3D position along the vector ; `t` is the distance from the
     /// [`Ray::origin`].
     pub fn at(&self, t: f64) -> Point3 {
         self.origin + t * self.direction
     }
-
-    /// Computes the color seen along a ray.
-    ///
-    /// This will try to hit anything in the `world`.
-    /// If nothing can be hit, returns a blue-to-white gradient depending on ray
-    /// Y coordinate.
-    pub fn color(&self, world: &Arc<dyn Hittable + Send + Sync>, depth: usize) -> Color {
-        if depth == 0 {
-            return Color::zero();
-        }
-
-        if let Some(hit) = world.try_hit(self, 0.001..=f64::INFINITY) {
-            if let Some((attenuation, scattered)) = hit.material.scatter(self, &hit) {
-                return attenuation * scattered.color(world, depth - 1);
-            }
-
-            return Color::zero();
-        }
-
-        let unit_direction = self.direction().normalized();
-        let t = 0.5 * (unit_direction.y() + 1.0);
-
-        (1.0 - t) * Color::new(1.0, 1.0, 1.0) + t * Color::new(0.5, 0.7, 1.0)
-    }
 }
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use crate::{
+    background::Background,
+    hittable::{Aabb, Hittable},
+    light::Light,
+    random::random,
+    vec3::{Point3, Vec3},
+};
+
+/// Everything rays can see: the hittables of the world, the background behind
+/// them, and the lights that can't be hit.
+pub struct Scene {
+    pub world: Arc<dyn Hittable + Send + Sync>,
+    pub background: Arc<dyn Background + Send + Sync>,
+    pub lights: Vec<Arc<dyn Light + Send + Sync>>,
+    /// The hittables of the world with an emissive material, which rays are
+    /// sent toward to find the light they emit.
+    pub emitters: Vec<Arc<dyn Hittable + Send + Sync>>,
+    /// Boxes holding the glass and metal objects of the world, which photons
+    /// are shot toward to find the caustics they cast.
+    pub photon_targets: Vec<Aabb>,
+}
+
+impl Scene {
+    /// Constructs a new `Scene`, without lights, emitters nor photon targets.
+    pub fn new(
+        world: Arc<dyn Hittable + Send + Sync>,
+        background: Arc<dyn Background + Send + Sync>,
+    ) -> Scene {
+        Scene {
+            world,
+            background,
+            lights: Vec::new(),
+            emitters: Vec::new(),
+            photon_targets: Vec::new(),
+        }
+    }
+
+    /// Sets the lights of the scene.
+    pub fn with_lights(self, lights: Vec<Arc<dyn Light + Send + Sync>>) -> Scene {
+        Scene { lights, ..self }
+    }
+
+    /// Sets the emitters of the scene. They must also be part of the world to
+    /// be seen.
+    pub fn with_emitters(self, emitters: Vec<Arc<dyn Hittable + Send + Sync>>) -> Scene {
+        Scene { emitters, ..self }
+    }
+
+    /// Sets the boxes photons are shot toward. They should hold every glass
+    /// and metal object casting caustics, but be as small as possible, as the
+    /// photons missing them are wasted.
+    pub fn with_photon_targets(self, photon_targets: Vec<Aabb>) -> Scene {
+        Scene {
+            photon_targets,
+            ..self
+        }
+    }
+
+    /// Returns the number of strategies to sample light from: one per emitter,
+    /// and one for the background if it can be sampled.
+    fn light_strategies(&self) -> usize {
+        self.emitters.len() + self.background.can_sample() as usize
+    }
+
+    /// Samples a direction from `origin` toward an emitter or the background,
+    /// each picked with the same probability. Returns [`None`] if the scene has
+    /// nothing to sample or the picked one can't be sampled.
+    pub fn sample_light_direction(&self, origin: Point3) -> Option<Vec3> {
+        let strategies = self.light_strategies();
+        if strategies == 0 {
+            return None;
+        }
+
+        let index = ((random() * strategies as f64) as usize).min(strategies - 1);
+        match self.emitters.get(index) {
+            Some(emitter) => emitter.sample_direction(origin),
+            None => self.background.sample().map(|(direction, _)| direction),
+        }
+    }
+
+    /// Returns the probability density with respect to solid angle of
+    /// [`Scene::sample_light_direction`] giving the `direction` from
+    /// `origin`.
+    pub fn light_pdf(&self, origin: Point3, direction: Vec3) -> f64 {
+        let strategies = self.light_strategies();
+        if strategies == 0 {
+            return 0.0;
+        }
+
+        let mut pdf: f64 = self
+            .emitters
+            .iter()
+            .map(|emitter| emitter.direction_pdf(origin, direction))
+            .sum();
+        if self.background.can_sample() {
+            pdf += self.background.pdf(direction);
+        }
+
+        pdf / strategies as f64
+    }
+}
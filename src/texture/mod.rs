@@ -0,0 +1,50 @@
+//! # Texture module
+//!
+//! Textures give the color of a material at any point of a surface.
+
+use crate::{color::Color, hittable::HitRecord};
+
+/// A texture, giving a color for each point of a surface.
+pub trait Texture {
+    /// Returns the color of the texture at the point of intersection of the
+    /// `record`.
+    fn value(&self, record: &HitRecord) -> Color;
+}
+
+/// A texture of a single color.
+pub struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    /// Constructs a new `SolidColor` texture.
+    pub fn new(color: Color) -> SolidColor {
+        SolidColor { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _record: &HitRecord) -> Color {
+        self.color
+    }
+}
+
+/// A texture using the vertex colors of the hit mesh (see
+/// [`HitRecord::vertex_color`]).
+pub struct VertexColor {
+    fallback: Color,
+}
+
+impl VertexColor {
+    /// Constructs a new `VertexColor` texture. The `fallback` color is used
+    /// where the surface has no vertex colors.
+    pub fn new(fallback: Color) -> VertexColor {
+        VertexColor { fallback }
+    }
+}
+
+impl Texture for VertexColor {
+    fn value(&self, record: &HitRecord) -> Color {
+        record.vertex_color.unwrap_or(self.fallback)
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     cmp::PartialEq,
-    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Range, Sub, SubAssign},
+    ops::{Add, AddAssign, Div, DivAssign, Index, Mul, MulAssign, Neg, Range, Sub, SubAssign},
 };
 
 use crate::random;
@@ -155,6 +155,60 @@ impl Vec3 {
             z: self.x * rhs.y - self.y * rhs.x,
         }
     }
+
+    /// Returns two unit vectors that form, with the vector, an orthonormal
+    /// basis. The vector must be normalized.
+    ///
+    /// See [Building an Orthonormal Basis, Revisited](https://jcgt.org/published/0006/01/01/),
+    /// Duff et al., 2017.
+    pub fn orthonormal_basis(&self) -> (Vec3, Vec3) {
+        let sign = 1.0_f64.copysign(self.z);
+        let a = -1.0 / (sign + self.z);
+        let b = self.x * self.y * a;
+
+        (
+            Vec3::new(1.0 + sign * self.x * self.x * a, sign * b, -sign * self.x),
+            Vec3::new(b, sign + self.y * self.y * a, -self.y),
+        )
+    }
+
+    /// Returns the vector made of the smallest components of the vector and
+    /// another.
+    pub fn min(&self, &rhs: &Vec3) -> Vec3 {
+        Vec3 {
+            x: self.x.min(rhs.x),
+            y: self.y.min(rhs.y),
+            z: self.z.min(rhs.z),
+        }
+    }
+
+    /// Returns the vector made of the largest components of the vector and
+    /// another.
+    pub fn max(&self, &rhs: &Vec3) -> Vec3 {
+        Vec3 {
+            x: self.x.max(rhs.x),
+            y: self.y.max(rhs.y),
+            z: self.z.max(rhs.z),
+        }
+    }
+}
+
+impl Index<usize> for Vec3 {
+    type Output = f64;
+
+    /// Returns the component along the `axis`: 0 for X, 1 for Y and 2 for Z.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `axis` is greater than 2.
+    fn index(&self, axis: usize) -> &f64 {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 axis out of bounds: {}", axis),
+        }
+    }
 }
 
 impl PartialEq for Vec3 {
@@ -340,6 +394,51 @@ mod test {
         );
     }
 
+    #[test]
+    fn orthonormal_basis_works() {
+        for n in &[
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(1.0, 2.0, -3.0).normalized(),
+        ] {
+            let (t, b) = n.orthonormal_basis();
+
+            assert!((t.length() - 1.0).abs() < 1e-12);
+            assert!((b.length() - 1.0).abs() < 1e-12);
+            assert!(t.dot(n).abs() < 1e-12);
+            assert!(b.dot(n).abs() < 1e-12);
+            assert!(t.dot(&b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn min_works() {
+        assert_eq!(
+            Vec3::new(1.0, 4.0, 3.0).min(&Vec3::new(2.0, 2.0, 3.0)),
+            Vec3::new(1.0, 2.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn max_works() {
+        assert_eq!(
+            Vec3::new(1.0, 4.0, 3.0).max(&Vec3::new(2.0, 2.0, 3.0)),
+            Vec3::new(2.0, 4.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn index_works() {
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!((v[0], v[1], v[2]), (1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds_panics() {
+        let _ = Vec3::zero()[3];
+    }
+
     #[test]
     fn negation_works() {
         assert_eq!(-Vec3::new(-1.0, -2.0, 3.0), Vec3::new(1.0, 2.0, -3.0));